@@ -1,50 +1,786 @@
-use crate::models::{AppPage, InputMode, PriceUpdate, Trade, TradeFilter};
-use std::collections::VecDeque;
+use crate::browser;
+use crate::cli;
+use crate::clipboard::{self, CopyTarget};
+use crate::command;
+use crate::config::{Config, DEFAULT_PRICE_THROTTLE_MS, DEFAULT_SPLIT_MIN_WIDTH, DEFAULT_VOLUME_SPIKE_MULTIPLIER, DEFAULT_VOLUME_SPIKE_WINDOW_SECS, DEFAULT_WHEEL_SCROLL_ROWS};
+use crate::state::{UiState, STATE_VERSION};
+use crate::portfolio::{PaperOrder, Portfolio, PortfolioSummary, Side};
+use crate::row_template::RowTemplate;
+use crate::history::HistoryPoint;
+use crate::events::{Event, EventLog, Severity, Source};
+use crate::notify::{DesktopNotifier, NotificationMode, TriggeredAlert};
+use crate::metrics::Metrics;
+use crate::models::{lossy_f64, round_to, AppPage, ClearScope, CoinSort, ColorBasis, ExportFormat, FlowBucket, GroupedTrade, InputMode, MuteKind, NumericInput, Position, PriceRange, PriceUpdate, Trade, TradeFilter, TradeSort};
+use crate::stats::{truncate_to_minute, SessionStats, SizeHistogram, TraderTotals};
+use crate::store::{SharedBuffer, DUPLICATE_WINDOW_SECS};
+use crate::theme::Theme;
+use crate::value_tier::ValueTier;
+use crate::websocket::{FeedState, GLOBAL_FEED, MAX_UNKNOWN_NAMES};
+use chrono::{DateTime, Local};
+use ratatui::layout::Rect;
+use rust_decimal::Decimal;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
 
-pub const MAX_TRADES: usize = 1000;
-pub const MAX_PRICE_UPDATES: usize = 100;
+/// Share of the width the trades pane starts with in split view, and the
+/// bounds and step for resizing it.
+pub const DEFAULT_SPLIT_PERCENT: u16 = 60;
+const MIN_SPLIT_PERCENT: u16 = 20;
+const MAX_SPLIT_PERCENT: u16 = 80;
+const SPLIT_STEP_PERCENT: u16 = 5;
+/// Second Ctrl+C within this window quits without confirmation.
+const FORCE_QUIT_WINDOW_SECS: i64 = 2;
+/// Rows moved by Shift+Up/Down.
+pub const FAST_SCROLL_ROWS: usize = 10;
+/// Filtered trades materialised beyond the bottom of the viewport. The cap
+/// is rounded up to a multiple of this so scrolling a few rows doesn't
+/// invalidate the cache.
+const RENDER_CAP_MARGIN: usize = 50;
+/// Viewport height assumed before the first frame has been drawn.
+const DEFAULT_VIEWPORT_ROWS: usize = 100;
+/// Trades older than this can no longer be folded into a large trade, so
+/// the size histogram counts them once. Twice the duplicate window leaves
+/// room for the publish delay.
+const HISTOGRAM_SETTLE_SECS: i64 = 2 * DUPLICATE_WINDOW_SECS;
+
+/// Everything that decides the contents of the filtered trades view. The
+/// cached result is reused for as long as none of these change.
+#[derive(Debug, Clone, PartialEq)]
+struct FilteredTradesKey {
+    generation: u64,
+    trade_filter: TradeFilter,
+    coin_filter: String,
+    trader_filter: String,
+    sort: TradeSort,
+    muted_coins: BTreeSet<String>,
+    muted_traders: BTreeSet<String>,
+    /// Grouping window in seconds, when grouping is on
+    grouping: Option<u64>,
+    avatar_only: bool,
+    /// Oldest received time (in seconds) still in view under `:since`
+    since_cutoff: Option<i64>,
+    render_cap: usize,
+}
+
+#[derive(Debug)]
+struct FilteredTradesCache {
+    key: FilteredTradesKey,
+    /// Only the first `render_cap` rows; see `matched_count` for the rest.
+    rows: Rc<Vec<GroupedTrade>>,
+    matched_count: usize,
+    muted_count: usize,
+}
+
+/// Positions in the filtered view of the trades matching a search, reused
+/// until the view or the query changes.
+#[derive(Debug)]
+struct SearchMatchesCache {
+    key: FilteredTradesKey,
+    query: String,
+    positions: Rc<Vec<usize>>,
+}
+
+/// Buys and sells in the view, reused until the view changes.
+#[derive(Debug)]
+struct BuySellCountsCache {
+    key: FilteredTradesKey,
+    counts: (usize, usize),
+}
+
+#[derive(Debug)]
+struct FollowedTradesCache {
+    generation: u64,
+    follow_list: BTreeSet<String>,
+    trades: Rc<Vec<Trade>>,
+}
+
+/// Everything besides new trades that changes which trades the size
+/// histogram counts.
+#[derive(Debug, Clone, PartialEq)]
+struct SizeHistogramKey {
+    trade_filter: TradeFilter,
+    coin_filter: String,
+    trader_filter: String,
+    muted_coins: BTreeSet<String>,
+    muted_traders: BTreeSet<String>,
+    avatar_only: bool,
+    /// The `:since` window; trades aging out of it are subtracted as they go
+    since: Option<Duration>,
+}
+
+/// Size histogram of the current view, updated incrementally: settled
+/// trades are counted once and subtracted again when they leave the buffer,
+/// and only the few newer ones are rescanned when a snapshot arrives.
+#[derive(Debug)]
+struct SizeHistogramCache {
+    key: SizeHistogramKey,
+    generation: Option<u64>,
+    settled: SizeHistogram,
+    /// Received time and value of every settled trade counted, newest first
+    counted: VecDeque<(DateTime<Local>, f64)>,
+    /// Received time of the newest settled trade looked at, counted or not
+    frontier: Option<DateTime<Local>>,
+    histogram: SizeHistogram,
+}
+
+/// Rows of the all-coins price table, reused until a price arrives or the
+/// sort changes.
+#[derive(Debug)]
+struct CoinTableCache {
+    generation: u64,
+    sort: CoinSort,
+    rows: Rc<Vec<PriceUpdate>>,
+}
+
+/// The tracked coin's whole history, reused until its history changes.
+#[derive(Debug)]
+struct PriceHistoryCache {
+    generation: u64,
+    coin: String,
+    points: Rc<Vec<HistoryPoint>>,
+}
+
+/// The tracked coin's history within its range, newest first. Shares the
+/// cached points rather than copying them.
+#[derive(Debug, Clone)]
+pub struct TrackedHistory {
+    points: Rc<Vec<HistoryPoint>>,
+    len: usize,
+}
+
+impl std::ops::Deref for TrackedHistory {
+    type Target = [HistoryPoint];
+
+    fn deref(&self) -> &[HistoryPoint] {
+        &self.points[..self.len]
+    }
+}
+
+/// While paused the app keeps reading the snapshots taken at pause time; the
+/// live buffers keep filling up in the background and are read again on resume.
+#[derive(Debug)]
+struct PauseState {
+    trades: Arc<Vec<Trade>>,
+    price_updates: Arc<Vec<PriceUpdate>>,
+    generation: u64,
+    since: DateTime<Local>,
+}
+
+/// What a click on a coin symbol or username in the trades pane filters to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuickFilter {
+    Coin(String),
+    Trader(String),
+}
+
+/// Where a coin symbol or username was last drawn in the trades pane.
+#[derive(Debug, Clone)]
+pub struct QuickFilterRegion {
+    pub area: Rect,
+    pub filter: QuickFilter,
+}
+
+/// A scrollbar as it was last drawn, so mouse clicks and drags on it can be
+/// mapped back to a scroll offset.
+#[derive(Debug, Clone)]
+pub struct ScrollbarTrack {
+    pub page: AppPage,
+    pub area: Rect,
+    pub content_len: usize,
+    /// Entries that fit on screen at once
+    pub visible_items: usize,
+}
+
+impl ScrollbarTrack {
+    fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.area.x && x < self.area.right() && y >= self.area.y && y < self.area.bottom()
+    }
+
+    /// Scroll offset proportional to where `y` falls along the track.
+    fn offset_at(&self, y: u16) -> usize {
+        let max_offset = self.content_len.saturating_sub(1);
+        let span = self.area.height.saturating_sub(1) as usize;
+        if span == 0 {
+            return 0;
+        }
+        let row = y.clamp(self.area.y, self.area.bottom().saturating_sub(1)) - self.area.y;
+        (row as usize * max_offset + span / 2) / span
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderField {
+    Coin,
+    Side,
+    Amount,
+}
+
+/// The paper order being typed on the Portfolio page.
+#[derive(Debug, Clone)]
+pub struct OrderForm {
+    pub coin: String,
+    pub side: Side,
+    /// Base currency to spend or receive
+    pub amount: String,
+    pub field: OrderField,
+    /// Why the last attempt to place it failed
+    pub error: Option<String>,
+}
 
 #[derive(Debug)]
 pub struct App {
-    pub trades: Arc<Mutex<VecDeque<Trade>>>,
-    pub price_updates: Arc<Mutex<VecDeque<PriceUpdate>>>,
+    pub trades: Arc<SharedBuffer<Trade>>,
+    pub price_updates: Arc<SharedBuffer<PriceUpdate>>,
+    /// Per-coin and per-trader aggregates, kept up to date by the receivers
+    pub session_stats: Arc<Mutex<SessionStats>>,
     pub current_page: AppPage,
+    /// When the Trades page was last left, for the missed-trades badge
+    trades_unseen_since: Option<DateTime<Local>>,
     pub trade_filter: TradeFilter,
     pub coin_filter: String,
     pub trader_filter: String,
     pub selected_tab: usize,
     pub input_mode: InputMode,
+    pub clear_scope: ClearScope,
+    pub help_scroll: usize,
+    /// Furthest the help overlay can scroll, filled in by the renderer
+    pub help_max_scroll: Cell<usize>,
+    /// Coin shown in the stats popup
+    pub stats_coin: Option<String>,
     pub input_buffer: String,
+    /// Why the last `:` command couldn't run
+    pub command_error: Option<String>,
+    /// Committed trades search; empty when there's none
+    pub search_query: String,
+    /// Where the list was when `/` was pressed, to go back to on Esc
+    search_origin: usize,
+    search_cache: RefCell<Option<SearchMatchesCache>>,
     pub scroll_offset: usize,
+    /// Scroll position of the unfocused pane in split view
+    pub other_pane_scroll: usize,
     pub tracked_coin: Option<String>,
     pub latest_price: Option<PriceUpdate>,
+    /// Simulated holdings by uppercase coin symbol, for this session only
+    pub positions: HashMap<String, Position>,
+    /// Paper trades against the streamed prices, saved after every fill
+    pub portfolio: Portfolio,
+    /// Where the portfolio is saved; `None` keeps it in memory only
+    pub portfolio_path: Option<PathBuf>,
+    pub order_form: OrderForm,
+    /// Page tabs as last drawn, for mouse clicks
+    pub page_tabs: RefCell<Vec<(Range<u16>, AppPage)>>,
+    pub compact_view: bool,
+    /// Collapse repeated trades by the same trader into one row
+    pub group_trades: bool,
+    /// Hide trades by traders without an avatar (session only)
+    pub avatar_only: bool,
+    /// Hide trades received longer ago than this (session only)
+    pub trade_since: Option<Duration>,
+    /// The oldest received time `trade_since` keeps in view, worked out
+    /// once per frame so every check in a frame agrees
+    since_cutoff: Cell<Option<DateTime<Local>>>,
+    /// Times render as HH:MM:SS.mmm
+    pub millisecond_timestamps: bool,
+    pub split_view: bool,
+    pub split_min_width: u16,
+    /// Width share of the trades pane in split view
+    pub split_percent: u16,
+    dragging_divider: bool,
+    /// First screen row of the drawn area; not 0 when drawing inline
+    pub viewport_top: Cell<u16>,
+    /// Trades and Price Tracker panes as last drawn, when split
+    pub split_panes: Cell<Option<(Rect, Rect)>>,
+    pub price_chart: bool,
+    /// History range picked for each coin tracked this session
+    price_ranges: HashMap<String, PriceRange>,
+    pub moving_averages: bool,
+    /// Price Tracker shows the latest price of every coin instead of one
+    pub all_coins_view: bool,
+    pub coin_sort: CoinSort,
+    pub color_basis: ColorBasis,
+    coin_table_cache: RefCell<Option<CoinTableCache>>,
+    price_history_cache: RefCell<Option<PriceHistoryCache>>,
+    pub trade_sort: TradeSort,
+    pub watchlist: BTreeSet<String>,
+    pub follow_list: BTreeSet<String>,
+    /// Show trades by followed traders in a pane below the trades list
+    pub follow_pane: bool,
+    pub follow_scroll: usize,
+    pub follow_list_index: usize,
+    /// Trades marked to come back to, newest first. Kept apart from the
+    /// trades buffer so they outlive its retention.
+    pub bookmarks: Vec<Trade>,
+    pub bookmark_list_index: usize,
+    /// Put trades by followed traders in the alert banner
+    pub follow_alerts: bool,
+    /// Where the followed-traders pane was last drawn, for wheel scrolling
+    pub follow_pane_area: Cell<Option<Rect>>,
+    followed_cache: RefCell<Option<FollowedTradesCache>>,
+    buy_sell_cache: RefCell<Option<BuySellCountsCache>>,
+    /// Show the trade size histogram above the trades list
+    pub size_histogram: bool,
+    /// Show per-minute traded value above the trades list
+    pub volume_chart: bool,
+    /// Show the busiest coins by trade count above the trades list
+    pub coin_counts_chart: bool,
+    /// Show the buy/sell split of the trades in view above the trades list
+    pub buy_sell_mix: bool,
+    /// Show the feed status line above the help bar
+    pub status_bar: bool,
+    /// Counters kept by the receivers for the status bar
+    pub metrics: Arc<Metrics>,
+    last_input: Instant,
+    /// Set while nothing is happening; the UI is drawn dimmed
+    pub idle: bool,
+    /// Interval the price receiver coalesces each coin's updates over
+    pub price_throttle: watch::Sender<Duration>,
+    size_histogram_cache: RefCell<Option<SizeHistogramCache>>,
+    pub muted_coins: BTreeSet<String>,
+    pub muted_traders: BTreeSet<String>,
+    pub mute_list_index: usize,
+    pub coin_list: Vec<String>,
+    pub coin_list_index: usize,
+    /// Keep the coin filter and the tracked coin in step (session only)
+    pub coin_link: bool,
+    /// Tracked coin offered as the coin filter while `coin_link` is on
+    pub link_filter_offer: Option<String>,
+    pub config_path: Option<PathBuf>,
+    loaded_config: Config,
+    pub volume_spike_multiplier: f64,
+    pub volume_spike_window_secs: u64,
+    pub wheel_scroll_rows: usize,
+    pub confirm_quit: bool,
+    /// The feed lag warning is out and waits for the lag to recover
+    feed_lag_warned: bool,
+    last_ctrl_c: Option<DateTime<Local>>,
+    /// Alerts, feed events and failures, newest first
+    pub events: EventLog,
+    pub event_log_scroll: usize,
+    /// Set with `--desktop-notifications`
+    desktop_notifier: Option<DesktopNotifier>,
+    /// What the trade receiver pipes to the `--exec` command
+    pub exec_filter: watch::Sender<Option<ExecFilter>>,
+    /// Dark or light palette, picked at startup
+    pub theme: Theme,
+    /// Message types the feed sent that the handler doesn't recognise, up to
+    /// `MAX_UNKNOWN_NAMES`
+    pub unknown_message_types: BTreeSet<String>,
+    /// Trade fields the feed sent that `TradeData` has no field for, up to
+    /// `MAX_UNKNOWN_NAMES`
+    pub unknown_trade_fields: BTreeSet<String>,
+    last_spike_alert: HashMap<String, DateTime<Local>>,
+    spike_checked_until: Option<DateTime<Local>>,
+    pause: Option<PauseState>,
+    pub status_message: Option<(String, DateTime<Local>)>,
+    /// When a numeric prompt last refused a keystroke, for a brief flash
+    pub rejected_input_at: Option<DateTime<Local>>,
+    filtered_cache: RefCell<Option<FilteredTradesCache>>,
+    /// Rows available to the trades list, filled in by the renderer
+    pub trades_viewport_rows: Cell<usize>,
+    /// Filled in by the renderer each frame
+    pub scrollbar_tracks: RefCell<Vec<ScrollbarTrack>>,
+    /// Clickable coin symbols and usernames, filled in by the renderer
+    pub quick_filter_regions: RefCell<Vec<QuickFilterRegion>>,
+    dragging_scrollbar: Option<AppPage>,
+    /// The last frame only had room for the "terminal too small" notice
+    pub too_small: Cell<bool>,
+    /// When the current feed connection came up; `None` while disconnected
+    pub connected_since: Option<Instant>,
+    /// The feed connection's state as last reported, shown in the status bar
+    pub feed_state: FeedState,
 }
 
 impl App {
-    pub fn new(trades: Arc<Mutex<VecDeque<Trade>>>, price_updates: Arc<Mutex<VecDeque<PriceUpdate>>>) -> Self {
+    pub fn new(
+        trades: Arc<SharedBuffer<Trade>>,
+        price_updates: Arc<SharedBuffer<PriceUpdate>>,
+        session_stats: Arc<Mutex<SessionStats>>,
+    ) -> Self {
         Self {
             trades,
             price_updates,
+            session_stats,
             current_page: AppPage::Trades,
+            trades_unseen_since: None,
             trade_filter: TradeFilter::All,
             coin_filter: String::new(),
             trader_filter: String::new(),
             selected_tab: 0,
             input_mode: InputMode::Normal,
+            clear_scope: ClearScope::Page,
+            help_scroll: 0,
+            help_max_scroll: Cell::new(0),
+            stats_coin: None,
             input_buffer: String::new(),
+            command_error: None,
+            search_query: String::new(),
+            bookmarks: Vec::new(),
+            bookmark_list_index: 0,
+            portfolio: Portfolio::default(),
+            portfolio_path: None,
+            order_form: OrderForm { coin: String::new(), side: Side::Buy, amount: String::new(), field: OrderField::Coin, error: None },
+            page_tabs: RefCell::new(Vec::new()),
+            search_origin: 0,
+            search_cache: RefCell::new(None),
             scroll_offset: 0,
+            other_pane_scroll: 0,
             tracked_coin: None,
             latest_price: None,
+            positions: HashMap::new(),
+            compact_view: false,
+            group_trades: false,
+            avatar_only: false,
+            trade_since: None,
+            since_cutoff: Cell::new(None),
+            millisecond_timestamps: false,
+            split_view: false,
+            split_min_width: DEFAULT_SPLIT_MIN_WIDTH,
+            split_percent: DEFAULT_SPLIT_PERCENT,
+            dragging_divider: false,
+            split_panes: Cell::new(None),
+            viewport_top: Cell::new(0),
+            price_chart: false,
+            price_ranges: HashMap::new(),
+            moving_averages: true,
+            all_coins_view: false,
+            coin_sort: CoinSort::Volume,
+            color_basis: ColorBasis::Change24h,
+            coin_table_cache: RefCell::new(None),
+            price_history_cache: RefCell::new(None),
+            trade_sort: TradeSort::Newest,
+            watchlist: BTreeSet::new(),
+            follow_list: BTreeSet::new(),
+            follow_pane: false,
+            follow_scroll: 0,
+            follow_list_index: 0,
+            follow_alerts: true,
+            follow_pane_area: Cell::new(None),
+            followed_cache: RefCell::new(None),
+            buy_sell_cache: RefCell::new(None),
+            size_histogram: false,
+            volume_chart: false,
+            coin_counts_chart: false,
+            buy_sell_mix: false,
+            status_bar: true,
+            metrics: Arc::default(),
+            last_input: Instant::now(),
+            idle: false,
+            price_throttle: watch::channel(Duration::from_millis(DEFAULT_PRICE_THROTTLE_MS)).0,
+            size_histogram_cache: RefCell::new(None),
+            muted_coins: BTreeSet::new(),
+            muted_traders: BTreeSet::new(),
+            mute_list_index: 0,
+            coin_list: Vec::new(),
+            coin_list_index: 0,
+            coin_link: false,
+            link_filter_offer: None,
+            config_path: None,
+            loaded_config: Config::default(),
+            volume_spike_multiplier: DEFAULT_VOLUME_SPIKE_MULTIPLIER,
+            volume_spike_window_secs: DEFAULT_VOLUME_SPIKE_WINDOW_SECS,
+            wheel_scroll_rows: DEFAULT_WHEEL_SCROLL_ROWS,
+            confirm_quit: true,
+            feed_lag_warned: false,
+            last_ctrl_c: None,
+            events: EventLog::default(),
+            event_log_scroll: 0,
+            unknown_message_types: BTreeSet::new(),
+            unknown_trade_fields: BTreeSet::new(),
+            desktop_notifier: None,
+            exec_filter: watch::channel(None).0,
+            theme: Theme::default(),
+            last_spike_alert: HashMap::new(),
+            spike_checked_until: None,
+            pause: None,
+            status_message: None,
+            rejected_input_at: None,
+            filtered_cache: RefCell::new(None),
+            trades_viewport_rows: Cell::new(DEFAULT_VIEWPORT_ROWS),
+            scrollbar_tracks: RefCell::new(Vec::new()),
+            quick_filter_regions: RefCell::new(Vec::new()),
+            dragging_scrollbar: None,
+            too_small: Cell::new(false),
+            connected_since: None,
+            feed_state: FeedState::Connecting,
+        }
+    }
+
+    pub fn apply_config(&mut self, config: Config, path: Option<PathBuf>) {
+        self.watchlist = config.watchlist.clone();
+        self.follow_list = config.follow_list.clone();
+        self.muted_coins = config.muted_coins.clone();
+        self.muted_traders = config.muted_traders.clone();
+        self.compact_view = config.compact_view;
+        self.group_trades = config.group_trades;
+        self.millisecond_timestamps = config.millisecond_timestamps;
+        self.split_view = config.split_view;
+        self.split_min_width = config.split_min_width;
+        self.follow_pane = config.follow_pane;
+        self.size_histogram = config.size_histogram;
+        self.volume_chart = config.volume_chart;
+        self.coin_counts_chart = config.coin_counts_chart;
+        self.buy_sell_mix = config.buy_sell_mix;
+        self.status_bar = config.status_bar;
+        self.follow_alerts = config.follow_alerts;
+        self.price_chart = config.price_chart;
+        self.moving_averages = config.moving_averages;
+        self.trade_sort = config.trade_sort;
+        self.coin_sort = config.coin_sort;
+        self.color_basis = config.color_basis;
+        self.price_throttle.send_replace(Duration::from_millis(config.price_throttle_ms));
+        self.volume_spike_multiplier = config.volume_spike_multiplier;
+        self.volume_spike_window_secs = config.volume_spike_window_secs;
+        self.wheel_scroll_rows = config.wheel_scroll_rows.max(1);
+        self.confirm_quit = config.confirm_quit;
+        self.loaded_config = config;
+        self.config_path = path;
+    }
+
+    /// What to bring back next session. Filters and bookmarks are left
+    /// out unless `persist_filters` / `persist_bookmarks` are on, so stale
+    /// ones can't linger in the file.
+    pub fn ui_state(&self) -> UiState {
+        let persist_filters = self.loaded_config.persist_filters;
+        UiState {
+            version: STATE_VERSION,
+            tracked_coin: self.tracked_coin.clone(),
+            coin_filter: if persist_filters { self.coin_filter.clone() } else { String::new() },
+            trader_filter: if persist_filters { self.trader_filter.clone() } else { String::new() },
+            trade_filter: self.trade_filter.clone(),
+            page: self.current_page.clone(),
+            split_percent: self.split_percent,
+            bookmarks: if self.loaded_config.persist_bookmarks { self.bookmarks.clone() } else { Vec::new() },
+        }
+    }
+
+    /// Restores a saved session on top of the applied config. Returns the
+    /// tracked coin, which the feed has to be switched to.
+    pub fn restore_ui_state(&mut self, state: UiState) -> Option<String> {
+        self.tracked_coin = state.tracked_coin;
+        if self.loaded_config.persist_filters {
+            self.coin_filter = state.coin_filter;
+            self.trader_filter = state.trader_filter;
+        }
+        self.trade_filter = state.trade_filter;
+        self.current_page = state.page;
+        self.split_percent = state.split_percent.clamp(MIN_SPLIT_PERCENT, MAX_SPLIT_PERCENT);
+        if self.loaded_config.persist_bookmarks {
+            self.bookmarks = state.bookmarks;
+        }
+        self.tracked_coin.clone()
+    }
+
+    /// The loaded config with the fields the app edits at runtime replaced by
+    /// their current values; everything else is written back untouched.
+    pub fn to_config(&self) -> Config {
+        Config {
+            watchlist: self.watchlist.clone(),
+            follow_list: self.follow_list.clone(),
+            muted_coins: self.muted_coins.clone(),
+            muted_traders: self.muted_traders.clone(),
+            compact_view: self.compact_view,
+            group_trades: self.group_trades,
+            millisecond_timestamps: self.millisecond_timestamps,
+            split_view: self.split_view,
+            follow_pane: self.follow_pane,
+            size_histogram: self.size_histogram,
+            volume_chart: self.volume_chart,
+            coin_counts_chart: self.coin_counts_chart,
+            buy_sell_mix: self.buy_sell_mix,
+            status_bar: self.status_bar,
+            price_chart: self.price_chart,
+            moving_averages: self.moving_averages,
+            trade_sort: self.trade_sort,
+            coin_sort: self.coin_sort,
+            color_basis: self.color_basis,
+            price_throttle_ms: self.price_throttle.borrow().as_millis() as u64,
+            volume_spike_multiplier: self.volume_spike_multiplier,
+            volume_spike_window_secs: self.volume_spike_window_secs,
+            ..self.loaded_config.clone()
+        }
+    }
+
+    /// Writes the persisted parts of the app state back to the config file,
+    /// if one is configured.
+    pub fn save_config(&mut self) {
+        let Some(ref path) = self.config_path else {
+            return;
+        };
+        if let Err(e) = self.to_config().save(path) {
+            let message = format!("Couldn't save the config to {}: {}", path.display(), e);
+            self.log_event(Event::new(Severity::Error, Source::Storage, message));
         }
     }
 
     pub fn switch_page(&mut self) {
-        self.current_page = match self.current_page {
+        let next = match self.current_page {
             AppPage::Trades => AppPage::PriceTracker,
-            AppPage::PriceTracker => AppPage::Trades,
+            // In split view `p` only moves the focus between the two panes
+            AppPage::PriceTracker if self.split_panes.get().is_some() => AppPage::Trades,
+            AppPage::PriceTracker => AppPage::Portfolio,
+            AppPage::Portfolio => AppPage::Trades,
         };
-        self.scroll_offset = 0;
+        self.show_page(next);
+    }
+
+    /// Opens the Portfolio page, or goes back to the trades from it.
+    pub fn toggle_portfolio(&mut self) {
+        if self.current_page == AppPage::Portfolio {
+            self.show_page(AppPage::Trades);
+        } else {
+            self.show_page(AppPage::Portfolio);
+        }
+    }
+
+    pub fn show_page(&mut self, page: AppPage) {
+        if page == self.current_page {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.current_page, page.clone());
+        self.trades_unseen_since = match self.current_page {
+            AppPage::Trades => None,
+            _ => self.trades_unseen_since.or(Some(Local::now())),
+        };
+        // Both panes stay on screen in split view, so each keeps its place.
+        // The Portfolio page is never split.
+        let both_on_screen = self.split_panes.get().is_some() && previous != AppPage::Portfolio && page != AppPage::Portfolio;
+        if both_on_screen {
+            std::mem::swap(&mut self.scroll_offset, &mut self.other_pane_scroll);
+        } else {
+            self.scroll_offset = 0;
+            self.other_pane_scroll = 0;
+        }
+    }
+
+    /// The page whose tab was drawn at column `x`.
+    pub fn page_tab_at(&self, x: u16) -> Option<AppPage> {
+        self.page_tabs
+            .borrow()
+            .iter()
+            .find(|(columns, _)| columns.contains(&x))
+            .map(|(_, page)| page.clone())
+    }
+
+    /// Scroll offset of the pane showing `page`, focused or not.
+    pub fn pane_scroll_offset(&self, page: &AppPage) -> usize {
+        if *page == self.current_page {
+            self.scroll_offset
+        } else {
+            self.other_pane_scroll
+        }
+    }
+
+    /// The split-view pane under (`x`, `y`), if any.
+    pub fn pane_at(&self, x: u16, y: u16) -> Option<AppPage> {
+        let (trades, prices) = self.split_panes.get()?;
+        let position = ratatui::layout::Position::new(x, y);
+        if trades.contains(position) {
+            Some(AppPage::Trades)
+        } else if prices.contains(position) {
+            Some(AppPage::PriceTracker)
+        } else {
+            None
+        }
+    }
+
+    /// Moves the split-view divider by `steps` resize steps; negative steps
+    /// shrink the trades pane.
+    pub fn resize_split(&mut self, steps: i16) {
+        let percent = self.split_percent as i16 + steps * SPLIT_STEP_PERCENT as i16;
+        self.set_split_percent(percent.clamp(MIN_SPLIT_PERCENT as i16, MAX_SPLIT_PERCENT as i16) as u16);
+    }
+
+    fn set_split_percent(&mut self, percent: u16) {
+        self.split_percent = percent.clamp(MIN_SPLIT_PERCENT, MAX_SPLIT_PERCENT);
+        self.clamp_scroll_offsets();
+    }
+
+    /// Starts a divider drag if (`x`, `y`) is on the price pane's left
+    /// border. The trades pane's right border holds its scrollbar.
+    pub fn press_divider(&mut self, x: u16, y: u16) -> bool {
+        let Some((_, prices)) = self.split_panes.get() else {
+            return false;
+        };
+        let on_divider = x == prices.x && y >= prices.y && y < prices.bottom();
+        self.dragging_divider = on_divider;
+        on_divider
+    }
+
+    /// Follows the mouse while the divider is being dragged.
+    pub fn drag_divider(&mut self, x: u16) {
+        if !self.dragging_divider {
+            return;
+        }
+        if let Some((trades, prices)) = self.split_panes.get() {
+            let total = (trades.width + prices.width).max(1) as u32;
+            let percent = (x.saturating_sub(trades.x) as u32 * 100 + total / 2) / total;
+            self.set_split_percent(percent as u16);
+        }
+    }
+
+    pub fn release_divider(&mut self) {
+        self.dragging_divider = false;
+    }
+
+    /// Entries in the list shown on `page`.
+    fn pane_len(&self, page: &AppPage) -> usize {
+        match page {
+            AppPage::Trades => self.filtered_trade_count(),
+            AppPage::PriceTracker if self.all_coins_view => self.coin_table().len(),
+            AppPage::PriceTracker => self.tracked_price_history().len(),
+            AppPage::Portfolio => self.portfolio.orders.len(),
+        }
+    }
+
+    /// Pulls both panes' offsets back inside their lists, so a layout change
+    /// never leaves either one past its last entry.
+    pub fn clamp_scroll_offsets(&mut self) {
+        let other_page = match self.current_page {
+            AppPage::Trades => AppPage::PriceTracker,
+            _ => AppPage::Trades,
+        };
+        self.scroll_offset = self.scroll_offset.min(self.pane_len(&self.current_page).saturating_sub(1));
+        self.other_pane_scroll = self.other_pane_scroll.min(self.pane_len(&other_page).saturating_sub(1));
+    }
+
+    /// Forgets everything the renderer recorded about the old layout, so no
+    /// click lands on where a tab or scrollbar used to be, and pulls both
+    /// panes' offsets back inside their lists. The next draw records the new
+    /// layout.
+    pub fn handle_resize(&mut self) {
+        self.page_tabs.borrow_mut().clear();
+        self.scrollbar_tracks.borrow_mut().clear();
+        self.quick_filter_regions.borrow_mut().clear();
+        self.split_panes.set(None);
+        self.follow_pane_area.set(None);
+        self.dragging_scrollbar = None;
+        self.dragging_divider = false;
+        self.clamp_scroll_offsets();
+    }
+
+    /// Moves keyboard focus to the split-view pane under (`x`, `y`).
+    pub fn focus_pane_at(&mut self, x: u16, y: u16) {
+        if self.pane_at(x, y).is_some_and(|page| page != self.current_page) {
+            self.switch_page();
+        }
+    }
+
+    /// Trades received since the Trades page was last left.
+    pub fn unseen_trade_count(&self) -> usize {
+        let Some(since) = self.trades_unseen_since else {
+            return 0;
+        };
+        self.trades
+            .snapshot()
+            .iter()
+            .take_while(|trade| trade.received_at > since)
+            .count()
     }
 
     pub fn start_coin_selection(&mut self) {
@@ -52,16 +788,81 @@ impl App {
         self.input_buffer = self.tracked_coin.clone().unwrap_or_default();
     }
 
+    /// Symbols seen in the trade and price buffers, most recent first.
+    pub fn known_coins(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut coins = Vec::new();
+        for trade in self.trades_snapshot().iter() {
+            if seen.insert(trade.data.coin_symbol.clone()) {
+                coins.push(trade.data.coin_symbol.clone());
+            }
+        }
+        for update in self.price_updates_snapshot().iter() {
+            if seen.insert(update.coin_symbol.clone()) {
+                coins.push(update.coin_symbol.clone());
+            }
+        }
+        coins
+    }
+
+    /// Opens the coin picker on a snapshot of known coins so the list
+    /// doesn't shift under the cursor while new trades arrive.
+    pub fn open_coin_list(&mut self) {
+        self.coin_list = self.known_coins();
+        self.coin_list_index = self
+            .tracked_coin
+            .as_ref()
+            .and_then(|tracked| self.coin_list.iter().position(|coin| coin == tracked))
+            .unwrap_or(0);
+        self.input_mode = InputMode::CoinList;
+    }
+
+    pub fn coin_list_up(&mut self) {
+        self.coin_list_index = self.coin_list_index.saturating_sub(1);
+    }
+
+    pub fn coin_list_down(&mut self) {
+        if self.coin_list_index + 1 < self.coin_list.len() {
+            self.coin_list_index += 1;
+        }
+    }
+
+    pub fn confirm_coin_list_selection(&mut self) -> Option<String> {
+        match self.coin_list.get(self.coin_list_index).cloned() {
+            Some(coin) => {
+                self.input_buffer = coin;
+                self.confirm_coin_selection()
+            }
+            None => {
+                self.input_mode = InputMode::Normal;
+                None
+            }
+        }
+    }
+
     pub fn confirm_coin_selection(&mut self) -> Option<String> {
-        if !self.input_buffer.trim().is_empty() {
-            self.tracked_coin = Some(self.input_buffer.trim().to_uppercase());
-            self.input_mode = InputMode::Normal;
+        self.input_mode = InputMode::Normal;
+        let symbol = self.input_buffer.clone();
+        self.track_coin(&symbol)
+    }
+
+    /// Points the Price Tracker at `symbol`. Returns the coin the feed should
+    /// switch to, or `None` for a blank symbol.
+    pub fn track_coin(&mut self, symbol: &str) -> Option<String> {
+        let coin = symbol.trim().to_uppercase();
+        if coin.is_empty() {
+            return None;
+        }
+        self.tracked_coin = Some(coin.clone());
+        self.latest_price = None;
+        self.all_coins_view = false;
+        if self.current_page == AppPage::PriceTracker {
             self.scroll_offset = 0;
-            self.latest_price = None;
-            return Some(self.input_buffer.trim().to_uppercase());
+        } else {
+            self.other_pane_scroll = 0;
         }
-        self.input_mode = InputMode::Normal;
-        None
+        self.offer_linked_filter();
+        Some(coin)
     }
 
     pub fn update_latest_price(&mut self, price_update: PriceUpdate) {
@@ -72,94 +873,3018 @@ impl App {
         }
     }
 
-    pub fn get_tracked_price_updates(&self) -> Vec<PriceUpdate> {
-        if let Some(ref tracked) = self.tracked_coin {
-            let updates = self.price_updates.lock().unwrap();
-            updates
-                .iter()
-                .filter(|update| update.coin_symbol == *tracked)
-                .cloned()
-                .collect()
+    /// The tracked coin's price history within its range, newest first:
+    /// recent updates one by one, older ones as minute and five-minute bars.
+    pub fn tracked_price_history(&self) -> TrackedHistory {
+        let Some(ref tracked) = self.tracked_coin else {
+            return TrackedHistory { points: Rc::default(), len: 0 };
+        };
+        let points = self.price_history_points(tracked);
+        let len = match self.price_range().duration() {
+            Some(range) => {
+                let since = Local::now() - range;
+                points.partition_point(|point| point.update.received_at >= since)
+            }
+            None => points.len(),
+        };
+        TrackedHistory { points, len }
+    }
+
+    /// All of `coin`'s history, newest first, copied out of the stats only
+    /// when it has changed since the last call.
+    fn price_history_points(&self, coin: &str) -> Rc<Vec<HistoryPoint>> {
+        let stats = self.session_stats.lock().unwrap();
+        let generation = stats.history_generation();
+        if let Some(ref cache) = *self.price_history_cache.borrow() {
+            if cache.generation == generation && cache.coin == coin {
+                return cache.points.clone();
+            }
+        }
+        let points = Rc::new(stats.price_history(coin, None));
+        drop(stats);
+        *self.price_history_cache.borrow_mut() = Some(PriceHistoryCache {
+            generation,
+            coin: coin.to_string(),
+            points: points.clone(),
+        });
+        points
+    }
+
+    /// The history range of the tracked coin; all of it until one is picked.
+    pub fn price_range(&self) -> PriceRange {
+        self.tracked_coin
+            .as_ref()
+            .and_then(|coin| self.price_ranges.get(coin))
+            .copied()
+            .unwrap_or(PriceRange::All)
+    }
+
+    pub fn select_price_range(&mut self, range: PriceRange) {
+        let Some(coin) = self.tracked_coin.clone() else {
+            return;
+        };
+        self.price_ranges.insert(coin, range);
+        self.clamp_scroll_offsets();
+    }
+
+    pub fn coin_filter_list(&self) -> Vec<String> {
+        parse_coin_filter(self.active_coin_filter())
+    }
+
+    /// The coin filter the view applies: what's being typed while the coin
+    /// filter input is open, otherwise the committed filter.
+    fn active_coin_filter(&self) -> &str {
+        if self.input_mode == InputMode::CoinFilter {
+            &self.input_buffer
         } else {
-            Vec::new()
+            &self.coin_filter
         }
     }
 
-    pub fn filtered_trades(&self) -> Vec<Trade> {
-        let trades = self.trades.lock().unwrap();
-        trades
-            .iter()
-            .filter(|trade| {
-                let type_match = match self.trade_filter {
-                    TradeFilter::All => trade.msg_type == "all-trades",
-                    TradeFilter::Large => trade.msg_type == "live-trade",
-                };
-                
-                let coin_match = self.coin_filter.is_empty() 
-                    || trade.data.coin_symbol.to_lowercase().contains(&self.coin_filter.to_lowercase());
-                
-                let trader_match = self.trader_filter.is_empty() 
-                    || trade.data.username.to_lowercase().contains(&self.trader_filter.to_lowercase());
-                
-                type_match && coin_match && trader_match
-            })
-            .cloned()
-            .collect()
+    /// Same as [`Self::active_coin_filter`] for the trader filter.
+    fn active_trader_filter(&self) -> &str {
+        if self.input_mode == InputMode::TraderFilter {
+            &self.input_buffer
+        } else {
+            &self.trader_filter
+        }
     }
 
-    pub fn scroll_up(&mut self) {
-        if self.scroll_offset > 0 {
-            self.scroll_offset -= 1;
+    fn filtered_trades_key(&self) -> FilteredTradesKey {
+        FilteredTradesKey {
+            generation: self.view_generation(),
+            trade_filter: self.trade_filter.clone(),
+            coin_filter: self.active_coin_filter().to_string(),
+            trader_filter: self.active_trader_filter().to_string(),
+            sort: self.trade_sort,
+            muted_coins: self.muted_coins.clone(),
+            muted_traders: self.muted_traders.clone(),
+            grouping: self.group_trades.then_some(self.loaded_config.group_window_secs),
+            avatar_only: self.avatar_only,
+            since_cutoff: self.since_cutoff.get().map(|cutoff| cutoff.timestamp()),
+            render_cap: self.render_cap(),
         }
     }
 
-    pub fn scroll_down(&mut self) {
-        let max_items = match self.current_page {
-            AppPage::Trades => self.filtered_trades().len(),
-            AppPage::PriceTracker => self.get_tracked_price_updates().len(),
+    /// How many filtered trades to materialise: enough to fill the viewport
+    /// at the current scroll offset plus a margin, so the work per frame
+    /// scales with the screen rather than the buffer.
+    fn render_cap(&self) -> usize {
+        let needed = self.pane_scroll_offset(&AppPage::Trades) + self.trades_viewport_rows.get() + RENDER_CAP_MARGIN;
+        needed.div_ceil(RENDER_CAP_MARGIN) * RENDER_CAP_MARGIN
+    }
+
+    /// Filtered and sorted rows from the top of the list down to just past
+    /// the viewport, one per trade or, with grouping on, per run of repeated
+    /// trades. Recomputed only when new trades arrive, the
+    /// filters/sort/mutes/grouping change or scrolling moves past the cap.
+    pub fn grouped_trades(&self) -> Rc<Vec<GroupedTrade>> {
+        self.refresh_filtered_cache();
+        self.filtered_cache.borrow().as_ref().unwrap().rows.clone()
+    }
+
+    /// Number of rows in the current view, including those past the render
+    /// cap.
+    pub fn filtered_trade_count(&self) -> usize {
+        self.refresh_filtered_cache();
+        self.filtered_cache.borrow().as_ref().unwrap().matched_count
+    }
+
+    /// Number of trades in the current tab that are hidden only by mutes.
+    pub fn muted_trade_count(&self) -> usize {
+        self.refresh_filtered_cache();
+        self.filtered_cache.borrow().as_ref().unwrap().muted_count
+    }
+
+    fn refresh_filtered_cache(&self) {
+        let key = self.filtered_trades_key();
+        if let Some(ref cache) = *self.filtered_cache.borrow() {
+            if cache.key == key {
+                return;
+            }
+        }
+
+        let (rows, matched_count, muted_count) = self.compute_filtered_trades(key.render_cap);
+        *self.filtered_cache.borrow_mut() = Some(FilteredTradesCache {
+            key,
+            rows: Rc::new(rows),
+            matched_count,
+            muted_count,
+        });
+    }
+
+    fn is_muted(&self, trade: &Trade) -> bool {
+        self.muted_coins.contains(&trade.data.coin_symbol.to_uppercase())
+            || self.muted_traders.contains(&trade.data.username.to_lowercase())
+    }
+
+    /// The search the view highlights: what's being typed while the search
+    /// input is open, otherwise the committed search.
+    pub fn active_search(&self) -> &str {
+        if self.input_mode == InputMode::Search {
+            &self.input_buffer
+        } else {
+            &self.search_query
+        }
+    }
+
+    /// Positions in the filtered trades, including those past the render
+    /// cap, of every trade matching the active search.
+    pub fn search_matches(&self) -> Rc<Vec<usize>> {
+        let query = self.active_search().to_lowercase();
+        if query.is_empty() {
+            return Rc::default();
+        }
+        // The matches cover the whole view, so scrolling doesn't change them
+        let key = FilteredTradesKey { render_cap: usize::MAX, ..self.filtered_trades_key() };
+        if let Some(ref cache) = *self.search_cache.borrow() {
+            if cache.key == key && cache.query == query {
+                return cache.positions.clone();
+            }
+        }
+
+        let trades = self.trades_snapshot();
+        let (view, _, _) = self.sorted_view(&trades, self.group_trades, None);
+        let positions: Rc<Vec<usize>> = Rc::new(
+            view.iter()
+                .enumerate()
+                .filter(|(_, run)| trade_matches_search(run.trade, &query))
+                .map(|(position, _)| position)
+                .collect(),
+        );
+        *self.search_cache.borrow_mut() = Some(SearchMatchesCache { key, query, positions: positions.clone() });
+        positions
+    }
+
+    /// Which match the selected trade is (1-based, `None` if it isn't one)
+    /// and how many there are, while a search is active.
+    pub fn search_position(&self) -> Option<(Option<usize>, usize)> {
+        if self.active_search().is_empty() {
+            return None;
+        }
+        let matches = self.search_matches();
+        let selected = self.pane_scroll_offset(&AppPage::Trades);
+        let index = matches.binary_search(&selected).ok().map(|index| index + 1);
+        Some((index, matches.len()))
+    }
+
+    /// Whether a trade belongs in the current view, ignoring sort order.
+    fn is_visible(&self, trade: &Trade) -> bool {
+        matches_trade_type(trade, &self.trade_filter)
+            && !self.is_muted(trade)
+            && filter_trade(trade, &self.trade_filter, self.active_coin_filter(), self.active_trader_filter())
+            && self.passes_avatar_filter(trade)
+            && self.since_cutoff.get().is_none_or(|cutoff| trade.received_at >= cutoff)
+    }
+
+    fn passes_avatar_filter(&self, trade: &Trade) -> bool {
+        !self.avatar_only || trade.data.has_avatar(&self.loaded_config.placeholder_avatars)
+    }
+
+    /// Moves the `:since` cutoff up to `now`, so trades drop out of the view
+    /// as they age past the window. The renderer calls this with its clock
+    /// reading at the start of every frame.
+    pub fn begin_frame(&self, now: DateTime<Local>) {
+        let window = self.trade_since.and_then(|window| chrono::Duration::from_std(window).ok());
+        self.since_cutoff.set(window.map(|window| now - window));
+    }
+
+    /// Whether a trade is old enough to be drawn dimmed at `now`.
+    pub fn is_aged(&self, trade: &Trade, now: DateTime<Local>) -> bool {
+        let age = self.loaded_config.trade_dim_age_secs;
+        age > 0 && now - trade.received_at > chrono::Duration::seconds(age as i64)
+    }
+
+    /// Returns the first `cap` rows of the view along with the total number
+    /// of rows and the number of trades hidden by mutes. Rows past the cap
+    /// are only counted, never cloned.
+    fn compute_filtered_trades(&self, cap: usize) -> (Vec<GroupedTrade>, usize, usize) {
+        let trades = self.trades_snapshot();
+        let (matched, matched_count, muted_count) = self.sorted_view(&trades, self.group_trades, Some(cap));
+        let filtered = matched.iter().take(cap).map(|run| run.to_grouped()).collect();
+        (filtered, matched_count, muted_count)
+    }
+
+    /// Every individual trade of the view in display order, ignoring
+    /// grouping.
+    fn view_trades(&self) -> Vec<Trade> {
+        let trades = self.trades_snapshot();
+        let (view, _, _) = self.sorted_view(&trades, false, None);
+        view.into_iter().map(|run| run.trade.clone()).collect()
+    }
+
+    /// Rows of the view in display order, along with the number of rows in
+    /// all and the number of trades hidden by mutes. With `group` on,
+    /// repeated trades are collapsed before sorting, so the sort sees the
+    /// run totals. With a `cap`, only the first `cap` rows are needed: in
+    /// the buffer's own order (newest first, ungrouped) matching stops
+    /// collecting there and the rest are only counted.
+    fn sorted_view<'a>(&self, trades: &'a [Trade], group: bool, cap: Option<usize>) -> (Vec<TradeRun<'a>>, usize, usize) {
+        let coin_filter = self.active_coin_filter();
+        let trader_filter = self.active_trader_filter();
+        let since_cutoff = self.since_cutoff.get();
+        let mut muted_count = 0;
+        let mut matches = trades
+            .iter()
+            // Newest first, so everything past the window can be skipped
+            .take_while(|trade| since_cutoff.is_none_or(|cutoff| trade.received_at >= cutoff))
+            .filter(|trade| matches_trade_type(trade, &self.trade_filter))
+            .filter(|trade| {
+                // Mutes apply before the positive filters
+                if self.is_muted(trade) {
+                    muted_count += 1;
+                    return false;
+                }
+                filter_trade(trade, &self.trade_filter, coin_filter, trader_filter) && self.passes_avatar_filter(trade)
+            });
+        if let Some(cap) = cap.filter(|_| !group && self.trade_sort == TradeSort::Newest) {
+            let rows: Vec<TradeRun> = matches.by_ref().take(cap).map(TradeRun::single).collect();
+            let matched_count = rows.len() + matches.count();
+            return (rows, matched_count, muted_count);
+        }
+        let matched: Vec<&Trade> = matches.collect();
+        let mut rows = if group {
+            group_repeated_trades(&matched, chrono::Duration::seconds(self.loaded_config.group_window_secs as i64))
+        } else {
+            matched.into_iter().map(TradeRun::single).collect()
         };
-        if self.scroll_offset < max_items.saturating_sub(1) {
-            self.scroll_offset += 1;
+
+        // The buffer is already newest-first; the other orders use a stable
+        // sort so ties keep their arrival order.
+        match self.trade_sort {
+            TradeSort::Newest => {}
+            TradeSort::Value => rows.sort_by_key(|row| std::cmp::Reverse(row.total_value)),
+            TradeSort::Amount => rows.sort_by_key(|row| std::cmp::Reverse(row.amount)),
+            TradeSort::Price => rows.sort_by_key(|row| std::cmp::Reverse(row.trade.data.price)),
         }
+        let matched_count = rows.len();
+        (rows, matched_count, muted_count)
     }
 
-    pub fn switch_trade_filter(&mut self) {
-        self.trade_filter = match self.trade_filter {
-            TradeFilter::All => TradeFilter::Large,
-            TradeFilter::Large => TradeFilter::All,
+    /// Total buy and sell value over the last `window` buys and sells of
+    /// `coin`. Trades of any other type are left out, as in
+    /// `buy_sell_counts`.
+    pub fn buy_sell_ratio(&self, coin: &str, window: usize) -> (Decimal, Decimal) {
+        self.trades_snapshot()
+            .iter()
+            .filter(|trade| trade.data.coin_symbol == coin)
+            .filter(|trade| matches!(trade.data.trade_type.as_str(), "BUY" | "SELL"))
+            .take(window)
+            .fold((Decimal::ZERO, Decimal::ZERO), |(buys, sells), trade| {
+                if trade.data.trade_type == "BUY" {
+                    (buys.saturating_add(trade.data.total_value), sells)
+                } else {
+                    (buys, sells.saturating_add(trade.data.total_value))
+                }
+            })
+    }
+
+    /// Total value of the trades in the current view that arrived within
+    /// `window`. The buffer is newest-first, so the scan stops at the first
+    /// trade past the window.
+    pub fn rolling_volume(&self, window: Duration) -> Decimal {
+        let Ok(window) = chrono::Duration::from_std(window) else {
+            return Decimal::ZERO;
         };
-        self.scroll_offset = 0;
+        let since = Local::now() - window;
+        self.trades_snapshot()
+            .iter()
+            .take_while(|trade| trade.received_at > since)
+            .filter(|trade| self.is_visible(trade))
+            .fold(Decimal::ZERO, |total, trade| total.saturating_add(trade.data.total_value))
     }
 
-    pub fn start_coin_filter(&mut self) {
-        self.input_mode = InputMode::CoinFilter;
-        self.input_buffer = self.coin_filter.clone();
+    pub fn toggle_volume_chart(&mut self) {
+        self.volume_chart = !self.volume_chart;
+        self.save_config();
     }
 
-    pub fn start_trader_filter(&mut self) {
-        self.input_mode = InputMode::TraderFilter;
-        self.input_buffer = self.trader_filter.clone();
+    /// The coin the volume chart is narrowed to: the coin filter when it
+    /// names exactly one coin, otherwise none (the whole feed).
+    pub fn volume_chart_coin(&self) -> Option<String> {
+        match self.coin_filter_list().as_slice() {
+            [coin] => Some(coin.to_uppercase()),
+            _ => None,
+        }
     }
 
-    pub fn confirm_filter(&mut self) {
-        match self.input_mode {
-            InputMode::CoinFilter => self.coin_filter = self.input_buffer.clone(),
-            InputMode::TraderFilter => self.trader_filter = self.input_buffer.clone(),
-            _ => {}
+    pub fn toggle_coin_counts_chart(&mut self) {
+        self.coin_counts_chart = !self.coin_counts_chart;
+        self.save_config();
+    }
+
+    pub fn toggle_buy_sell_mix(&mut self) {
+        self.buy_sell_mix = !self.buy_sell_mix;
+        self.save_config();
+    }
+
+    /// Number of buys and sells among the trades in the current view (tab,
+    /// filters and mutes). Trades of any other type count as neither.
+    pub fn buy_sell_counts(&self) -> (usize, usize) {
+        // Counted over the whole view, so scrolling doesn't change them
+        let key = FilteredTradesKey { render_cap: usize::MAX, ..self.filtered_trades_key() };
+        if let Some(ref cache) = *self.buy_sell_cache.borrow() {
+            if cache.key == key {
+                return cache.counts;
+            }
         }
-        self.input_mode = InputMode::Normal;
-        self.scroll_offset = 0;
+
+        let counts = self
+            .trades_snapshot()
+            .iter()
+            .filter(|trade| self.is_visible(trade))
+            .fold((0, 0), |(buys, sells), trade| match trade.data.trade_type.as_str() {
+                "BUY" => (buys + 1, sells),
+                "SELL" => (buys, sells + 1),
+                _ => (buys, sells),
+            });
+        *self.buy_sell_cache.borrow_mut() = Some(BuySellCountsCache { key, counts });
+        counts
     }
 
-    pub fn cancel_filter(&mut self) {
-        self.input_mode = InputMode::Normal;
+    pub fn toggle_status_bar(&mut self) {
+        self.status_bar = !self.status_bar;
+        self.save_config();
     }
 
-    pub fn add_to_input(&mut self, c: char) {
-        self.input_buffer.push(c);
+    /// Number of trades per coin in the buffer, busiest first and
+    /// alphabetical among ties.
+    pub fn trade_counts_by_coin(&self) -> Vec<(String, u64)> {
+        let mut counts: HashMap<&str, u64> = HashMap::new();
+        let trades = self.trades_snapshot();
+        for trade in trades.iter() {
+            *counts.entry(trade.data.coin_symbol.as_str()).or_default() += 1;
+        }
+        let mut counts: Vec<(String, u64)> = counts.into_iter().map(|(coin, count)| (coin.to_string(), count)).collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
     }
 
-    pub fn delete_from_input(&mut self) {
-        self.input_buffer.pop();
+    pub fn toggle_size_histogram(&mut self) {
+        self.size_histogram = !self.size_histogram;
+        self.save_config();
     }
-}
\ No newline at end of file
+
+    /// Trade count and value per size bucket over the trades in the current
+    /// view (tab, filters and mutes).
+    pub fn size_histogram(&self) -> SizeHistogram {
+        let key = SizeHistogramKey {
+            trade_filter: self.trade_filter.clone(),
+            coin_filter: self.active_coin_filter().to_string(),
+            trader_filter: self.active_trader_filter().to_string(),
+            muted_coins: self.muted_coins.clone(),
+            muted_traders: self.muted_traders.clone(),
+            avatar_only: self.avatar_only,
+            since: self.trade_since,
+        };
+        let generation = self.view_generation();
+        let mut slot = self.size_histogram_cache.borrow_mut();
+        if slot.as_ref().is_some_and(|cache| cache.key != key) {
+            *slot = None;
+        }
+        let cache = slot.get_or_insert_with(|| SizeHistogramCache {
+            key,
+            generation: None,
+            settled: SizeHistogram::default(),
+            counted: VecDeque::new(),
+            frontier: None,
+            histogram: SizeHistogram::default(),
+        });
+        let since_cutoff = self.since_cutoff.get();
+        let aged_out = |received_at: DateTime<Local>| since_cutoff.is_some_and(|cutoff| received_at < cutoff);
+        if cache.generation == Some(generation) && !cache.counted.back().is_some_and(|&(received_at, _)| aged_out(received_at)) {
+            return cache.histogram;
+        }
+
+        let trades = self.trades_snapshot();
+        // Drop settled trades that have since left the buffer or the window
+        let oldest = trades.last().map(|trade| trade.received_at);
+        while let Some(&(received_at, value)) = cache.counted.back() {
+            if oldest.is_some_and(|oldest| received_at >= oldest) && !aged_out(received_at) {
+                break;
+            }
+            cache.counted.pop_back();
+            cache.settled.remove(value);
+        }
+
+        let settle_before = Local::now() - chrono::Duration::seconds(HISTOGRAM_SETTLE_SECS);
+        let previous_frontier = cache.frontier;
+        let mut frontier = previous_frontier;
+        let mut unsettled = SizeHistogram::default();
+        let mut newly_settled = Vec::new();
+        for trade in trades
+            .iter()
+            .take_while(|trade| previous_frontier.is_none_or(|frontier| trade.received_at > frontier))
+        {
+            let settled = trade.received_at <= settle_before;
+            if settled {
+                frontier = frontier.max(Some(trade.received_at));
+            }
+            if !self.is_visible(trade) {
+                continue;
+            }
+            if settled {
+                newly_settled.push((trade.received_at, lossy_f64(trade.data.total_value)));
+            } else {
+                unsettled.add(lossy_f64(trade.data.total_value));
+            }
+        }
+        // Oldest first, so the newest ends up at the front
+        for (received_at, value) in newly_settled.into_iter().rev() {
+            cache.counted.push_front((received_at, value));
+            cache.settled.add(value);
+        }
+        cache.frontier = frontier;
+        cache.generation = Some(generation);
+        cache.histogram = cache.settled.combined(&unsettled);
+        cache.histogram
+    }
+
+    /// Buy/sell value of the filtered trades bucketed by minute, oldest first,
+    /// ending with the current minute. Minutes without trades are empty buckets.
+    /// Trades that are neither buys nor sells add to a minute's count only.
+    pub fn flow_by_minute(&self, minutes: usize) -> Vec<FlowBucket> {
+        let current_minute = truncate_to_minute(Local::now());
+
+        let mut buckets: Vec<FlowBucket> = (0..minutes)
+            .rev()
+            .map(|ago| FlowBucket {
+                minute: current_minute - chrono::Duration::minutes(ago as i64),
+                buy_value: 0.0,
+                sell_value: 0.0,
+                trade_count: 0,
+            })
+            .collect();
+
+        for trade in self.trades_snapshot().iter().filter(|trade| self.is_visible(trade)) {
+            let ago = (current_minute - truncate_to_minute(trade.received_at)).num_minutes();
+            if ago < 0 || ago as usize >= minutes {
+                continue;
+            }
+            let bucket = &mut buckets[minutes - 1 - ago as usize];
+            match trade.data.trade_type.as_str() {
+                "BUY" => bucket.buy_value += lossy_f64(trade.data.total_value),
+                "SELL" => bucket.sell_value += lossy_f64(trade.data.total_value),
+                _ => {}
+            }
+            bucket.trade_count += 1;
+        }
+        buckets
+    }
+
+    /// The price update keys act on: the history row at the scroll offset,
+    /// or the all-coins table row there when that table is showing.
+    pub fn selected_price_update(&self) -> Option<PriceUpdate> {
+        if self.current_page != AppPage::PriceTracker {
+            return None;
+        }
+        if self.all_coins_view {
+            return self.coin_table().get(self.scroll_offset).cloned();
+        }
+        self.tracked_price_history().get(self.scroll_offset).map(|point| point.update.clone())
+    }
+
+    /// Latest price update of every coin, sorted by `coin_sort`.
+    pub fn coin_table(&self) -> Rc<Vec<PriceUpdate>> {
+        let stats = self.session_stats.lock().unwrap();
+        let generation = stats.price_generation();
+        if let Some(ref cache) = *self.coin_table_cache.borrow() {
+            if cache.generation == generation && cache.sort == self.coin_sort {
+                return cache.rows.clone();
+            }
+        }
+
+        let mut rows: Vec<PriceUpdate> = stats.latest_prices().cloned().collect();
+        drop(stats);
+        match self.coin_sort {
+            CoinSort::Symbol => rows.sort_by(|a, b| a.coin_symbol.cmp(&b.coin_symbol)),
+            CoinSort::Price => rows.sort_by_key(|update| std::cmp::Reverse(update.current_price)),
+            CoinSort::Change => rows.sort_by(|a, b| b.change_24h.total_cmp(&a.change_24h)),
+            CoinSort::Volume => rows.sort_by_key(|update| std::cmp::Reverse(update.volume_24h)),
+            CoinSort::MarketCap => rows.sort_by_key(|update| std::cmp::Reverse(update.market_cap)),
+            CoinSort::Updated => rows.sort_by_key(|update| std::cmp::Reverse(update.received_at)),
+        }
+        let rows = Rc::new(rows);
+        *self.coin_table_cache.borrow_mut() = Some(CoinTableCache {
+            generation,
+            sort: self.coin_sort,
+            rows: rows.clone(),
+        });
+        rows
+    }
+
+    /// Switches the Price Tracker between the tracked coin and the all-coins
+    /// table. Returns the coin the feed should be switched to.
+    pub fn toggle_all_coins_view(&mut self) -> Option<String> {
+        self.all_coins_view = !self.all_coins_view;
+        self.scroll_offset = 0;
+        if self.all_coins_view {
+            Some(GLOBAL_FEED.to_string())
+        } else {
+            self.tracked_coin.clone()
+        }
+    }
+
+    /// Tracks the highlighted coin of the all-coins table in the single-coin
+    /// view. Returns the newly tracked symbol.
+    pub fn track_selected_coin(&mut self) -> Option<String> {
+        if !self.all_coins_view {
+            return None;
+        }
+        let update = self.coin_table().get(self.scroll_offset).cloned()?;
+        let coin = update.coin_symbol.to_uppercase();
+        self.tracked_coin = Some(coin.clone());
+        self.latest_price = Some(update);
+        self.all_coins_view = false;
+        self.scroll_offset = 0;
+        self.offer_linked_filter();
+        Some(coin)
+    }
+
+    pub fn toggle_coin_link(&mut self) {
+        self.coin_link = !self.coin_link;
+        self.set_status(if self.coin_link {
+            "Coin filter linked to the tracked coin".to_string()
+        } else {
+            "Coin filter unlinked".to_string()
+        });
+    }
+
+    /// The symbol a linked coin filter points the tracker at: only a single
+    /// entry that exactly matches a coin seen this session, so a partial
+    /// filter like "pe" never switches the feed.
+    fn linked_filter_coin(&self) -> Option<String> {
+        if !self.coin_link {
+            return None;
+        }
+        let [entry] = parse_coin_filter(&self.coin_filter).try_into().ok()?;
+        self.known_coins()
+            .into_iter()
+            .find(|coin| coin.eq_ignore_ascii_case(&entry))
+            .map(|coin| coin.to_uppercase())
+    }
+
+    // Asks whether the newly tracked coin should become the coin filter too
+    fn offer_linked_filter(&mut self) {
+        let Some(tracked) = self.tracked_coin.clone() else {
+            return;
+        };
+        if self.coin_link && !self.coin_filter.trim().eq_ignore_ascii_case(&tracked) {
+            self.link_filter_offer = Some(tracked);
+            self.input_mode = InputMode::ConfirmLinkFilter;
+        }
+    }
+
+    pub fn accept_link_filter_offer(&mut self) {
+        if let Some(coin) = self.link_filter_offer.take() {
+            self.coin_filter = coin;
+        }
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn decline_link_filter_offer(&mut self) {
+        self.link_filter_offer = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn cycle_coin_sort(&mut self) {
+        self.coin_sort = self.coin_sort.next();
+        self.scroll_offset = 0;
+        self.save_config();
+    }
+
+    pub fn note_input(&mut self) {
+        self.last_input = Instant::now();
+        self.idle = false;
+    }
+
+    /// Goes idle once neither input nor a feed message has arrived for
+    /// `idle_dim_secs`, and back as soon as either does. Returns whether
+    /// that changed.
+    pub fn update_idle(&mut self, now: Instant) -> bool {
+        let idle_after = Duration::from_secs(self.loaded_config.idle_dim_secs);
+        let last_activity = self.metrics.last_message_at().map_or(self.last_input, |at| at.max(self.last_input));
+        let idle = !idle_after.is_zero() && now.saturating_duration_since(last_activity) >= idle_after;
+        let changed = idle != self.idle;
+        self.idle = idle;
+        changed
+    }
+
+    pub fn set_price_throttle(&mut self, interval: Duration) {
+        self.price_throttle.send_replace(interval);
+        if interval.is_zero() {
+            self.set_status("Keeping every price update".to_string());
+        } else {
+            self.set_status(format!("Keeping at most one price update per coin every {}ms", interval.as_millis()));
+        }
+        self.save_config();
+    }
+
+    pub fn cycle_color_basis(&mut self) {
+        self.color_basis = self.color_basis.next();
+        self.set_status(format!("Price change measured against: {}", self.color_basis.label()));
+        self.save_config();
+    }
+
+    /// A price update's change under `color_basis`; not a number when the
+    /// session has no usable first price for the coin.
+    pub fn basis_change(&self, session: &SessionStats, update: &PriceUpdate) -> f64 {
+        match self.color_basis {
+            ColorBasis::Change24h => update.change_24h,
+            ColorBasis::Session => session.session_change_percent(&update.coin_symbol, update.current_price).unwrap_or(f64::NAN),
+        }
+    }
+
+    /// Copies the selected trade or price update: a one-line summary, or the
+    /// full entry as JSON.
+    pub fn copy_selected(&mut self, as_json: bool) {
+        let text = match self.current_page {
+            AppPage::Trades => self.selected_trade().map(|trade| {
+                if as_json {
+                    serde_json::to_string_pretty(&trade)
+                } else {
+                    Ok(trade.summary())
+                }
+            }),
+            AppPage::PriceTracker => self.selected_price_update().map(|update| {
+                if as_json {
+                    serde_json::to_string_pretty(&update)
+                } else {
+                    Ok(update.summary())
+                }
+            }),
+            AppPage::Portfolio => self.portfolio.orders.get(self.scroll_offset).map(|order| {
+                if as_json {
+                    serde_json::to_string_pretty(order)
+                } else {
+                    Ok(order.summary())
+                }
+            }),
+        };
+        let what = if as_json { "JSON" } else { "summary" };
+        match text {
+            None => self.set_status("Nothing selected to copy".to_string()),
+            Some(Err(e)) => self.set_status(format!("Couldn't serialize the entry: {}", e)),
+            Some(Ok(text)) => match clipboard::copy(&text) {
+                Ok(CopyTarget::Clipboard) => self.set_status(format!("Copied {} to clipboard", what)),
+                Ok(CopyTarget::File(path)) => {
+                    self.set_status(format!("No clipboard available - {} saved to {}", what, path.display()))
+                }
+                Err(e) => self.set_status(format!("Couldn't copy {}: {}", what, e)),
+            },
+        }
+    }
+
+    /// Copies every trade in the current view, in display order, as
+    /// tab-separated text with a header row.
+    pub fn copy_visible_trades(&mut self) {
+        // `grouped_trades` stops at the render cap and collapses repeats, so
+        // take every trade of the view
+        let trades = self.view_trades();
+        if trades.is_empty() {
+            self.set_status("No trades to copy".to_string());
+            return;
+        }
+        let text = ExportFormat::Tsv.trades_text(&trades);
+        let rows = format!("{} trade{}", trades.len(), if trades.len() == 1 { "" } else { "s" });
+        match clipboard::copy(&text) {
+            Ok(CopyTarget::Clipboard) => self.set_status(format!("Copied {} to clipboard as TSV", rows)),
+            Ok(CopyTarget::File(path)) => {
+                self.set_status(format!("No clipboard available - {} saved to {}", rows, path.display()))
+            }
+            Err(e) => self.set_status(format!("Couldn't copy {}: {}", rows, e)),
+        }
+    }
+
+    /// Writes every trade in the current view to `path`.
+    pub fn export_visible_trades(&mut self, format: ExportFormat, path: &Path) {
+        let trades = self.view_trades();
+        self.export_trades(&trades, format, path);
+    }
+
+    pub fn export_bookmarks(&mut self, format: ExportFormat, path: &Path) {
+        let bookmarks = self.bookmarks.clone();
+        self.export_trades(&bookmarks, format, path);
+    }
+
+    fn export_trades(&mut self, trades: &[Trade], format: ExportFormat, path: &Path) {
+        let rows = format!("{} trade{}", trades.len(), if trades.len() == 1 { "" } else { "s" });
+        let (severity, message) = match std::fs::write(path, format.trades_text(trades)) {
+            Ok(()) => (Severity::Info, format!("Exported {} to {}", rows, path.display())),
+            Err(e) => (Severity::Error, format!("Couldn't write {}: {}", path.display(), e)),
+        };
+        self.set_status(message.clone());
+        self.log_event(Event::new(severity, Source::Export, message));
+    }
+
+    /// The trade keys act on: the row at the scroll offset, which the list
+    /// draws highlighted at the top. For a grouped row, its newest trade.
+    pub fn selected_trade(&self) -> Option<Trade> {
+        if self.current_page != AppPage::Trades {
+            return None;
+        }
+        self.grouped_trades().get(self.scroll_offset).map(|row| row.trade.clone())
+    }
+
+    pub fn is_watched_coin(&self, coin_symbol: &str) -> bool {
+        self.watchlist.contains(&coin_symbol.to_uppercase())
+    }
+
+    pub fn is_followed_trader(&self, username: &str) -> bool {
+        self.follow_list.contains(&username.to_lowercase())
+    }
+
+    pub fn is_highlighted(&self, trade: &Trade) -> bool {
+        self.is_watched_coin(&trade.data.coin_symbol) || self.is_followed_trader(&trade.data.username)
+    }
+
+    pub fn toggle_watch_selected_coin(&mut self) {
+        if let Some(trade) = self.selected_trade() {
+            let coin = trade.data.coin_symbol.to_uppercase();
+            if !self.watchlist.remove(&coin) {
+                self.watchlist.insert(coin);
+            }
+            self.save_config();
+        }
+    }
+
+    pub fn toggle_follow_selected_trader(&mut self) {
+        if let Some(trade) = self.selected_trade() {
+            let trader = trade.data.username.to_lowercase();
+            if !self.follow_list.remove(&trader) {
+                self.follow_list.insert(trader);
+            }
+            self.save_config();
+        }
+    }
+
+    /// Trades by followed traders, newest first, regardless of the other
+    /// filters.
+    pub fn followed_trades(&self) -> Rc<Vec<Trade>> {
+        let generation = self.view_generation();
+        if let Some(ref cache) = *self.followed_cache.borrow() {
+            if cache.generation == generation && cache.follow_list == self.follow_list {
+                return cache.trades.clone();
+            }
+        }
+
+        let trades: Rc<Vec<Trade>> = Rc::new(
+            self.trades_snapshot()
+                .iter()
+                .filter(|trade| self.is_followed_trader(&trade.data.username))
+                .cloned()
+                .collect(),
+        );
+        *self.followed_cache.borrow_mut() = Some(FollowedTradesCache {
+            generation,
+            follow_list: self.follow_list.clone(),
+            trades: trades.clone(),
+        });
+        trades
+    }
+
+    pub fn toggle_follow_pane(&mut self) {
+        self.follow_pane = !self.follow_pane;
+        self.follow_scroll = 0;
+        self.save_config();
+    }
+
+    pub fn follow_scroll_up_by(&mut self, rows: usize) {
+        self.follow_scroll = self.follow_scroll.saturating_sub(rows);
+    }
+
+    pub fn follow_scroll_down_by(&mut self, rows: usize) {
+        let len = self.followed_trades().len();
+        self.follow_scroll = (self.follow_scroll + rows).min(len.saturating_sub(1));
+    }
+
+    /// Whether (`x`, `y`) falls inside the followed-traders pane.
+    pub fn in_follow_pane(&self, x: u16, y: u16) -> bool {
+        self.follow_pane_area
+            .get()
+            .is_some_and(|area| x >= area.x && x < area.right() && y >= area.y && y < area.bottom())
+    }
+
+    /// Followed traders with their session totals, if they've traded yet.
+    pub fn follow_entries(&self) -> Vec<(String, Option<TraderTotals>)> {
+        let stats = self.session_stats.lock().unwrap();
+        self.follow_list
+            .iter()
+            .map(|trader| (trader.clone(), stats.trader_totals(trader).cloned()))
+            .collect()
+    }
+
+    pub fn is_bookmarked(&self, trade: &Trade) -> bool {
+        self.bookmarks.iter().any(|bookmark| bookmark.is_duplicate_of(trade))
+    }
+
+    pub fn toggle_bookmark_selected(&mut self) {
+        let Some(trade) = self.selected_trade() else {
+            self.set_status("No trade selected".to_string());
+            return;
+        };
+        if let Some(index) = self.bookmarks.iter().position(|bookmark| bookmark.is_duplicate_of(&trade)) {
+            self.bookmarks.remove(index);
+            self.set_status("Bookmark removed".to_string());
+        } else {
+            let index = self.bookmarks.partition_point(|bookmark| bookmark.received_at > trade.received_at);
+            self.set_status(format!("Bookmarked {} {} by {}", trade.data.trade_type, trade.data.coin_symbol, trade.data.username));
+            self.bookmarks.insert(index, trade);
+        }
+        self.bookmark_list_index = self.bookmark_list_index.min(self.bookmarks.len().saturating_sub(1));
+    }
+
+    /// Whether a bookmarked trade is still in the trades buffer.
+    pub fn is_live(&self, trade: &Trade) -> bool {
+        self.trades_snapshot().iter().any(|live| live.is_duplicate_of(trade))
+    }
+
+    pub fn open_bookmark_list(&mut self) {
+        self.input_mode = InputMode::BookmarkList;
+        self.bookmark_list_index = 0;
+    }
+
+    pub fn close_bookmark_list(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn bookmark_list_up(&mut self) {
+        self.bookmark_list_index = self.bookmark_list_index.saturating_sub(1);
+    }
+
+    pub fn bookmark_list_down(&mut self) {
+        if self.bookmark_list_index + 1 < self.bookmarks.len() {
+            self.bookmark_list_index += 1;
+        }
+    }
+
+    /// Drops the highlighted bookmark. The trade itself stays in the feed.
+    pub fn remove_selected_bookmark(&mut self) {
+        if self.bookmark_list_index < self.bookmarks.len() {
+            self.bookmarks.remove(self.bookmark_list_index);
+            self.bookmark_list_index = self.bookmark_list_index.min(self.bookmarks.len().saturating_sub(1));
+        }
+    }
+
+    pub fn open_follow_list(&mut self) {
+        self.input_mode = InputMode::FollowList;
+        self.follow_list_index = 0;
+    }
+
+    pub fn close_follow_list(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn follow_list_up(&mut self) {
+        self.follow_list_index = self.follow_list_index.saturating_sub(1);
+    }
+
+    pub fn follow_list_down(&mut self) {
+        if self.follow_list_index + 1 < self.follow_list.len() {
+            self.follow_list_index += 1;
+        }
+    }
+
+    pub fn unfollow_selected(&mut self) {
+        if let Some(trader) = self.follow_list.iter().nth(self.follow_list_index).cloned() {
+            self.follow_list.remove(&trader);
+            self.follow_list_index = self.follow_list_index.min(self.follow_list.len().saturating_sub(1));
+            self.follow_scroll = 0;
+            self.save_config();
+        }
+    }
+
+    /// Opens the selected trade's coin page in the browser.
+    pub fn open_selected_coin_page(&mut self) {
+        match self.selected_trade() {
+            Some(trade) => {
+                let url = browser::expand_url(
+                    &self.loaded_config.coin_url_template,
+                    &[("coin", &trade.data.coin_symbol)],
+                );
+                self.open_url(&url);
+            }
+            None => self.set_status("No trade selected".to_string()),
+        }
+    }
+
+    /// Opens the selected trade's trader profile in the browser.
+    pub fn open_selected_trader_page(&mut self) {
+        match self.selected_trade() {
+            Some(trade) => {
+                let url = browser::expand_url(
+                    &self.loaded_config.trader_url_template,
+                    &[("username", &trade.data.username), ("user_id", &trade.data.user_id)],
+                );
+                self.open_url(&url);
+            }
+            None => self.set_status("No trade selected".to_string()),
+        }
+    }
+
+    /// Opens the selected trade's coin icon in the browser.
+    pub fn open_selected_coin_icon(&mut self) {
+        let Some(trade) = self.selected_trade() else {
+            self.set_status("No trade selected".to_string());
+            return;
+        };
+        match browser::image_url(&self.loaded_config.image_base_url, trade.data.coin_icon.as_deref().unwrap_or_default()) {
+            Some(url) => self.open_url(&url),
+            None => self.set_status(format!("{} has no icon", trade.data.coin_symbol)),
+        }
+    }
+
+    /// Opens the stats popup for the filtered coin when the filter names
+    /// exactly one known coin, otherwise for the selected trade's coin.
+    pub fn open_coin_stats(&mut self) {
+        let filtered_coin = match self.coin_filter_list().as_slice() {
+            [coin] => Some(coin.to_uppercase()),
+            _ => None,
+        }
+        .filter(|coin| self.session_stats.lock().unwrap().trade_stats(coin).is_some());
+        let coin = filtered_coin.or_else(|| self.selected_trade().map(|trade| trade.data.coin_symbol.to_uppercase()));
+        match coin {
+            Some(coin) => {
+                self.stats_coin = Some(coin);
+                self.input_mode = InputMode::CoinStats;
+            }
+            None => self.set_status("Filter by a coin or select a trade to see its stats".to_string()),
+        }
+    }
+
+    pub fn close_coin_stats(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Whether the tracked coin's latest price is older than the
+    /// configured staleness threshold at `now`.
+    pub fn latest_price_is_stale(&self, now: DateTime<Local>) -> bool {
+        let threshold = chrono::Duration::seconds(self.loaded_config.stale_price_secs as i64);
+        self.latest_price
+            .as_ref()
+            .is_some_and(|price| now - price.received_at > threshold)
+    }
+
+    pub fn tracked_position(&self) -> Option<&Position> {
+        self.positions.get(&self.tracked_coin.as_ref()?.to_uppercase())
+    }
+
+    /// Starts entering a simulated position in the tracked coin, prefilled
+    /// with the current one or the live price.
+    pub fn start_position_entry(&mut self) {
+        if self.tracked_coin.is_none() {
+            self.set_status("Track a coin first to simulate a position in it".to_string());
+            return;
+        }
+        self.input_buffer = match (self.tracked_position(), &self.latest_price) {
+            (Some(position), _) => format!("{} {}", position.entry_price, position.quantity),
+            (None, Some(price)) => format!("{} ", price.current_price),
+            (None, None) => String::new(),
+        };
+        self.input_mode = InputMode::PositionEntry;
+    }
+
+    /// Saves the typed position for the tracked coin; an empty entry
+    /// removes it. Invalid input keeps the prompt open.
+    pub fn confirm_position_entry(&mut self) {
+        let Some(coin) = self.tracked_coin.as_ref().map(|coin| coin.to_uppercase()) else {
+            self.input_mode = InputMode::Normal;
+            return;
+        };
+        if self.input_buffer.trim().is_empty() {
+            if self.positions.remove(&coin).is_some() {
+                self.set_status(format!("Removed the {} position", coin));
+            }
+        } else {
+            match Position::parse(&self.input_buffer) {
+                Some(position) => {
+                    self.positions.insert(coin, position);
+                }
+                None => {
+                    self.set_status("Enter a positive entry price and quantity, e.g. 0.0042 1000".to_string());
+                    return;
+                }
+            }
+        }
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Latest streamed price of `coin`, whether or not it's tracked. It comes
+    /// from the session stats, which see every update, rather than the
+    /// throttled and capped price history.
+    pub fn latest_price_of(&self, coin: &str) -> Option<Decimal> {
+        self.session_stats.lock().unwrap().latest_price(coin).map(|update| update.current_price)
+    }
+
+    pub fn portfolio_summary(&self) -> PortfolioSummary {
+        let stats = self.session_stats.lock().unwrap();
+        self.portfolio.summary(|coin| stats.latest_price(coin).map(|update| update.current_price))
+    }
+
+    /// Opens the order form, starting from the tracked coin.
+    pub fn start_order(&mut self, side: Side) {
+        let coin = self.tracked_coin.clone().unwrap_or_default();
+        self.order_form = OrderForm {
+            field: if coin.is_empty() { OrderField::Coin } else { OrderField::Amount },
+            coin,
+            side,
+            amount: String::new(),
+            error: None,
+        };
+        self.input_mode = InputMode::OrderEntry;
+    }
+
+    /// Known coin that the typed coin is the start of, for Tab to complete.
+    pub fn order_coin_suggestion(&self) -> Option<String> {
+        let typed = self.order_form.coin.to_uppercase();
+        if typed.is_empty() {
+            return None;
+        }
+        self.portfolio
+            .holdings
+            .keys()
+            .cloned()
+            .chain(self.known_coins().into_iter().map(|coin| coin.to_uppercase()))
+            .find(|coin| coin.starts_with(&typed) && *coin != typed)
+    }
+
+    /// Completes the coin and moves on to the amount; elsewhere in the form
+    /// it just moves to the next field.
+    pub fn complete_order_coin(&mut self) {
+        if self.order_form.field != OrderField::Coin {
+            self.cycle_order_field(true);
+            return;
+        }
+        if let Some(coin) = self.order_coin_suggestion() {
+            self.order_form.coin = coin;
+        }
+        self.order_form.field = OrderField::Amount;
+    }
+
+    /// Moves to the next (or previous) field of the order form.
+    pub fn cycle_order_field(&mut self, forward: bool) {
+        let fields = [OrderField::Coin, OrderField::Side, OrderField::Amount];
+        let index = fields.iter().position(|field| *field == self.order_form.field).unwrap_or(0);
+        let next = if forward { index + 1 } else { index + fields.len() - 1 };
+        self.order_form.field = fields[next % fields.len()];
+    }
+
+    pub fn toggle_order_side(&mut self) {
+        self.order_form.side = self.order_form.side.toggled();
+    }
+
+    /// Types into the focused field. On the side field `b` and `s` pick
+    /// the side.
+    pub fn order_input(&mut self, c: char) {
+        self.order_form.error = None;
+        match self.order_form.field {
+            OrderField::Coin => self.order_form.coin.push(c.to_ascii_uppercase()),
+            OrderField::Side => match c.to_ascii_lowercase() {
+                'b' => self.order_form.side = Side::Buy,
+                's' => self.order_form.side = Side::Sell,
+                _ => {}
+            },
+            OrderField::Amount => {
+                if self.accepts_input(c) {
+                    self.order_form.amount.push(c);
+                }
+            }
+        }
+    }
+
+    pub fn order_backspace(&mut self) {
+        self.order_form.error = None;
+        match self.order_form.field {
+            OrderField::Coin => {
+                self.order_form.coin.pop();
+            }
+            OrderField::Side => {}
+            OrderField::Amount => {
+                self.order_form.amount.pop();
+            }
+        }
+    }
+
+    /// Fills the typed order at the coin's latest streamed price. A failed
+    /// order keeps the form open with the reason.
+    pub fn place_order(&mut self) {
+        let form = &self.order_form;
+        let coin = form.coin.trim().to_uppercase();
+        if coin.is_empty() {
+            self.order_form.error = Some("Enter a coin".to_string());
+            return;
+        }
+        let Ok(amount) = form.amount.trim().trim_start_matches('$').parse::<Decimal>() else {
+            self.order_form.error = Some("Enter the amount to spend or receive, e.g. 25".to_string());
+            return;
+        };
+        let Some(price) = self.latest_price_of(&coin) else {
+            self.order_form.error = Some(format!("No streamed price for {} yet", coin));
+            return;
+        };
+        match self.portfolio.execute(&coin, form.side, amount, price, Local::now()) {
+            Ok(order) => {
+                self.input_mode = InputMode::Normal;
+                self.scroll_offset = 0;
+                self.save_portfolio();
+                self.set_status(order_status(&order));
+            }
+            Err(message) => self.order_form.error = Some(message),
+        }
+    }
+
+    fn save_portfolio(&mut self) {
+        let Some(ref path) = self.portfolio_path else {
+            return;
+        };
+        if let Err(e) = self.portfolio.save(path) {
+            let message = format!("Couldn't save the paper portfolio to {}: {}", path.display(), e);
+            self.log_event(Event::new(Severity::Error, Source::Storage, message));
+        }
+    }
+
+    fn open_url(&mut self, url: &str) {
+        match browser::open_url(url) {
+            Ok(()) => self.set_status(format!("Opened {}", url)),
+            Err(e) => self.set_status(format!("Couldn't open {}: {}", url, e)),
+        }
+    }
+
+    pub fn mute_selected(&mut self, kind: MuteKind) {
+        if let Some(trade) = self.selected_trade() {
+            match kind {
+                MuteKind::Coin => self.muted_coins.insert(trade.data.coin_symbol.to_uppercase()),
+                MuteKind::Trader => self.muted_traders.insert(trade.data.username.to_lowercase()),
+            };
+            self.clamp_scroll();
+            self.save_config();
+        }
+    }
+
+    /// Mutes in display order: coins first, then traders.
+    pub fn mute_entries(&self) -> Vec<(MuteKind, String)> {
+        self.muted_coins
+            .iter()
+            .map(|coin| (MuteKind::Coin, coin.clone()))
+            .chain(self.muted_traders.iter().map(|trader| (MuteKind::Trader, trader.clone())))
+            .collect()
+    }
+
+    pub fn open_mute_list(&mut self) {
+        self.input_mode = InputMode::MuteList;
+        self.mute_list_index = 0;
+    }
+
+    pub fn close_mute_list(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn mute_list_up(&mut self) {
+        self.mute_list_index = self.mute_list_index.saturating_sub(1);
+    }
+
+    pub fn mute_list_down(&mut self) {
+        if self.mute_list_index + 1 < self.mute_entries().len() {
+            self.mute_list_index += 1;
+        }
+    }
+
+    pub fn remove_selected_mute(&mut self) {
+        if let Some((kind, value)) = self.mute_entries().get(self.mute_list_index).cloned() {
+            match kind {
+                MuteKind::Coin => self.muted_coins.remove(&value),
+                MuteKind::Trader => self.muted_traders.remove(&value),
+            };
+            let remaining = self.mute_entries().len();
+            self.mute_list_index = self.mute_list_index.min(remaining.saturating_sub(1));
+            self.save_config();
+        }
+    }
+
+    fn clamp_scroll(&mut self) {
+        let len = self.filtered_trade_count();
+        self.scroll_offset = self.scroll_offset.min(len.saturating_sub(1));
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_some()
+    }
+
+    /// Trades the UI is showing: the snapshot taken at pause time while
+    /// paused, otherwise the latest published one.
+    pub fn trades_snapshot(&self) -> Arc<Vec<Trade>> {
+        match self.pause {
+            Some(ref pause) => pause.trades.clone(),
+            None => self.trades.snapshot(),
+        }
+    }
+
+    pub fn price_updates_snapshot(&self) -> Arc<Vec<PriceUpdate>> {
+        match self.pause {
+            Some(ref pause) => pause.price_updates.clone(),
+            None => self.price_updates.snapshot(),
+        }
+    }
+
+    /// Generation of the data the UI is showing: frozen while paused.
+    fn view_generation(&self) -> u64 {
+        match self.pause {
+            Some(ref pause) => pause.generation,
+            None => self.trades.generation(),
+        }
+    }
+
+    /// Changes whenever either shared buffer publishes a new snapshot.
+    pub fn data_generation(&self) -> (u64, u64) {
+        (self.trades.generation(), self.price_updates.generation())
+    }
+
+    pub fn toggle_pause(&mut self) {
+        match self.pause.take() {
+            Some(pause) => {
+                let queued = self.queued_while_paused(&pause);
+                self.set_status(format!("Resumed - applied {} queued message{}", queued, if queued == 1 { "" } else { "s" }));
+            }
+            None => {
+                self.pause = Some(PauseState {
+                    trades: self.trades.snapshot(),
+                    price_updates: self.price_updates.snapshot(),
+                    generation: self.trades.generation(),
+                    since: Local::now(),
+                });
+            }
+        }
+    }
+
+    /// Messages that have landed in the live buffers since the pause began.
+    pub fn queued_count(&self) -> usize {
+        self.pause.as_ref().map_or(0, |pause| self.queued_while_paused(pause))
+    }
+
+    fn queued_while_paused(&self, pause: &PauseState) -> usize {
+        let trades = self
+            .trades
+            .snapshot()
+            .iter()
+            .take_while(|trade| trade.received_at > pause.since)
+            .count();
+        let price_updates = self
+            .price_updates
+            .snapshot()
+            .iter()
+            .take_while(|update| update.received_at > pause.since)
+            .count();
+        trades + price_updates
+    }
+
+    /// Follows the feed's connection state. Every `Connected` report is a
+    /// fresh connection, so the uptime starts over on each reconnect.
+    pub fn set_feed_state(&mut self, state: FeedState) {
+        self.feed_state = state;
+        self.connected_since = match state {
+            FeedState::Connected => Some(Instant::now()),
+            FeedState::Connecting | FeedState::Disconnected => None,
+        };
+    }
+
+    /// How long the current feed connection has been up.
+    pub fn connection_uptime(&self) -> Option<Duration> {
+        self.connected_since.map(|since| since.elapsed())
+    }
+
+    pub fn set_status(&mut self, message: String) {
+        self.status_message = Some((message, Local::now()));
+    }
+
+    /// Returns `true` when the app should quit right away; otherwise opens
+    /// the quit confirmation. Only asks while there is data that would be lost.
+    pub fn request_quit(&mut self) -> bool {
+        let has_data = !self.trades_snapshot().is_empty() || !self.price_updates_snapshot().is_empty();
+        if !self.confirm_quit || !has_data {
+            return true;
+        }
+        self.input_mode = InputMode::ConfirmQuit;
+        false
+    }
+
+    /// Records a Ctrl+C and returns whether it was the second one inside the
+    /// force-quit window.
+    pub fn register_ctrl_c(&mut self) -> bool {
+        let now = Local::now();
+        let window = chrono::Duration::seconds(FORCE_QUIT_WINDOW_SECS);
+        if self.last_ctrl_c.is_some_and(|at| now - at <= window) {
+            return true;
+        }
+        self.last_ctrl_c = Some(now);
+        self.set_status("Press Ctrl+C again to quit".to_string());
+        false
+    }
+
+    pub fn open_help(&mut self) {
+        self.help_scroll = 0;
+        self.input_mode = InputMode::Help;
+    }
+
+    pub fn close_help(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn help_scroll_up(&mut self) {
+        self.help_scroll = self.help_scroll.saturating_sub(1);
+    }
+
+    pub fn help_scroll_down(&mut self) {
+        self.help_scroll = (self.help_scroll + 1).min(self.help_max_scroll.get());
+    }
+
+    pub fn start_clear_confirmation(&mut self, scope: ClearScope) {
+        self.clear_scope = scope;
+        self.input_mode = InputMode::ConfirmClear;
+    }
+
+    /// Empties the buffers covered by the pending clear along with everything
+    /// derived from them. Filters, lists and the tracked coin are kept.
+    pub fn confirm_clear(&mut self) {
+        let (clear_trades, clear_prices) = match self.clear_scope {
+            ClearScope::All => (true, true),
+            ClearScope::Page => (
+                self.current_page == AppPage::Trades,
+                self.current_page == AppPage::PriceTracker,
+            ),
+        };
+        // Clearing the Portfolio page starts the paper account over
+        if self.clear_scope == ClearScope::Page && self.current_page == AppPage::Portfolio {
+            self.portfolio = Portfolio::default();
+            self.save_portfolio();
+            self.set_status("Paper portfolio reset".to_string());
+        }
+        if clear_trades {
+            self.clear_trades();
+        }
+        if clear_prices {
+            self.clear_price_updates();
+        }
+        if self.clear_scope == ClearScope::All {
+            // Known symbols come from the buffers, so drop the stale picker list
+            self.coin_list.clear();
+            self.coin_list_index = 0;
+        }
+        self.scroll_offset = 0;
+        if self.clear_scope == ClearScope::All {
+            self.other_pane_scroll = 0;
+        }
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// The clear takes the writer-side lock, so it can't interleave with a
+    /// receiver's push, and publishes the empty snapshot immediately.
+    fn clear_trades(&mut self) {
+        // Clearing publishes a new generation, which invalidates the
+        // filtered cache
+        self.trades.clear();
+        self.session_stats.lock().unwrap().clear_trades();
+        self.follow_scroll = 0;
+        if let Some(ref mut pause) = self.pause {
+            pause.trades = Arc::new(Vec::new());
+            pause.generation = self.trades.generation();
+        }
+        self.spike_checked_until = Some(Local::now());
+        self.last_spike_alert.clear();
+    }
+
+    fn clear_price_updates(&mut self) {
+        self.price_updates.clear();
+        self.session_stats.lock().unwrap().clear_prices();
+        if let Some(ref mut pause) = self.pause {
+            pause.price_updates = Arc::new(Vec::new());
+        }
+        self.latest_price = None;
+    }
+
+    pub fn record_unknown_message_type(&mut self, msg_type: String) {
+        self.log_event(Event::new(
+            Severity::Warning,
+            Source::Feed,
+            format!("Unrecognized message type on the feed: {} (listed under ?)", msg_type),
+        ));
+        if self.unknown_message_types.len() < MAX_UNKNOWN_NAMES {
+            self.unknown_message_types.insert(msg_type);
+        }
+    }
+
+    pub fn record_unknown_trade_fields(&mut self, fields: Vec<String>) {
+        self.log_event(Event::new(
+            Severity::Info,
+            Source::Feed,
+            format!("New trade fields on the feed: {} (listed under ?)", fields.join(", ")),
+        ));
+        for field in fields {
+            if self.unknown_trade_fields.len() >= MAX_UNKNOWN_NAMES {
+                break;
+            }
+            self.unknown_trade_fields.insert(field);
+        }
+    }
+
+    /// Channels the feed is subscribed to.
+    pub fn subscribe_channels(&self) -> &[String] {
+        &self.loaded_config.subscribe_channels
+    }
+
+    /// Raises a warning from the alert rules; it shows in the banner.
+    pub fn push_alert(&mut self, message: String) {
+        self.log_event(Event::new(Severity::Warning, Source::Alerts, message));
+    }
+
+    /// Sends alerts to the desktop as well, either each rule at most once
+    /// per configured cooldown or batched into digests, as configured.
+    /// Builds without a notification backend only log that they can't.
+    pub fn enable_desktop_notifications(&mut self) {
+        if !DesktopNotifier::is_supported() {
+            self.log_event(Event::new(
+                Severity::Warning,
+                Source::Alerts,
+                "This build has no desktop notifications; alerts only show in the banner",
+            ));
+            return;
+        }
+        self.desktop_notifier = Some(match self.loaded_config.notification_mode {
+            NotificationMode::Immediate => DesktopNotifier::new(Duration::from_secs(self.loaded_config.notification_cooldown_secs)),
+            NotificationMode::Digest => DesktopNotifier::with_digest(Duration::from_secs(self.loaded_config.notification_digest_secs)),
+        });
+    }
+
+    /// Raises an alert in the banner and, when enabled, on the desktop.
+    fn raise_alert(&mut self, rule: &str, coin: &str, value: Decimal, message: String) {
+        if let Some(notifier) = self.desktop_notifier.as_mut() {
+            notifier.notify(TriggeredAlert {
+                rule: rule.to_string(),
+                coin: coin.to_string(),
+                value: lossy_f64(value),
+                message: message.clone(),
+            });
+        }
+        self.push_alert(message);
+    }
+
+    pub fn log_event(&mut self, event: Event) {
+        self.events.push(event);
+        if self.input_mode == InputMode::EventLog {
+            // Already on screen, and the newest rows push the rest down
+            self.events.mark_seen();
+            if self.event_log_scroll > 0 {
+                self.event_log_scroll = (self.event_log_scroll + 1).min(self.events.len() - 1);
+            }
+        }
+    }
+
+    pub fn open_event_log(&mut self) {
+        self.input_mode = InputMode::EventLog;
+        self.event_log_scroll = 0;
+        self.events.mark_seen();
+    }
+
+    pub fn close_event_log(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn event_log_up(&mut self) {
+        self.event_log_scroll = self.event_log_scroll.saturating_sub(1);
+    }
+
+    pub fn event_log_down(&mut self) {
+        if self.event_log_scroll + 1 < self.events.len() {
+            self.event_log_scroll += 1;
+        }
+    }
+
+    /// Ratio of the value traded in `coin` over the last spike window to the
+    /// window before it, if that ratio reaches the configured multiplier.
+    pub fn detect_volume_spike(&self, coin: &str) -> Option<f64> {
+        let (recent, prior) = self.spike_window_volumes(coin);
+        if prior <= Decimal::ZERO {
+            return None;
+        }
+        let ratio = lossy_f64(recent) / lossy_f64(prior);
+        (ratio >= self.volume_spike_multiplier).then_some(ratio)
+    }
+
+    /// Value traded in `coin` over the last spike window.
+    pub fn spike_window_volume(&self, coin: &str) -> Decimal {
+        self.spike_window_volumes(coin).0
+    }
+
+    /// Value traded in `coin` over the last spike window and the one before.
+    fn spike_window_volumes(&self, coin: &str) -> (Decimal, Decimal) {
+        let now = Local::now();
+        let window = chrono::Duration::seconds(self.volume_spike_window_secs as i64);
+        let recent_start = now - window;
+        let prior_start = recent_start - window;
+
+        let mut recent = Decimal::ZERO;
+        let mut prior = Decimal::ZERO;
+        for trade in self.trades_snapshot().iter() {
+            if trade.data.coin_symbol != coin {
+                continue;
+            }
+            if trade.received_at >= recent_start {
+                recent = recent.saturating_add(trade.data.total_value);
+            } else if trade.received_at >= prior_start {
+                prior = prior.saturating_add(trade.data.total_value);
+            } else {
+                // Buffer is newest-first, everything after this is older
+                break;
+            }
+        }
+        (recent, prior)
+    }
+
+    /// Raises alerts for the trades that arrived since the last check: trades
+    /// by followed traders and volume spikes in the coins that were traded.
+    pub fn check_new_trades(&mut self) {
+        // Wait for the live buffers; anything that arrived is checked on resume
+        if self.is_paused() {
+            return;
+        }
+        let trades = self.trades.snapshot();
+        let Some(newest) = trades.first() else {
+            return;
+        };
+        if self.spike_checked_until.is_some_and(|at| newest.received_at <= at) {
+            return;
+        }
+        let checked_until = self.spike_checked_until.replace(newest.received_at);
+
+        let new_trades: Vec<&Trade> = trades
+            .iter()
+            .take_while(|trade| checked_until.is_none_or(|at| trade.received_at > at))
+            .collect();
+
+        if self.follow_alerts {
+            // Oldest first so the newest ends up at the front of the banner
+            for trade in new_trades.iter().rev() {
+                if self.is_followed_trader(&trade.data.username) {
+                    let message = format!(
+                        "★ {} {} {:.2} {} (${:.2})",
+                        trade.data.username,
+                        trade.data.trade_type,
+                        round_to(trade.data.amount, 2),
+                        trade.data.coin_symbol,
+                        round_to(trade.data.total_value, 2)
+                    );
+                    self.raise_alert("Followed trader", &trade.data.coin_symbol, trade.data.total_value, message);
+                }
+            }
+        }
+
+        let coins: HashSet<String> = new_trades.iter().map(|trade| trade.data.coin_symbol.clone()).collect();
+        self.check_volume_spikes(coins);
+        self.check_feed_lag();
+    }
+
+    /// Hands the current tab, filters and mutes to the trade receiver, which
+    /// pipes the trades that pass them to `--exec`. Only sends when they
+    /// changed.
+    pub fn publish_exec_filter(&self) {
+        let filter = ExecFilter {
+            trade_filter: self.trade_filter.clone(),
+            coin_filter: self.active_coin_filter().to_string(),
+            trader_filter: self.active_trader_filter().to_string(),
+            muted_coins: self.muted_coins.clone(),
+            muted_traders: self.muted_traders.clone(),
+            avatar_only: self.avatar_only,
+            placeholder_avatars: self.loaded_config.placeholder_avatars.clone(),
+        };
+        self.exec_filter.send_if_modified(|published| {
+            let changed = published.as_ref() != Some(&filter);
+            *published = Some(filter);
+            changed
+        });
+    }
+
+    /// Warns once when the average feed lag crosses the threshold, and again
+    /// only after it has dropped back below half of it.
+    fn check_feed_lag(&mut self) {
+        let Some(lag) = self.session_stats.lock().unwrap().average_feed_lag() else {
+            return;
+        };
+        let threshold = self.loaded_config.feed_lag_warn_secs;
+        if !self.feed_lag_warned && lag.abs() > threshold {
+            self.feed_lag_warned = true;
+            let cause = if lag > 0.0 { "feed is lagging" } else { "server clock is ahead" };
+            self.push_alert(format!(
+                "Trades arrive {:+.1}s from their timestamps on average - {}",
+                lag, cause
+            ));
+        } else if self.feed_lag_warned && lag.abs() < threshold / 2.0 {
+            self.feed_lag_warned = false;
+        }
+    }
+
+    /// Checks coins with newly arrived trades for volume spikes and logs an
+    /// alert for each, at most once per coin per spike window.
+    fn check_volume_spikes(&mut self, coins: HashSet<String>) {
+        let now = Local::now();
+        let cooldown = chrono::Duration::seconds(self.volume_spike_window_secs as i64);
+        for coin in coins {
+            let recently_alerted = self
+                .last_spike_alert
+                .get(&coin)
+                .is_some_and(|at| now - *at < cooldown);
+            if recently_alerted {
+                continue;
+            }
+            if let Some(ratio) = self.detect_volume_spike(&coin) {
+                let value = self.spike_window_volume(&coin);
+                let message = format!(
+                    "{} volume spike: {:.1}x the previous {}s",
+                    coin, ratio, self.volume_spike_window_secs
+                );
+                self.raise_alert("Volume spike", &coin, value, message);
+                self.last_spike_alert.insert(coin, now);
+            }
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll_up_by(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll_down_by(1);
+    }
+
+    pub fn scroll_up_by(&mut self, rows: usize) {
+        self.scroll_offset = self.scroll_offset.saturating_sub(rows);
+    }
+
+    /// Scrolls towards older entries, stopping with the last one at the top.
+    pub fn scroll_down_by(&mut self, rows: usize) {
+        let max_items = self.pane_len(&self.current_page);
+        self.scroll_offset = self.scroll_offset.saturating_add(rows).min(max_items.saturating_sub(1));
+    }
+
+    /// Entries shown at once in the focused list, as of the last frame.
+    pub fn page_size(&self) -> usize {
+        self.scrollbar_tracks
+            .borrow()
+            .iter()
+            .find(|track| track.page == self.current_page)
+            // No scrollbar means everything fits, so a page is the whole list
+            .map_or(usize::MAX, |track| track.visible_items.max(1))
+    }
+
+    /// Starts a scrollbar drag if (`x`, `y`) is on a drawn scrollbar, jumping
+    /// to the matching offset and focusing that pane. Returns whether it hit.
+    pub fn press_scrollbar(&mut self, x: u16, y: u16) -> bool {
+        let track = self
+            .scrollbar_tracks
+            .borrow()
+            .iter()
+            .find(|track| track.contains(x, y))
+            .cloned();
+        let Some(track) = track else {
+            return false;
+        };
+        if self.current_page != track.page {
+            self.switch_page();
+        }
+        self.scroll_offset = track.offset_at(y);
+        self.dragging_scrollbar = Some(track.page);
+        true
+    }
+
+    /// Follows the mouse while a scrollbar drag is in progress, even when it
+    /// leaves the scrollbar column.
+    pub fn drag_scrollbar(&mut self, y: u16) {
+        let Some(ref page) = self.dragging_scrollbar else {
+            return;
+        };
+        let track = self
+            .scrollbar_tracks
+            .borrow()
+            .iter()
+            .find(|track| track.page == *page)
+            .cloned();
+        if let Some(track) = track {
+            self.scroll_offset = track.offset_at(y);
+        }
+    }
+
+    pub fn release_scrollbar(&mut self) {
+        self.dragging_scrollbar = None;
+    }
+
+    pub fn switch_trade_filter(&mut self) {
+        self.trade_filter = match self.trade_filter {
+            TradeFilter::All => TradeFilter::Large,
+            TradeFilter::Large => TradeFilter::All,
+        };
+        self.scroll_offset = 0;
+    }
+
+    pub fn toggle_split_view(&mut self) {
+        self.split_view = !self.split_view;
+        self.save_config();
+    }
+
+    pub fn toggle_price_chart(&mut self) {
+        self.price_chart = !self.price_chart;
+        self.scroll_offset = 0;
+        self.save_config();
+    }
+
+    pub fn toggle_moving_averages(&mut self) {
+        self.moving_averages = !self.moving_averages;
+        self.save_config();
+    }
+
+    /// Whether the split layout is in effect for a terminal `width` columns wide.
+    pub fn split_active(&self, width: u16) -> bool {
+        self.split_view && width >= self.split_min_width && self.current_page != AppPage::Portfolio
+    }
+
+    pub fn cycle_trade_sort(&mut self) {
+        self.trade_sort = self.trade_sort.next();
+        self.scroll_offset = 0;
+        self.save_config();
+    }
+
+    pub fn toggle_compact_view(&mut self) {
+        self.compact_view = !self.compact_view;
+        self.save_config();
+    }
+
+    pub fn toggle_avatar_only(&mut self) {
+        self.avatar_only = !self.avatar_only;
+        self.scroll_offset = 0;
+        self.set_status(if self.avatar_only {
+            "Only showing traders with an avatar".to_string()
+        } else {
+            "Showing every trader".to_string()
+        });
+    }
+
+    /// Hides trades received longer ago than `window`; `None` shows them all
+    /// again.
+    pub fn set_trade_since(&mut self, window: Option<Duration>) {
+        self.trade_since = window;
+        self.begin_frame(Local::now());
+        self.scroll_offset = 0;
+        self.set_status(match window {
+            Some(window) => format!("Hiding trades older than {}", cli::format_retention(window)),
+            None => "Showing trades of any age".to_string(),
+        });
+    }
+
+    pub fn toggle_millisecond_timestamps(&mut self) {
+        self.millisecond_timestamps = !self.millisecond_timestamps;
+        self.set_status(if self.millisecond_timestamps {
+            "Showing times to the millisecond".to_string()
+        } else {
+            "Showing times to the second".to_string()
+        });
+        self.save_config();
+    }
+
+    /// How prominently a trade (or collapsed row) worth `value` is drawn.
+    pub fn value_tier(&self, value: Decimal) -> ValueTier {
+        self.loaded_config.value_breakpoints.tier(lossy_f64(value))
+    }
+
+    /// The configured one-line layout for rows in the list view, if any.
+    pub fn row_template(&self) -> Option<&RowTemplate> {
+        self.loaded_config.trade_row_template.as_ref()
+    }
+
+    /// `chrono` format for a time of day, with milliseconds when toggled on.
+    pub fn time_format(&self) -> &'static str {
+        if self.millisecond_timestamps {
+            "%H:%M:%S%.3f"
+        } else {
+            "%H:%M:%S"
+        }
+    }
+
+    /// Rows shift when runs collapse or expand, so the list goes back to
+    /// the top.
+    pub fn toggle_group_trades(&mut self) {
+        self.group_trades = !self.group_trades;
+        self.scroll_offset = 0;
+        self.set_status(if self.group_trades {
+            "Grouping repeated trades by trader".to_string()
+        } else {
+            "Showing every trade".to_string()
+        });
+        self.save_config();
+    }
+
+    pub fn start_coin_filter(&mut self) {
+        self.input_mode = InputMode::CoinFilter;
+        self.input_buffer = self.coin_filter.clone();
+    }
+
+    pub fn start_trader_filter(&mut self) {
+        self.input_mode = InputMode::TraderFilter;
+        self.input_buffer = self.trader_filter.clone();
+    }
+
+    pub fn clear_filters(&mut self) {
+        if self.coin_filter.is_empty() && self.trader_filter.is_empty() {
+            self.set_status("No filters to clear".to_string());
+            return;
+        }
+        self.coin_filter.clear();
+        self.trader_filter.clear();
+        self.scroll_offset = 0;
+        self.set_status("Filters cleared".to_string());
+    }
+
+    /// Commits the typed filter. Returns the coin the feed should switch to
+    /// when a linked coin filter names a known coin.
+    pub fn confirm_filter(&mut self) -> Option<String> {
+        // The list already shows the typed filter, so keep it as it is
+        let filter = self.input_buffer.clone();
+        match std::mem::replace(&mut self.input_mode, InputMode::Normal) {
+            InputMode::CoinFilter => self.apply_coin_filter(filter),
+            InputMode::TraderFilter => {
+                self.trader_filter = filter;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Sets the coin filter. Returns the coin the feed should switch to
+    /// when a linked coin filter names a known coin.
+    fn apply_coin_filter(&mut self, filter: String) -> Option<String> {
+        self.coin_filter = filter;
+        let coin = self
+            .linked_filter_coin()
+            .filter(|coin| self.tracked_coin.as_ref() != Some(coin))?;
+        self.tracked_coin = Some(coin.clone());
+        self.latest_price = None;
+        self.all_coins_view = false;
+        self.set_status(format!("Now tracking {}", coin));
+        Some(coin)
+    }
+
+    /// Coin filter set from the command line, with the list back at the top.
+    pub fn set_coin_filter(&mut self, filter: String) -> Option<String> {
+        self.scroll_offset = 0;
+        self.apply_coin_filter(filter)
+    }
+
+    pub fn set_trader_filter(&mut self, filter: String) {
+        self.trader_filter = filter;
+        self.scroll_offset = 0;
+    }
+
+    /// The coin symbol or username drawn at (`x`, `y`) in the last frame.
+    pub fn quick_filter_at(&self, x: u16, y: u16) -> Option<QuickFilter> {
+        self.quick_filter_regions
+            .borrow()
+            .iter()
+            .find(|region| {
+                let area = region.area;
+                x >= area.x && x < area.right() && y >= area.y && y < area.bottom()
+            })
+            .map(|region| region.filter.clone())
+    }
+
+    /// Narrows the trades to exactly one coin or trader. Returns the coin
+    /// the feed should switch to, as a typed coin filter would.
+    pub fn apply_quick_filter(&mut self, filter: QuickFilter) -> Option<String> {
+        match filter {
+            QuickFilter::Coin(symbol) => {
+                self.set_status(format!("Showing only {} trades", symbol));
+                self.set_coin_filter(symbol)
+            }
+            QuickFilter::Trader(username) => {
+                self.set_status(format!("Showing only trades by {}", username));
+                self.set_trader_filter(username);
+                None
+            }
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.set_status(format!("Using the {} palette", theme.name()));
+    }
+
+    pub fn set_trade_filter(&mut self, filter: TradeFilter) {
+        if self.trade_filter != filter {
+            self.switch_trade_filter();
+        }
+    }
+
+    pub fn start_command(&mut self) {
+        self.input_mode = InputMode::Command;
+        self.input_buffer.clear();
+        self.command_error = None;
+    }
+
+    pub fn start_search(&mut self) {
+        self.input_mode = InputMode::Search;
+        self.input_buffer = self.search_query.clone();
+        self.search_origin = self.scroll_offset;
+    }
+
+    /// Keeps the typed search so `n`/`N` can step through its matches.
+    pub fn confirm_search(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.search_query = std::mem::take(&mut self.input_buffer);
+        if !self.search_query.is_empty() && self.search_matches().is_empty() {
+            self.set_status(format!("No trades match \"{}\"", self.search_query));
+        }
+    }
+
+    pub fn clear_search(&mut self) {
+        if !self.search_query.is_empty() {
+            self.search_query.clear();
+            self.set_status("Search cleared".to_string());
+        }
+    }
+
+    /// Moves the selection to the next (or previous) match of the committed
+    /// search, wrapping around at the ends of the list.
+    pub fn step_search(&mut self, forward: bool) {
+        if self.search_query.is_empty() {
+            self.set_status("No search - press / to start one".to_string());
+            return;
+        }
+        let matches = self.search_matches();
+        let ahead = if forward {
+            matches.iter().find(|&&position| position > self.scroll_offset)
+        } else {
+            matches.iter().rev().find(|&&position| position < self.scroll_offset)
+        };
+        let wrapped = if forward { matches.first() } else { matches.last() };
+        match (ahead, wrapped) {
+            (Some(&position), _) => self.scroll_offset = position,
+            (None, Some(&position)) => {
+                self.scroll_offset = position;
+                self.set_status(format!("Search wrapped to the {}", if forward { "top" } else { "bottom" }));
+            }
+            (None, None) => self.set_status(format!("No trades match \"{}\"", self.search_query)),
+        }
+    }
+
+    pub fn complete_command(&mut self) {
+        if let Some(completed) = command::complete(&self.input_buffer) {
+            self.input_buffer = completed;
+        }
+    }
+
+    pub fn cancel_filter(&mut self) {
+        match self.input_mode {
+            // The list was following the typed text, so go back to the top
+            // of the committed view
+            InputMode::CoinFilter | InputMode::TraderFilter => self.scroll_offset = 0,
+            // Esc drops the search altogether, not just the edit
+            InputMode::Search => {
+                self.scroll_offset = self.search_origin;
+                self.search_query.clear();
+            }
+            _ => {}
+        }
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn add_to_input(&mut self, c: char) {
+        if !self.accepts_input(c) {
+            return;
+        }
+        self.input_buffer.push(c);
+        self.follow_live_input();
+    }
+
+    /// The numeric shape the current prompt expects; text prompts take
+    /// anything.
+    pub fn numeric_input(&self) -> Option<NumericInput> {
+        match self.input_mode {
+            InputMode::PositionEntry => Some(NumericInput { values: 2, signed: false }),
+            InputMode::OrderEntry if self.order_form.field == OrderField::Amount => {
+                Some(NumericInput { values: 1, signed: false })
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks `c` against the current prompt's validator, flashing the
+    /// prompt when it's refused.
+    fn accepts_input(&mut self, c: char) -> bool {
+        let typed = match self.input_mode {
+            InputMode::OrderEntry => &self.order_form.amount,
+            _ => &self.input_buffer,
+        };
+        let accepted = self.numeric_input().is_none_or(|numeric| numeric.accepts(typed, c));
+        if !accepted {
+            self.rejected_input_at = Some(Local::now());
+        }
+        accepted
+    }
+
+    pub fn delete_from_input(&mut self) {
+        self.input_buffer.pop();
+        self.follow_live_input();
+    }
+
+    fn follow_live_input(&mut self) {
+        match self.input_mode {
+            // Each keystroke in a filter input changes the list, so the old
+            // offset no longer points at anything meaningful
+            InputMode::CoinFilter | InputMode::TraderFilter => self.scroll_offset = 0,
+            // Searching jumps to the first match, or back to where it
+            // started while nothing matches
+            InputMode::Search => {
+                self.scroll_offset = self.search_matches().first().copied().unwrap_or(self.search_origin);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Splits a comma-separated coin filter into lowercase entries, dropping
+/// blanks. An empty result means "all coins".
+pub fn parse_coin_filter(coin: &str) -> Vec<String> {
+    coin.split(',')
+        .map(|entry| entry.trim().to_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+fn order_status(order: &PaperOrder) -> String {
+    let verb = match order.side {
+        Side::Buy => "Bought",
+        Side::Sell => "Sold",
+    };
+    format!("{} {:.4} {} at ${:.8} for ${:.2}", verb, round_to(order.quantity, 4), order.coin, round_to(order.price, 8), round_to(order.value, 2))
+}
+
+/// A row of the view before it's cloned for the cache.
+#[derive(Debug, Clone, Copy)]
+struct TradeRun<'a> {
+    trade: &'a Trade,
+    count: usize,
+    amount: Decimal,
+    total_value: Decimal,
+}
+
+impl<'a> TradeRun<'a> {
+    fn single(trade: &'a Trade) -> Self {
+        Self { trade, count: 1, amount: trade.data.amount, total_value: trade.data.total_value }
+    }
+
+    fn to_grouped(self) -> GroupedTrade {
+        GroupedTrade {
+            trade: self.trade.clone(),
+            count: self.count,
+            amount: self.amount,
+            total_value: self.total_value,
+        }
+    }
+}
+
+/// Collapses consecutive trades (newest first) by the same trader on the
+/// same coin and side into one run, as long as each is within `window` of
+/// the run's newest trade.
+fn group_repeated_trades<'a>(trades: &[&'a Trade], window: chrono::Duration) -> Vec<TradeRun<'a>> {
+    let mut runs: Vec<TradeRun> = Vec::new();
+    for &trade in trades {
+        if let Some(run) = runs.last_mut() {
+            let newest = &run.trade.data;
+            if newest.username == trade.data.username
+                && newest.coin_symbol == trade.data.coin_symbol
+                && newest.trade_type == trade.data.trade_type
+                && run.trade.received_at - trade.received_at <= window
+            {
+                run.count += 1;
+                run.amount = run.amount.saturating_add(trade.data.amount);
+                run.total_value = run.total_value.saturating_add(trade.data.total_value);
+                continue;
+            }
+        }
+        runs.push(TradeRun::single(trade));
+    }
+    runs
+}
+
+/// Whether the coin symbol, coin name or trader of `trade` contains
+/// `query`, which must already be lowercase.
+pub fn trade_matches_search(trade: &Trade, query: &str) -> bool {
+    [&trade.data.coin_symbol, &trade.data.coin_name, &trade.data.username]
+        .iter()
+        .any(|text| text.to_lowercase().contains(query))
+}
+
+/// Byte ranges of `text` where `query` occurs, ignoring case, for
+/// highlighting search hits.
+pub fn search_match_ranges(text: &str, query: &str) -> Vec<Range<usize>> {
+    let query = query.to_lowercase();
+    let lower = text.to_lowercase();
+    // Lowercasing some characters changes their length, after which the
+    // positions no longer line up with `text`
+    if query.is_empty() || lower.len() != text.len() {
+        return Vec::new();
+    }
+    lower
+        .match_indices(&query)
+        .map(|(start, hit)| start..start + hit.len())
+        .filter(|range| text.is_char_boundary(range.start) && text.is_char_boundary(range.end))
+        .collect()
+}
+
+fn matches_trade_type(trade: &Trade, filter: &TradeFilter) -> bool {
+    match filter {
+        TradeFilter::All => true,
+        TradeFilter::Large => trade.is_large,
+    }
+}
+
+/// Whether `trade` passes the trade type tab and the coin/trader filters.
+/// Coin and trader matching are case-insensitive substring matches; `coin`
+/// may hold several comma-separated symbols, any of which can match.
+pub fn filter_trade(trade: &Trade, filter: &TradeFilter, coin: &str, trader: &str) -> bool {
+    if !matches_trade_type(trade, filter) {
+        return false;
+    }
+
+    let coin_filters = parse_coin_filter(coin);
+    let coin_symbol = trade.data.coin_symbol.to_lowercase();
+    let coin_match = coin_filters.is_empty()
+        || coin_filters.iter().any(|coin| coin_symbol.contains(coin.as_str()));
+
+    let trader_match = trader.is_empty()
+        || trade.data.username.to_lowercase().contains(&trader.to_lowercase());
+
+    coin_match && trader_match
+}
+
+/// The tab, filters and mutes of the trades view, as `--exec` applies them.
+/// The UI publishes them to the trade receiver, which pipes new trades as
+/// they arrive, paused display or not.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecFilter {
+    trade_filter: TradeFilter,
+    coin_filter: String,
+    trader_filter: String,
+    muted_coins: BTreeSet<String>,
+    muted_traders: BTreeSet<String>,
+    avatar_only: bool,
+    placeholder_avatars: Vec<String>,
+}
+
+impl ExecFilter {
+    pub fn matches(&self, trade: &Trade) -> bool {
+        !self.muted_coins.contains(&trade.data.coin_symbol.to_uppercase())
+            && !self.muted_traders.contains(&trade.data.username.to_lowercase())
+            && filter_trade(trade, &self.trade_filter, &self.coin_filter, &self.trader_filter)
+            && (!self.avatar_only || trade.data.has_avatar(&self.placeholder_avatars))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(msg_type: &str, coin_symbol: &str, username: &str) -> Trade {
+        Trade {
+            msg_type: msg_type.to_string(),
+            is_large: msg_type == "live-trade",
+            ..Trade::sample(coin_symbol, username)
+        }
+    }
+
+    #[test]
+    fn empty_filters_match_everything_of_the_right_type() {
+        let t = trade("all-trades", "PEPE", "alice");
+        assert!(filter_trade(&t, &TradeFilter::All, "", ""));
+    }
+
+    #[test]
+    fn all_tab_shows_every_trade_and_large_tab_only_flagged_ones() {
+        let all = trade("all-trades", "PEPE", "alice");
+        let large = trade("live-trade", "PEPE", "alice");
+        assert!(filter_trade(&all, &TradeFilter::All, "", ""));
+        assert!(!filter_trade(&all, &TradeFilter::Large, "", ""));
+        assert!(filter_trade(&large, &TradeFilter::Large, "", ""));
+        assert!(filter_trade(&large, &TradeFilter::All, "", ""));
+    }
+
+    #[test]
+    fn coin_and_trader_filters_ignore_case() {
+        let t = trade("all-trades", "PEPE", "Alice");
+        assert!(filter_trade(&t, &TradeFilter::All, "pepe", ""));
+        assert!(filter_trade(&t, &TradeFilter::All, "", "ALICE"));
+        assert!(filter_trade(&t, &TradeFilter::All, "PePe", "aLiCe"));
+    }
+
+    #[test]
+    fn filters_match_substrings() {
+        let t = trade("all-trades", "PEPECOIN", "alice_trader");
+        assert!(filter_trade(&t, &TradeFilter::All, "pep", ""));
+        assert!(filter_trade(&t, &TradeFilter::All, "", "trader"));
+        assert!(!filter_trade(&t, &TradeFilter::All, "doge", ""));
+        assert!(!filter_trade(&t, &TradeFilter::All, "", "bob"));
+    }
+
+    #[test]
+    fn coin_filter_accepts_a_comma_separated_list() {
+        let pepe = trade("all-trades", "PEPE", "alice");
+        let doge = trade("all-trades", "DOGE", "alice");
+        let shib = trade("all-trades", "SHIB", "alice");
+        assert!(filter_trade(&pepe, &TradeFilter::All, "pepe, doge", ""));
+        assert!(filter_trade(&doge, &TradeFilter::All, "pepe, doge", ""));
+        assert!(!filter_trade(&shib, &TradeFilter::All, "pepe, doge", ""));
+        // Blank entries are ignored rather than matching everything
+        assert!(!filter_trade(&shib, &TradeFilter::All, "pepe, ,", ""));
+        assert!(filter_trade(&shib, &TradeFilter::All, " , ", ""));
+    }
+
+    #[test]
+    fn coin_and_trader_filters_must_both_match() {
+        let t = trade("all-trades", "PEPE", "alice");
+        assert!(filter_trade(&t, &TradeFilter::All, "pepe", "alice"));
+        assert!(!filter_trade(&t, &TradeFilter::All, "pepe", "bob"));
+        assert!(!filter_trade(&t, &TradeFilter::All, "doge", "alice"));
+    }
+
+    #[test]
+    fn render_cap_limits_materialised_trades_but_scrolling_reaches_the_end() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            for i in 0..1000 {
+                items.push_back(trade("all-trades", &format!("C{}", i), "alice"));
+            }
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::new(SharedBuffer::new()), Arc::default());
+        app.trades_viewport_rows.set(20);
+
+        assert!(app.grouped_trades().len() < 1000);
+        assert_eq!(app.filtered_trade_count(), 1000);
+
+        for _ in 0..100 {
+            app.scroll_down_by(FAST_SCROLL_ROWS);
+        }
+        assert_eq!(app.scroll_offset, 999);
+        assert_eq!(app.selected_trade().unwrap().data.coin_symbol, "C999");
+    }
+
+    #[test]
+    fn volume_spike_compares_the_last_window_to_the_one_before() {
+        let now = Local::now();
+        let at = |coin: &str, total: i64, secs_ago: i64| {
+            let mut trade = trade("all-trades", coin, "alice");
+            trade.data.total_value = Decimal::new(total, 0);
+            trade.received_at = now - chrono::Duration::seconds(secs_ago);
+            trade
+        };
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            // Newest first, as the feed stores them
+            items.push_back(at("PEPE", 30, 10));
+            items.push_back(at("DOGE", 10, 20));
+            items.push_back(at("PEPE", 10, 40));
+            items.push_back(at("PEPE", 5, 80));
+            items.push_back(at("DOGE", 10, 90));
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::new(SharedBuffer::new()), Arc::default());
+        app.volume_spike_window_secs = 60;
+        app.volume_spike_multiplier = 3.0;
+
+        assert_eq!(app.detect_volume_spike("PEPE"), Some(8.0));
+        assert_eq!(app.spike_window_volume("PEPE"), Decimal::new(40, 0));
+        assert_eq!(app.detect_volume_spike("DOGE"), None);
+        assert_eq!(app.detect_volume_spike("SHIB"), None);
+    }
+
+    #[test]
+    fn newest_first_view_stops_collecting_matches_at_the_cap() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            for i in 0..1000 {
+                let user = if i % 10 == 0 { "bob" } else { "alice" };
+                items.push_back(trade("all-trades", &format!("C{}", i), user));
+            }
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::new(SharedBuffer::new()), Arc::default());
+        app.muted_traders.insert("bob".to_string());
+        let snapshot = app.trades_snapshot();
+
+        let (rows, matched_count, muted_count) = app.sorted_view(&snapshot, false, Some(20));
+        assert_eq!((rows.len(), matched_count, muted_count), (20, 900, 100));
+        assert_eq!(rows[0].trade.data.coin_symbol, "C1");
+
+        // Any other order needs every match before it can pick the first
+        app.trade_sort = TradeSort::Value;
+        let (rows, matched_count, _) = app.sorted_view(&snapshot, false, Some(20));
+        assert_eq!((rows.len(), matched_count), (900, 900));
+    }
+
+    #[test]
+    fn search_jumps_to_matches_without_hiding_other_trades() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            items.push_back(trade("all-trades", "DOGE", "alice"));
+            items.push_back(trade("all-trades", "PEPE", "bob"));
+            items.push_back(trade("all-trades", "WIF", "pepelover"));
+            items.push_back(trade("all-trades", "BONK", "carol"));
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::new(SharedBuffer::new()), Arc::default());
+
+        app.start_search();
+        for c in "PEp".chars() {
+            app.add_to_input(c);
+        }
+        assert_eq!(app.scroll_offset, 1);
+        app.confirm_search();
+        assert_eq!(app.filtered_trade_count(), 4);
+        assert_eq!(app.search_position(), Some((Some(1), 2)));
+
+        app.step_search(true);
+        assert_eq!(app.scroll_offset, 2);
+        app.step_search(true);
+        assert_eq!(app.scroll_offset, 1);
+        app.step_search(false);
+        assert_eq!(app.scroll_offset, 2);
+
+        app.clear_search();
+        assert_eq!(app.search_position(), None);
+    }
+
+    #[test]
+    fn bookmarks_outlive_the_trades_buffer() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            items.push_back(trade("all-trades", "PEPE", "alice"));
+            items.push_back(trade("all-trades", "DOGE", "bob"));
+        });
+        trades.publish();
+        let mut app = App::new(trades.clone(), Arc::new(SharedBuffer::new()), Arc::default());
+
+        app.scroll_offset = 1;
+        app.toggle_bookmark_selected();
+        assert_eq!(app.bookmarks.len(), 1);
+        assert!(app.is_bookmarked(&app.selected_trade().unwrap()));
+
+        trades.clear();
+        assert_eq!(app.bookmarks[0].data.coin_symbol, "DOGE");
+        assert!(!app.is_live(&app.bookmarks[0]));
+
+        app.remove_selected_bookmark();
+        assert!(app.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn paper_orders_fill_at_the_latest_price_and_keep_the_form_on_errors() {
+        let update = PriceUpdate {
+            current_price: Decimal::new(5, 1),
+            ..PriceUpdate::sample("PEPE")
+        };
+        let price_updates = Arc::new(SharedBuffer::new());
+        price_updates.modify(|items| items.push_back(update.clone()));
+        price_updates.publish();
+        let mut app = App::new(Arc::default(), price_updates, Arc::default());
+        app.session_stats.lock().unwrap().record_price(&update);
+
+        app.start_order(Side::Sell);
+        "pe".chars().for_each(|c| app.order_input(c));
+        app.complete_order_coin();
+        assert_eq!((app.order_form.coin.as_str(), app.order_form.field), ("PEPE", OrderField::Amount));
+        // Gone from the price history, but still the latest price
+        app.price_updates.clear();
+        "10".chars().for_each(|c| app.order_input(c));
+        app.place_order();
+        // Nothing held yet, so the form stays open with the reason
+        assert_eq!(app.input_mode, InputMode::OrderEntry);
+        assert!(app.order_form.error.is_some());
+
+        app.cycle_order_field(false);
+        app.order_input('b');
+        app.place_order();
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert_eq!(app.portfolio.holdings["PEPE"].quantity, Decimal::from(20));
+        assert_eq!(app.portfolio_summary().market_value, Decimal::from(10));
+    }
+
+    #[test]
+    fn grouping_collapses_runs_by_trader_coin_and_side_within_the_window() {
+        let at = |t: &mut Trade, secs: i64, amount: i64| {
+            t.received_at = Local::now() - chrono::Duration::seconds(secs);
+            t.data.amount = Decimal::from(amount);
+            t.data.total_value = Decimal::from(amount);
+        };
+        let mut trades: Vec<Trade> = Vec::new();
+        for (coin, user, secs, amount) in [
+            ("PEPE", "alice", 0, 1),
+            ("PEPE", "alice", 4, 2),
+            ("PEPE", "alice", 9, 3),
+            // Past the window of the run's newest trade
+            ("PEPE", "alice", 12, 4),
+            ("DOGE", "alice", 13, 5),
+            ("DOGE", "bob", 14, 6),
+        ] {
+            let mut t = trade("all-trades", coin, user);
+            at(&mut t, secs, amount);
+            trades.push(t);
+        }
+        let mut sell = trade("all-trades", "DOGE", "bob");
+        sell.data.trade_type = "SELL".to_string();
+        at(&mut sell, 15, 7);
+        trades.push(sell);
+
+        let refs: Vec<&Trade> = trades.iter().collect();
+        let runs = group_repeated_trades(&refs, chrono::Duration::seconds(10));
+        let counts: Vec<(usize, Decimal)> = runs.iter().map(|run| (run.count, run.amount)).collect();
+        assert_eq!(counts, [(3, 6), (1, 4), (1, 5), (1, 6), (1, 7)].map(|(count, amount)| (count, Decimal::from(amount))));
+        assert!(std::ptr::eq(runs[0].trade, &trades[0]));
+
+        let buffer = Arc::new(SharedBuffer::new());
+        buffer.modify(|items| items.extend(trades.iter().cloned()));
+        buffer.publish();
+        let mut app = App::new(buffer, Arc::default(), Arc::default());
+        app.toggle_group_trades();
+        assert_eq!(app.filtered_trade_count(), 5);
+        assert_eq!(app.grouped_trades()[0].total_value, Decimal::from(6));
+        // Copying still takes every individual trade
+        assert_eq!(app.view_trades().len(), 7);
+
+        // Sorting by value sees the run's total
+        app.trade_sort = TradeSort::Value;
+        assert_eq!(app.grouped_trades()[0].trade.data.username, "bob");
+        app.trade_sort = TradeSort::Amount;
+        assert_eq!(app.grouped_trades()[1].count, 3);
+    }
+
+    #[test]
+    fn search_match_ranges_ignore_case() {
+        assert_eq!(search_match_ranges("PepePEPE", "pepe"), vec![0..4, 4..8]);
+        assert_eq!(search_match_ranges("alice", "bob"), Vec::<Range<usize>>::new());
+        assert_eq!(search_match_ranges("alice", ""), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn rolling_volume_sums_visible_trades_inside_the_window() {
+        let valued = |coin_symbol: &str, value: i64, mins_ago: i64| {
+            let mut trade = trade("all-trades", coin_symbol, "alice");
+            trade.data.total_value = Decimal::from(value);
+            trade.received_at = Local::now() - chrono::Duration::minutes(mins_ago);
+            trade
+        };
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            items.push_back(valued("PEPE", 10, 1));
+            items.push_back(valued("DOGE", 5, 30));
+            items.push_back(valued("PEPE", 100, 59));
+            items.push_back(valued("PEPE", 1000, 61));
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::new(SharedBuffer::new()), Arc::default());
+
+        let hour = Duration::from_secs(60 * 60);
+        assert_eq!(app.rolling_volume(hour), Decimal::from(115));
+        app.coin_filter = "pepe".to_string();
+        assert_eq!(app.rolling_volume(hour), Decimal::from(110));
+        assert_eq!(app.rolling_volume(Duration::from_secs(5 * 60)), Decimal::from(10));
+    }
+
+    #[test]
+    fn buy_sell_ratio_leaves_out_other_trade_types() {
+        let side = |trade_type: &str, total: i64| {
+            let mut trade = trade("all-trades", "PEPE", "alice");
+            trade.data.trade_type = trade_type.to_string();
+            trade.data.total_value = Decimal::from(total);
+            trade
+        };
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            items.push_back(side("TRANSFER", 1000));
+            items.push_back(side("BUY", 30));
+            items.push_back(side("SELL", 10));
+            items.push_back(side("SELL", 5));
+        });
+        trades.publish();
+        let app = App::new(trades, Arc::default(), Arc::default());
+        // The window counts buys and sells only
+        assert_eq!(app.buy_sell_ratio("PEPE", 2), (Decimal::from(30), Decimal::from(10)));
+        assert_eq!(app.buy_sell_ratio("PEPE", 10), (Decimal::from(30), Decimal::from(15)));
+    }
+
+    #[test]
+    fn flow_by_minute_only_counts_sells_as_sells() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            for (trade_type, total) in [("BUY", 3), ("SELL", 2), ("TRANSFER", 100)] {
+                let mut trade = trade("all-trades", "PEPE", "alice");
+                trade.data.trade_type = trade_type.to_string();
+                trade.data.total_value = Decimal::from(total);
+                items.push_back(trade);
+            }
+        });
+        trades.publish();
+        let app = App::new(trades, Arc::default(), Arc::default());
+        let minutes = app.flow_by_minute(2);
+        let total = |value: fn(&FlowBucket) -> f64| minutes.iter().map(value).sum::<f64>();
+        assert_eq!(total(|bucket| bucket.buy_value), 3.0);
+        assert_eq!(total(|bucket| bucket.sell_value), 2.0);
+        assert_eq!(minutes.iter().map(|bucket| bucket.trade_count).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn buy_sell_counts_follow_the_view() {
+        let side = |coin_symbol: &str, trade_type: &str, msg_type: &str| {
+            let mut trade = trade(msg_type, coin_symbol, "alice");
+            trade.data.trade_type = trade_type.to_string();
+            trade
+        };
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            items.push_back(side("PEPE", "BUY", "live-trade"));
+            items.push_back(side("PEPE", "SELL", "all-trades"));
+            items.push_back(side("PEPE", "BUY", "all-trades"));
+            items.push_back(side("DOGE", "SELL", "all-trades"));
+            // Neither a buy nor a sell
+            items.push_back(side("DOGE", "TRANSFER", "all-trades"));
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::default(), Arc::default());
+        assert_eq!(app.buy_sell_counts(), (2, 2));
+        app.coin_filter = "pepe".to_string();
+        assert_eq!(app.buy_sell_counts(), (2, 1));
+        app.trade_filter = TradeFilter::Large;
+        assert_eq!(app.buy_sell_counts(), (1, 0));
+        app.coin_filter = "doge".to_string();
+        assert_eq!(app.buy_sell_counts(), (0, 0));
+    }
+
+    #[test]
+    fn split_panes_keep_their_own_scroll_offsets() {
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        app.scroll_offset = 7;
+        app.switch_page();
+        assert_eq!(app.scroll_offset, 0);
+
+        app.split_panes.set(Some((Rect::new(0, 3, 90, 30), Rect::new(90, 3, 60, 30))));
+        app.scroll_offset = 4;
+        app.switch_page();
+        assert_eq!((app.current_page.clone(), app.scroll_offset), (AppPage::Trades, 0));
+        app.scroll_offset = 9;
+        assert_eq!(app.pane_scroll_offset(&AppPage::PriceTracker), 4);
+
+        app.focus_pane_at(100, 10);
+        assert_eq!((app.current_page.clone(), app.scroll_offset), (AppPage::PriceTracker, 4));
+        assert_eq!(app.pane_scroll_offset(&AppPage::Trades), 9);
+        app.focus_pane_at(95, 1);
+        assert_eq!(app.current_page, AppPage::PriceTracker);
+    }
+
+    #[test]
+    fn split_divider_moves_in_clamped_steps_and_clamps_scrolling() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            for _ in 0..5 {
+                items.push_back(trade("all-trades", "PEPE", "alice"));
+            }
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::default(), Arc::default());
+        app.other_pane_scroll = 3;
+        app.scroll_offset = 40;
+        app.current_page = AppPage::PriceTracker;
+
+        app.resize_split(1);
+        assert_eq!(app.split_percent, 65);
+        // Nothing is tracked, so the price pane goes back to the top
+        assert_eq!((app.scroll_offset, app.other_pane_scroll), (0, 3));
+        app.resize_split(10);
+        assert_eq!(app.split_percent, 80);
+        app.resize_split(-20);
+        assert_eq!(app.split_percent, 20);
+
+        app.split_panes.set(Some((Rect::new(0, 3, 100, 30), Rect::new(100, 3, 100, 30))));
+        assert!(!app.press_divider(99, 10));
+        assert!(app.press_divider(100, 10));
+        app.drag_divider(150);
+        assert_eq!(app.split_percent, 75);
+        app.release_divider();
+        app.drag_divider(50);
+        assert_eq!(app.split_percent, 75);
+    }
+
+    #[test]
+    fn size_histogram_follows_filters_new_trades_and_evictions() {
+        let sized = |coin_symbol: &str, value: i64, secs_ago: i64| {
+            let mut trade = trade("all-trades", coin_symbol, "alice");
+            trade.data.total_value = Decimal::from(value);
+            trade.received_at = Local::now() - chrono::Duration::seconds(secs_ago);
+            trade
+        };
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            items.push_back(sized("PEPE", 500, 60));
+            items.push_back(sized("DOGE", 50, 61));
+            items.push_back(sized("PEPE", 5, 62));
+        });
+        trades.publish();
+        let mut app = App::new(trades.clone(), Arc::new(SharedBuffer::new()), Arc::default());
+        assert_eq!(app.size_histogram().counts, [1, 1, 1, 0, 0]);
+
+        app.start_coin_filter();
+        app.add_to_input('p');
+        assert_eq!(app.size_histogram().counts, [1, 0, 1, 0, 0]);
+        app.confirm_filter();
+
+        // A fresh trade is rescanned until it settles, then counted once
+        trades.modify(|items| items.push_front(sized("PEPE", 20_000, 0)));
+        trades.publish();
+        assert_eq!(app.size_histogram().counts, [1, 0, 1, 0, 1]);
+        trades.publish();
+        assert_eq!(app.size_histogram().counts, [1, 0, 1, 0, 1]);
+        assert_eq!(app.size_histogram().values[4], 20_000.0);
+
+        // The oldest trade leaving the buffer leaves the histogram too
+        trades.modify(|items| {
+            items.pop_back();
+        });
+        trades.publish();
+        assert_eq!(app.size_histogram().counts, [0, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn filters_are_restored_only_when_persisted() {
+        let state = UiState {
+            version: STATE_VERSION,
+            tracked_coin: Some("PEPE".to_string()),
+            coin_filter: "PEPE".to_string(),
+            trader_filter: "alice".to_string(),
+            trade_filter: TradeFilter::Large,
+            page: AppPage::PriceTracker,
+            split_percent: 70,
+            bookmarks: Vec::new(),
+        };
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        assert_eq!(app.restore_ui_state(state.clone()), Some("PEPE".to_string()));
+        assert_eq!((app.coin_filter.as_str(), app.trader_filter.as_str()), ("", ""));
+        assert_eq!((&app.trade_filter, &app.current_page), (&TradeFilter::Large, &AppPage::PriceTracker));
+        app.coin_filter = "DOGE".to_string();
+        assert_eq!(app.ui_state().coin_filter, "");
+
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        app.apply_config(Config { persist_filters: true, ..Config::default() }, None);
+        app.restore_ui_state(state.clone());
+        assert_eq!((app.coin_filter.as_str(), app.trader_filter.as_str()), ("PEPE", "alice"));
+        assert_eq!(app.ui_state(), state);
+    }
+
+    #[test]
+    fn linked_filter_tracks_only_exact_known_coins() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            items.push_back(trade("all-trades", "PEPE", "alice"));
+            items.push_back(trade("all-trades", "DOGE", "bob"));
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::new(SharedBuffer::new()), Arc::default());
+
+        app.start_coin_filter();
+        app.input_buffer = "pepe".to_string();
+        assert_eq!(app.confirm_filter(), None);
+
+        app.toggle_coin_link();
+        app.start_coin_filter();
+        app.input_buffer = "pe".to_string();
+        assert_eq!(app.confirm_filter(), None);
+        app.start_coin_filter();
+        app.input_buffer = "pepe,doge".to_string();
+        assert_eq!(app.confirm_filter(), None);
+        app.start_coin_filter();
+        app.input_buffer = "pepe".to_string();
+        assert_eq!(app.confirm_filter(), Some("PEPE".to_string()));
+        assert_eq!(app.tracked_coin.as_deref(), Some("PEPE"));
+
+        // Tracking another coin offers it as the filter
+        app.input_buffer = "doge".to_string();
+        app.confirm_coin_selection();
+        assert_eq!(app.input_mode, InputMode::ConfirmLinkFilter);
+        app.accept_link_filter_offer();
+        assert_eq!(app.coin_filter, "DOGE");
+        assert_eq!(app.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn typed_filter_applies_live_and_enter_keeps_it() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            items.push_back(trade("all-trades", "PEPE", "alice"));
+            items.push_back(trade("all-trades", "DOGE", "bob"));
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::new(SharedBuffer::new()), Arc::default());
+
+        app.start_coin_filter();
+        app.add_to_input('d');
+        assert_eq!(app.filtered_trade_count(), 1);
+        assert_eq!(app.grouped_trades()[0].trade.data.coin_symbol, "DOGE");
+
+        app.confirm_filter();
+        assert_eq!(app.filtered_trade_count(), 1);
+
+        app.start_trader_filter();
+        app.add_to_input('x');
+        assert_eq!(app.filtered_trade_count(), 0);
+        app.cancel_filter();
+        assert_eq!(app.filtered_trade_count(), 1);
+
+        app.scroll_offset = 1;
+        app.clear_filters();
+        assert_eq!((app.coin_filter.as_str(), app.trader_filter.as_str()), ("", ""));
+        assert_eq!((app.filtered_trade_count(), app.scroll_offset), (2, 0));
+    }
+
+    #[test]
+    fn millisecond_toggle_switches_the_time_format_and_persists() {
+        use chrono::TimeZone;
+        let at = Local.timestamp_millis_opt(1_700_000_000_123).unwrap();
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        assert_eq!(at.format(app.time_format()).to_string().len(), 8);
+
+        app.toggle_millisecond_timestamps();
+        assert!(at.format(app.time_format()).to_string().ends_with(".123"));
+        assert!(app.to_config().millisecond_timestamps);
+    }
+
+    #[test]
+    fn price_range_filters_the_history_and_is_kept_per_coin() {
+        let session_stats = Arc::new(Mutex::new(SessionStats::default()));
+        let mut app = App::new(Arc::default(), Arc::default(), session_stats.clone());
+        let record = |coin: &str, minutes_ago| {
+            session_stats.lock().unwrap().record_history(&PriceUpdate {
+                received_at: Local::now() - chrono::Duration::minutes(minutes_ago),
+                ..PriceUpdate::sample(coin)
+            });
+        };
+        for (coin, minutes_ago) in [("PEPE", 40), ("PEPE", 10), ("PEPE", 1), ("DOGE", 1)] {
+            record(coin, minutes_ago);
+        }
+        app.track_coin("PEPE");
+        app.current_page = AppPage::PriceTracker;
+        assert_eq!(app.tracked_price_history().len(), 3);
+        app.scroll_offset = 2;
+
+        app.select_price_range(PriceRange::ThirtyMinutes);
+        assert_eq!(app.tracked_price_history().len(), 2);
+        // Scrolling stays inside the filtered list
+        assert_eq!(app.scroll_offset, 1);
+        app.select_price_range(PriceRange::FiveMinutes);
+        assert_eq!(app.tracked_price_history().len(), 1);
+
+        app.track_coin("DOGE");
+        assert_eq!(app.price_range(), PriceRange::All);
+        app.track_coin("PEPE");
+        assert_eq!(app.price_range(), PriceRange::FiveMinutes);
+
+        // The cached history picks up a new update
+        record("PEPE", 0);
+        assert_eq!(app.tracked_price_history().len(), 2);
+    }
+
+    #[test]
+    fn idles_after_a_quiet_spell_and_wakes_on_input_or_data() {
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        app.apply_config(serde_json::from_str(r#"{"idle_dim_secs": 60}"#).unwrap(), None);
+        let start = Instant::now();
+        assert!(!app.update_idle(start + Duration::from_secs(59)));
+        assert!(app.update_idle(start + Duration::from_secs(61)));
+        assert!(app.idle);
+
+        app.note_input();
+        assert!(!app.idle);
+        assert!(!app.update_idle(Instant::now() + Duration::from_secs(30)));
+
+        // A feed message counts as much as a key
+        app.metrics.record_message();
+        assert!(!app.update_idle(Instant::now() + Duration::from_secs(59)));
+        assert!(app.update_idle(Instant::now() + Duration::from_secs(61)));
+
+        app.apply_config(serde_json::from_str(r#"{"idle_dim_secs": 0}"#).unwrap(), None);
+        assert!(app.update_idle(Instant::now() + Duration::from_secs(3600)));
+        assert!(!app.idle);
+    }
+
+    #[test]
+    fn color_basis_switches_between_the_feed_and_session_change() {
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        let mut session = SessionStats::default();
+        let mut update = PriceUpdate {
+            current_price: Decimal::new(2, 0),
+            change_24h: -12.5,
+            ..PriceUpdate::sample("PEPE")
+        };
+        session.record_price(&update);
+        update.current_price = Decimal::new(3, 0);
+        assert_eq!(app.basis_change(&session, &update), -12.5);
+
+        app.cycle_color_basis();
+        assert_eq!(app.basis_change(&session, &update), 50.0);
+        assert_eq!(app.to_config().color_basis, ColorBasis::Session);
+        // A coin the session hasn't priced yet has no change to show
+        update.coin_symbol = "DOGE".to_string();
+        assert!(app.basis_change(&session, &update).is_nan());
+    }
+
+    #[test]
+    fn trade_counts_are_sorted_busiest_first() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            for (coin, user) in [("PEPE", "alice"), ("DOGE", "bob"), ("PEPE", "carol"), ("BONK", "dave")] {
+                items.push_back(trade("all-trades", coin, user));
+            }
+        });
+        trades.publish();
+        let app = App::new(trades, Arc::default(), Arc::default());
+        let expected = [("PEPE", 2), ("BONK", 1), ("DOGE", 1)].map(|(coin, count)| (coin.to_string(), count));
+        assert_eq!(app.trade_counts_by_coin(), expected);
+    }
+
+    #[test]
+    fn avatar_filter_hides_traders_without_their_own_picture() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            for (username, image) in [("alice", "avatars/1.png"), ("bob", ""), ("carol", "avatars/default.png")] {
+                let mut trade = trade("all-trades", "PEPE", username);
+                trade.data.user_image = Some(image.to_string());
+                items.push_back(trade);
+            }
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::default(), Arc::default());
+        assert_eq!(app.filtered_trade_count(), 3);
+        app.toggle_avatar_only();
+        let usernames: Vec<String> = app.grouped_trades().iter().map(|row| row.trade.data.username.clone()).collect();
+        assert_eq!(usernames, ["alice"]);
+    }
+
+    #[test]
+    fn exec_filter_follows_the_view_once_published() {
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        let filter = app.exec_filter.subscribe();
+        assert!(filter.borrow().is_none());
+
+        app.publish_exec_filter();
+        let pepe = trade("all-trades", "PEPE", "alice");
+        assert!(filter.borrow().as_ref().unwrap().matches(&pepe));
+
+        app.muted_coins.insert("PEPE".to_string());
+        app.publish_exec_filter();
+        assert!(!filter.borrow().as_ref().unwrap().matches(&pepe));
+        app.muted_coins.clear();
+        app.trade_filter = TradeFilter::Large;
+        app.publish_exec_filter();
+        assert!(!filter.borrow().as_ref().unwrap().matches(&pepe));
+        assert!(filter.borrow().as_ref().unwrap().matches(&trade("live-trade", "PEPE", "alice")));
+    }
+
+    #[test]
+    fn since_window_hides_older_trades_and_aged_ones_dim() {
+        let trades = Arc::new(SharedBuffer::new());
+        trades.modify(|items| {
+            for (username, mins_ago) in [("alice", 1), ("bob", 4), ("carol", 12)] {
+                let mut trade = trade("all-trades", "PEPE", username);
+                trade.received_at = Local::now() - chrono::Duration::minutes(mins_ago);
+                items.push_back(trade);
+            }
+        });
+        trades.publish();
+        let mut app = App::new(trades, Arc::default(), Arc::default());
+
+        // Only carol's trade is past the default ten minutes
+        let now = Local::now();
+        let aged: Vec<bool> = app.grouped_trades().iter().map(|row| app.is_aged(&row.trade, now)).collect();
+        assert_eq!(aged, [false, false, true]);
+
+        app.set_trade_since(Some(Duration::from_secs(5 * 60)));
+        assert_eq!(app.filtered_trade_count(), 2);
+        assert_eq!(app.size_histogram().counts[0], 2);
+        // The window combines with the other filters
+        app.trader_filter = "bob".to_string();
+        assert_eq!(app.filtered_trade_count(), 1);
+        app.trader_filter.clear();
+
+        // A later frame moves the window past bob's trade
+        app.begin_frame(now + chrono::Duration::minutes(2));
+        assert_eq!(app.filtered_trade_count(), 1);
+        assert_eq!(app.size_histogram().counts[0], 1);
+
+        app.set_trade_since(None);
+        assert_eq!(app.filtered_trade_count(), 3);
+        app.loaded_config.trade_dim_age_secs = 0;
+        assert!(!app.is_aged(&app.grouped_trades()[2].trade, now));
+    }
+
+    #[test]
+    fn a_config_that_cant_be_saved_is_logged() {
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        // A directory can't be written over
+        app.config_path = Some(std::env::temp_dir());
+        app.toggle_status_bar();
+        let error = app.events.errors().next().expect("no error logged");
+        assert!(error.message.starts_with("Couldn't save the config"), "{}", error.message);
+    }
+
+    #[test]
+    fn unknown_feed_names_are_capped() {
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        for batch in 0..3 {
+            app.record_unknown_trade_fields((0..MAX_UNKNOWN_NAMES).map(|i| format!("field{}-{}", batch, i)).collect());
+            app.record_unknown_message_type(format!("type{}", batch));
+        }
+        assert_eq!(app.unknown_trade_fields.len(), MAX_UNKNOWN_NAMES);
+        assert_eq!(app.unknown_message_types.len(), 3);
+    }
+
+    #[test]
+    fn uptime_runs_only_while_connected() {
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        assert_eq!(app.connection_uptime(), None);
+
+        app.set_feed_state(FeedState::Connected);
+        let first = app.connected_since.unwrap();
+        assert!(app.connection_uptime().is_some());
+
+        app.set_feed_state(FeedState::Disconnected);
+        assert_eq!(app.connection_uptime(), None);
+
+        // A reconnect starts counting again
+        std::thread::sleep(Duration::from_millis(5));
+        app.set_feed_state(FeedState::Connected);
+        assert!(app.connected_since.unwrap() > first);
+    }
+
+    #[test]
+    fn quick_filters_narrow_to_exactly_the_clicked_coin_or_trader() {
+        let mut app = App::new(Arc::default(), Arc::default(), Arc::default());
+        app.quick_filter_regions.borrow_mut().extend([
+            QuickFilterRegion { area: Rect::new(10, 4, 5, 1), filter: QuickFilter::Trader("alice".to_string()) },
+            QuickFilterRegion { area: Rect::new(3, 5, 4, 1), filter: QuickFilter::Coin("PEPE".to_string()) },
+        ]);
+        assert_eq!(app.quick_filter_at(9, 4), None);
+        assert_eq!(app.quick_filter_at(15, 4), None);
+        assert_eq!(app.quick_filter_at(14, 4), Some(QuickFilter::Trader("alice".to_string())));
+
+        app.scroll_offset = 7;
+        let filter = app.quick_filter_at(3, 5).unwrap();
+        assert_eq!(app.apply_quick_filter(filter), None);
+        assert_eq!((app.coin_filter.as_str(), app.scroll_offset), ("PEPE", 0));
+        app.apply_quick_filter(QuickFilter::Trader("alice".to_string()));
+        assert_eq!(app.trader_filter, "alice");
+
+        app.handle_resize();
+        assert_eq!(app.quick_filter_at(14, 4), None);
+    }
+}
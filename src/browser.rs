@@ -0,0 +1,103 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+/// Fills `{name}` placeholders in a URL template, percent-encoding each value.
+pub fn expand_url(template: &str, values: &[(&str, &str)]) -> String {
+    values.iter().fold(template.to_string(), |url, (name, value)| {
+        url.replace(&format!("{{{}}}", name), &percent_encode(value))
+    })
+}
+
+/// Turns an image path from the feed into a full URL. Absolute URLs are
+/// kept as they are, relative ones are percent-encoded segment by segment
+/// and joined onto `base`.
+pub fn image_url(base: &str, path: &str) -> Option<String> {
+    let path = path.trim();
+    if path.is_empty() {
+        None
+    } else if path.starts_with("http://") || path.starts_with("https://") {
+        Some(path.to_string())
+    } else {
+        let path: Vec<String> = path.trim_start_matches('/').split('/').map(percent_encode).collect();
+        Some(format!("{}/{}", base.trim_end_matches('/'), path.join("/")))
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Opens `url` with the platform's default handler in a detached process.
+/// Its output is discarded so it can't draw over the terminal UI.
+pub fn open_url(url: &str) -> io::Result<()> {
+    let mut command = opener_command(url);
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // Reap the opener when it exits instead of leaving a zombie behind
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn opener_command(url: &str) -> Command {
+    let mut command = Command::new("open");
+    command.arg(url);
+    command
+}
+
+// Not `cmd /C start`, which would run whatever follows a `&` or `|` in
+// the URL
+#[cfg(target_os = "windows")]
+fn opener_command(url: &str) -> Command {
+    let mut command = Command::new("rundll32");
+    command.args(["url.dll,FileProtocolHandler", url]);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn opener_command(url: &str) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(url);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_percent_encoded_into_the_template() {
+        assert_eq!(percent_encode("a-Z_0.~"), "a-Z_0.~");
+        assert_eq!(percent_encode("a b&c|d/é"), "a%20b%26c%7Cd%2F%C3%A9");
+        assert_eq!(
+            expand_url("https://rugplay.com/coin/{coin}?u={user}", &[("coin", "PEPE"), ("user", "al ice&x")]),
+            "https://rugplay.com/coin/PEPE?u=al%20ice%26x"
+        );
+        // Unknown placeholders are left alone
+        assert_eq!(expand_url("/{other}", &[("coin", "PEPE")]), "/{other}");
+    }
+
+    #[test]
+    fn relative_image_paths_are_encoded_and_joined_onto_the_base() {
+        let base = "https://rugplay.com/";
+        assert_eq!(image_url(base, " "), None);
+        assert_eq!(image_url(base, "https://cdn.example/a.png").as_deref(), Some("https://cdn.example/a.png"));
+        assert_eq!(image_url(base, "/coins/pepe.png").as_deref(), Some("https://rugplay.com/coins/pepe.png"));
+        assert_eq!(
+            image_url(base, "coins/a&calc|b.png").as_deref(),
+            Some("https://rugplay.com/coins/a%26calc%7Cb.png")
+        );
+    }
+}
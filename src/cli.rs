@@ -0,0 +1,138 @@
+use clap::Parser;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+
+// Tabs, filters, help bar and a few rows of content
+const MIN_INLINE_HEIGHT: u16 = 12;
+
+#[derive(Debug, Parser)]
+#[command(name = "rugplay-terminal", about = "Real-time rugplay.com trade and price monitor")]
+pub struct Args {
+    /// Path to the config file (defaults to ~/.config/rug-listener/config.json)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Maximum number of trades kept in memory
+    #[arg(long, value_parser = parse_limit)]
+    pub max_trades: Option<usize>,
+
+    /// Maximum number of price updates kept in memory
+    #[arg(long, value_parser = parse_limit)]
+    pub max_price_updates: Option<usize>,
+
+    /// Drop trades and price updates older than this, e.g. 90s, 30m, 2h
+    #[arg(long, value_parser = parse_retention)]
+    pub retain: Option<Duration>,
+
+    /// Keep every price tick, including ones identical to the previous update
+    #[arg(long)]
+    pub keep_duplicate_prices: bool,
+
+    /// Quit immediately on q, even with data buffered
+    #[arg(long)]
+    pub no_confirm_quit: bool,
+
+    /// Start without restoring the tracked coin, filters and page from the
+    /// last session
+    #[arg(long)]
+    pub fresh: bool,
+
+    /// Draw below the prompt in a ROWS-high area (default 30) instead of
+    /// taking over the whole screen
+    #[arg(long, value_name = "ROWS", num_args = 0..=1, default_missing_value = "30", value_parser = parse_inline_height)]
+    pub inline: Option<u16>,
+
+    /// Serve read-only JSON snapshots (/trades, /prices, /health) on
+    /// 127.0.0.1:PORT
+    #[arg(long, value_name = "PORT")]
+    pub serve: Option<u16>,
+
+    /// Stream every new trade as a JSON line to clients of a Unix socket
+    /// created at PATH
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+
+    /// Leave the mouse to the terminal so text can be selected and copied
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// Pipe every trade in view (tab, filters and mutes) to COMMAND's stdin
+    /// as one JSON object per line. COMMAND runs through the shell and
+    /// stays running
+    #[arg(long, value_name = "COMMAND")]
+    pub exec: Option<String>,
+
+    /// Start the --exec command again whenever it exits
+    #[arg(long, requires = "exec")]
+    pub exec_restart: bool,
+
+    /// Use the palette for light terminal backgrounds (picked automatically
+    /// when COLORFGBG reports one)
+    #[arg(long)]
+    pub light: bool,
+
+    /// Also show alerts as desktop notifications (needs a build with the
+    /// `desktop-notifications` feature)
+    #[arg(long)]
+    pub desktop_notifications: bool,
+
+    /// Write diagnostics to PATH (defaults to
+    /// ~/.local/state/rug-listener/rug-listener.log)
+    #[arg(long, value_name = "PATH")]
+    pub log: Option<PathBuf>,
+
+    /// Least severe diagnostics logged: off, error, warn, info, debug or
+    /// trace
+    #[arg(long, value_name = "LEVEL", default_value = "info")]
+    pub log_level: LevelFilter,
+}
+
+fn parse_limit(value: &str) -> Result<usize, String> {
+    let limit: usize = value.parse().map_err(|_| format!("`{}` is not a valid count", value))?;
+    if limit == 0 {
+        return Err("limit must be at least 1".to_string());
+    }
+    Ok(limit)
+}
+
+fn parse_inline_height(value: &str) -> Result<u16, String> {
+    let rows: u16 = value.parse().map_err(|_| format!("`{}` is not a valid row count", value))?;
+    if rows < MIN_INLINE_HEIGHT {
+        return Err(format!("the inline view needs at least {} rows", MIN_INLINE_HEIGHT));
+    }
+    Ok(rows)
+}
+
+/// Parses `<number><unit>` where unit is one of s, m, h or d. A bare number
+/// is taken as seconds.
+pub fn parse_retention(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("`{}` is not a valid duration (expected e.g. 90s, 30m, 2h)", value))?;
+    let multiplier = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("unknown duration unit `{}` (expected s, m, h or d)", unit)),
+    };
+    if number == 0 {
+        return Err("retention must be greater than zero".to_string());
+    }
+    Ok(Duration::from_secs(number * multiplier))
+}
+
+/// The inverse of [`parse_retention`]: the largest unit that divides the
+/// duration evenly, so 300 seconds reads `5m` and 90 reads `90s`.
+pub fn format_retention(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    [(24 * 60 * 60, "d"), (60 * 60, "h"), (60, "m")]
+        .into_iter()
+        .find(|&(unit, _)| seconds > 0 && seconds.is_multiple_of(unit))
+        .map_or_else(|| format!("{}s", seconds), |(unit, suffix)| format!("{}{}", seconds / unit, suffix))
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use std::collections::hash_map::RandomState;
+use std::fs::{File, OpenOptions};
+use std::hash::BuildHasher;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+const FALLBACK_FILE_PREFIX: &str = "rug-listener-clipboard";
+/// Names tried before giving up, in case an earlier one was taken.
+const FALLBACK_ATTEMPTS: usize = 8;
+
+/// Where copied text ended up.
+#[derive(Debug)]
+pub enum CopyTarget {
+    Clipboard,
+    /// No clipboard is reachable (e.g. over SSH), so the text was written here
+    File(PathBuf),
+}
+
+// Kept alive for the whole run: on X11 the copied text is only served while
+// the clipboard handle that set it still exists.
+static CLIPBOARD: OnceLock<Mutex<Option<arboard::Clipboard>>> = OnceLock::new();
+
+/// Copies `text` to the system clipboard, falling back to a file in the temp
+/// directory when there's no clipboard to talk to.
+pub fn copy(text: &str) -> Result<CopyTarget> {
+    let clipboard = CLIPBOARD.get_or_init(|| Mutex::new(arboard::Clipboard::new().ok()));
+    if let Some(clipboard) = clipboard.lock().unwrap().as_mut() {
+        if clipboard.set_text(text).is_ok() {
+            return Ok(CopyTarget::Clipboard);
+        }
+    }
+
+    let (path, mut file) = create_fallback_file()?;
+    file.write_all(text.as_bytes())?;
+    Ok(CopyTarget::File(path))
+}
+
+/// Creates a new file with an unguessable name in the temp directory. It's
+/// never opened if it already exists, so a file or link planted at the
+/// name can't be written through.
+fn create_fallback_file() -> io::Result<(PathBuf, File)> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut last_error = None;
+    for _ in 0..FALLBACK_ATTEMPTS {
+        // Every RandomState is seeded afresh from the OS
+        let suffix = RandomState::new().hash_one(std::process::id());
+        let path = std::env::temp_dir().join(format!("{}-{:016x}.txt", FALLBACK_FILE_PREFIX, suffix));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => last_error = Some(e),
+            Err(e) => return Err(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| io::Error::other("no free file name")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_files_are_new_and_never_reused() {
+        let (first, _) = create_fallback_file().unwrap();
+        let (second, _) = create_fallback_file().unwrap();
+        assert_ne!(first, second);
+        for path in [first, second] {
+            assert!(path.file_name().unwrap().to_string_lossy().starts_with(FALLBACK_FILE_PREFIX));
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
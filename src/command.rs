@@ -0,0 +1,233 @@
+//! The `:` command line. Commands run the same [`Action`] a key would, so
+//! both go through one dispatch path; only those carrying text a key would
+//! have prompted for, such as a filter or a path, have their own variant.
+//! Arguments are split on whitespace, and quoting one keeps its spaces.
+
+use crate::keymap::Action;
+use crate::models::ExportFormat;
+use crate::theme::Theme;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Run(Action),
+    CoinFilter(String),
+    TraderFilter(String),
+    Track(String),
+    Export(ExportFormat, PathBuf),
+    ExportBookmarks(ExportFormat, PathBuf),
+    PriceThrottle(Duration),
+    TradeSince(Option<Duration>),
+}
+
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+}
+
+const fn spec(name: &'static str, usage: &'static str, description: &'static str) -> CommandSpec {
+    CommandSpec { name, usage, description }
+}
+
+/// Every command, in the order the help overlay lists them.
+pub const COMMANDS: &[CommandSpec] = &[
+    spec("coin", "coin [SYMBOLS]", "Set the coin filter (comma-separated; empty clears it)"),
+    spec("trader", "trader [NAME]", "Set the trader filter (empty clears it)"),
+    spec("track", "track SYMBOL", "Track a coin in the Price Tracker"),
+    spec("since", "since [DURATION]", "Hide trades older than a window like 5m (empty clears it)"),
+    spec("all", "all", "Show all trades"),
+    spec("large", "large", "Show large trades only"),
+    spec("export", "export [bookmarks] csv|tsv PATH", "Write the visible (or bookmarked) trades to a file (quote a PATH with spaces)"),
+    spec("bookmarks", "bookmarks", "Show bookmarked trades"),
+    spec("clear", "clear [filters|all]", "Clear the current page, the filters or everything"),
+    spec("sort", "sort", "Cycle the sort order"),
+    spec("throttle", "throttle MILLISECONDS", "Keep one price update per coin per interval in the history (0 keeps all)"),
+    spec("split", "split", "Toggle split view"),
+    spec("theme", "theme dark|light|colorblind", "Switch the color palette for this session"),
+    spec("pause", "pause", "Pause/resume the display"),
+    spec("help", "help", "Show the key bindings"),
+    spec("quit", "quit", "Quit"),
+];
+
+/// Parses a command line (without the leading `:`).
+pub fn parse(input: &str) -> Result<Command, String> {
+    let input = input.trim();
+    let (name, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    let rest = rest.trim();
+    let words = split_args(rest)?;
+    let args: Vec<&str> = words.iter().map(String::as_str).collect();
+    let usage = || {
+        let usage = COMMANDS.iter().find(|command| command.name == name).map_or(name, |command| command.usage);
+        format!("Usage: :{}", usage)
+    };
+    let no_args = |command: Command| if args.is_empty() { Ok(command) } else { Err(usage()) };
+
+    match name {
+        "" => Err("Type a command, Tab completes its name".to_string()),
+        "coin" => Ok(Command::CoinFilter(rest.to_string())),
+        "trader" => Ok(Command::TraderFilter(rest.to_string())),
+        "track" => match args[..] {
+            [symbol] => Ok(Command::Track(symbol.to_uppercase())),
+            _ => Err(usage()),
+        },
+        "since" => match args[..] {
+            [] => Ok(Command::TradeSince(None)),
+            [window] => crate::cli::parse_retention(window).map(|window| Command::TradeSince(Some(window))),
+            _ => Err(usage()),
+        },
+        "all" => no_args(Command::Run(Action::ShowAllTrades)),
+        "large" => no_args(Command::Run(Action::ShowLargeTrades)),
+        "export" => {
+            let (export, format, path): (fn(ExportFormat, PathBuf) -> Command, _, _) = match args[..] {
+                [format, path] => (Command::Export, format, path),
+                ["bookmarks", format, path] => (Command::ExportBookmarks, format, path),
+                _ => return Err(usage()),
+            };
+            ExportFormat::parse(format)
+                .map(|format| export(format, PathBuf::from(path)))
+                .ok_or_else(|| format!("Unknown export format `{}` (expected csv or tsv)", format))
+        }
+        "bookmarks" => no_args(Command::Run(Action::OpenBookmarks)),
+        "clear" => match args[..] {
+            [] => Ok(Command::Run(Action::ClearPage)),
+            ["filters"] => Ok(Command::Run(Action::ClearFilters)),
+            ["all"] => Ok(Command::Run(Action::ClearAll)),
+            _ => Err(usage()),
+        },
+        "sort" => no_args(Command::Run(Action::CycleSort)),
+        "throttle" => match args[..] {
+            [millis] => millis
+                .parse()
+                .map(|millis| Command::PriceThrottle(Duration::from_millis(millis)))
+                .map_err(|_| format!("`{}` isn't a number of milliseconds", millis)),
+            _ => Err(usage()),
+        },
+        "split" => no_args(Command::Run(Action::ToggleSplit)),
+        "theme" => match args[..] {
+            [name] => Theme::named(name)
+                .map(|theme| Command::Run(Action::SetTheme(theme)))
+                .ok_or_else(|| format!("Unknown theme `{}` (expected dark, light or colorblind)", name)),
+            _ => Err(usage()),
+        },
+        "pause" => no_args(Command::Run(Action::TogglePause)),
+        "help" => no_args(Command::Run(Action::ShowHelp)),
+        "quit" | "q" => no_args(Command::Run(Action::Quit)),
+        _ => Err(format!("Unknown command `{}`", name)),
+    }
+}
+
+/// Splits `input` into arguments at whitespace, except inside single or
+/// double quotes, which are dropped.
+fn split_args(input: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote = None;
+    for c in input.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("Unclosed quote".to_string());
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+/// Completes the command name being typed: the full name (plus a space) when
+/// only one command matches, otherwise as far as the matches agree. Returns
+/// `None` once there's nothing more to add.
+pub fn complete(input: &str) -> Option<String> {
+    if input.contains(char::is_whitespace) {
+        return None;
+    }
+    let matches: Vec<&str> = COMMANDS
+        .iter()
+        .map(|command| command.name)
+        .filter(|name| name.starts_with(input))
+        .collect();
+    let completed = match matches[..] {
+        [] => return None,
+        [name] => format!("{} ", name),
+        [first, ..] => {
+            let shared = matches
+                .iter()
+                .map(|name| first.chars().zip(name.chars()).take_while(|(a, b)| a == b).count())
+                .min()
+                .unwrap_or(0);
+            first.chars().take(shared).collect()
+        }
+    };
+    (completed != input).then_some(completed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_map_to_actions_and_arguments() {
+        assert_eq!(parse("coin pepe, doge"), Ok(Command::CoinFilter("pepe, doge".to_string())));
+        assert_eq!(parse("coin"), Ok(Command::CoinFilter(String::new())));
+        assert_eq!(parse(" track foo "), Ok(Command::Track("FOO".to_string())));
+        assert_eq!(parse("large"), Ok(Command::Run(Action::ShowLargeTrades)));
+        assert_eq!(parse("theme colorblind"), Ok(Command::Run(Action::SetTheme(Theme::COLORBLIND))));
+        assert_eq!(
+            parse("export CSV trades.csv"),
+            Ok(Command::Export(ExportFormat::Csv, PathBuf::from("trades.csv")))
+        );
+        assert_eq!(
+            parse("export bookmarks tsv marks.tsv"),
+            Ok(Command::ExportBookmarks(ExportFormat::Tsv, PathBuf::from("marks.tsv")))
+        );
+        assert_eq!(
+            parse("export csv \"my trades/today.csv\""),
+            Ok(Command::Export(ExportFormat::Csv, PathBuf::from("my trades/today.csv")))
+        );
+        assert_eq!(
+            parse("export tsv '/tmp/it''s here.tsv'"),
+            Ok(Command::Export(ExportFormat::Tsv, PathBuf::from("/tmp/its here.tsv")))
+        );
+        assert_eq!(parse("clear filters"), Ok(Command::Run(Action::ClearFilters)));
+        assert_eq!(parse("throttle 250"), Ok(Command::PriceThrottle(Duration::from_millis(250))));
+        assert_eq!(parse("throttle 0"), Ok(Command::PriceThrottle(Duration::ZERO)));
+        assert_eq!(parse("since 5m"), Ok(Command::TradeSince(Some(Duration::from_secs(300)))));
+        assert_eq!(parse("since"), Ok(Command::TradeSince(None)));
+        assert_eq!(parse("quit"), Ok(Command::Run(Action::Quit)));
+    }
+
+    #[test]
+    fn bad_commands_and_arguments_are_explained() {
+        assert_eq!(parse("launch"), Err("Unknown command `launch`".to_string()));
+        assert_eq!(parse("track"), Err("Usage: :track SYMBOL".to_string()));
+        assert_eq!(parse("all now"), Err("Usage: :all".to_string()));
+        assert!(parse("export xml out.xml").unwrap_err().contains("xml"));
+        assert!(parse("since 5w").unwrap_err().contains("`w`"));
+        assert_eq!(parse("throttle 1s"), Err("`1s` isn't a number of milliseconds".to_string()));
+        assert_eq!(parse("export csv \"trades.csv"), Err("Unclosed quote".to_string()));
+        assert!(parse("theme sepia").unwrap_err().contains("sepia"));
+    }
+
+    #[test]
+    fn tab_completes_command_names() {
+        assert_eq!(complete("tr"), Some("tra".to_string()));
+        assert_eq!(complete("tra"), None);
+        assert_eq!(complete("trac"), Some("track ".to_string()));
+        assert_eq!(complete("th"), None);
+        assert_eq!(complete("thr"), Some("throttle ".to_string()));
+        assert_eq!(complete("e"), Some("export ".to_string()));
+        assert_eq!(complete("b"), Some("bookmarks ".to_string()));
+        assert_eq!(complete("zz"), None);
+        assert_eq!(complete("coin pe"), None);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::models::{CoinSort, ColorBasis, TradeSort};
+use crate::notify::NotificationMode;
+use crate::row_template::RowTemplate;
+use crate::stats::NetFlowWindow;
+use crate::value_tier::ValueBreakpoints;
+use crate::websocket::DEFAULT_CHANNELS;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CONFIG_ENV_VAR: &str = "RUG_LISTENER_CONFIG";
+const CONFIG_DIR_NAME: &str = "rug-listener";
+const CONFIG_FILE_NAME: &str = "config.json";
+
+pub const DEFAULT_MAX_TRADES: usize = 1000;
+pub const DEFAULT_MAX_PRICE_UPDATES: usize = 100;
+pub const DEFAULT_VOLUME_SPIKE_MULTIPLIER: f64 = 3.0;
+pub const DEFAULT_VOLUME_SPIKE_WINDOW_SECS: u64 = 60;
+pub const DEFAULT_WHEEL_SCROLL_ROWS: usize = 3;
+pub const DEFAULT_SPLIT_MIN_WIDTH: u16 = 140;
+pub const DEFAULT_STALE_PRICE_SECS: u64 = 10;
+pub const DEFAULT_IDLE_DIM_SECS: u64 = 300;
+pub const DEFAULT_TRADE_DIM_AGE_SECS: u64 = 600;
+pub const DEFAULT_PRICE_THROTTLE_MS: u64 = 1000;
+pub const DEFAULT_GROUP_WINDOW_SECS: u64 = 10;
+pub const DEFAULT_FEED_LAG_WARN_SECS: f64 = 5.0;
+pub const DEFAULT_NOTIFICATION_COOLDOWN_SECS: u64 = 60;
+pub const DEFAULT_NOTIFICATION_DIGEST_SECS: u64 = 10;
+pub const DEFAULT_COIN_URL_TEMPLATE: &str = "https://rugplay.com/coin/{coin}";
+pub const DEFAULT_TRADER_URL_TEMPLATE: &str = "https://rugplay.com/user/{username}";
+pub const DEFAULT_IMAGE_BASE_URL: &str = "https://rugplay.com/";
+pub const DEFAULT_PLACEHOLDER_AVATARS: &[&str] = &["default"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub watchlist: BTreeSet<String>,
+    pub follow_list: BTreeSet<String>,
+    pub muted_coins: BTreeSet<String>,
+    pub muted_traders: BTreeSet<String>,
+    pub compact_view: bool,
+    /// Collapse repeated trades by the same trader into one row
+    pub group_trades: bool,
+    /// How far apart the first and last trade of a collapsed row can be
+    pub group_window_secs: u64,
+    /// Show times as HH:MM:SS.mmm so trades within the same second can be
+    /// told apart
+    pub millisecond_timestamps: bool,
+    pub split_view: bool,
+    /// Narrower terminals fall back to one page at a time even with split
+    /// view on
+    pub split_min_width: u16,
+    /// Show trades by followed traders in their own pane
+    pub follow_pane: bool,
+    /// Show the trade size histogram on the Trades page
+    pub size_histogram: bool,
+    /// Show the per-minute traded value chart on the Trades page
+    pub volume_chart: bool,
+    /// Show the trades-per-coin chart on the Trades page
+    pub coin_counts_chart: bool,
+    /// Show the buy/sell split of the trades in view on the Trades page
+    pub buy_sell_mix: bool,
+    /// Show the feed's health in a line above the help bar
+    pub status_bar: bool,
+    /// Alert when a followed trader trades
+    pub follow_alerts: bool,
+    /// Show the tracked coin's history as a chart instead of a list
+    pub price_chart: bool,
+    /// Overlay moving averages on the price chart
+    pub moving_averages: bool,
+    pub trade_sort: TradeSort,
+    /// Restore the coin and trader filters along with the rest of the saved
+    /// UI state at startup
+    pub persist_filters: bool,
+    /// Keep bookmarked trades in the saved UI state between sessions
+    pub persist_bookmarks: bool,
+    /// Column the all-coins price table is sorted by
+    pub coin_sort: CoinSort,
+    /// What the tracked coin's change is measured and colored against:
+    /// "change24h" (the feed's 24h change) or "session" (since startup)
+    pub color_basis: ColorBasis,
+    /// Rolling window for the per-coin net flow: "5m", "15m" or "1h"
+    pub net_flow_window: NetFlowWindow,
+    /// Alert when a coin's traded value over the last window is at least this
+    /// many times the value traded over the window before it.
+    pub volume_spike_multiplier: f64,
+    pub volume_spike_window_secs: u64,
+    pub max_trades: usize,
+    pub max_price_updates: usize,
+    /// Drop trades and price updates older than this many seconds
+    pub retain_secs: Option<u64>,
+    /// Skip price updates identical to the previous one for the same coin
+    pub dedupe_price_updates: bool,
+    /// Keep at most one price update per coin per this many milliseconds in
+    /// the price history (the latest one); 0 keeps every update
+    pub price_throttle_ms: u64,
+    /// Rows moved per mouse wheel tick
+    pub wheel_scroll_rows: usize,
+    /// Ask before quitting while trades or price updates are buffered
+    pub confirm_quit: bool,
+    /// The tracked coin's price is flagged as stale once its latest update
+    /// is older than this many seconds
+    pub stale_price_secs: u64,
+    /// Dim the UI and redraw less often once neither a key, a click nor
+    /// feed data has arrived for this many seconds; 0 never dims
+    pub idle_dim_secs: u64,
+    /// Trades older than this many seconds are drawn dimmed; 0 never dims
+    pub trade_dim_age_secs: u64,
+    /// Warn when trades arrive, on average, more than this many seconds
+    /// away from their own timestamps
+    pub feed_lag_warn_secs: f64,
+    /// "immediate" for a desktop notification per alert, "digest" for one
+    /// summary of the alerts raised every `notification_digest_secs`
+    pub notification_mode: NotificationMode,
+    /// Minimum seconds between desktop notifications from the same alert
+    /// rule in immediate mode
+    pub notification_cooldown_secs: u64,
+    /// Seconds between digest notifications in digest mode
+    pub notification_digest_secs: u64,
+    /// Page opened for a trade's coin; `{coin}` is replaced by the symbol
+    pub coin_url_template: String,
+    /// Page opened for a trade's trader; `{username}` and `{user_id}` are
+    /// replaced
+    pub trader_url_template: String,
+    /// Prefix for the relative coin icon paths the feed sends
+    pub image_base_url: String,
+    /// Trade values from which rows are drawn bolder, brighter and, for
+    /// the biggest, with the value highlighted
+    pub value_breakpoints: ValueBreakpoints,
+    /// One line per trade instead of the default entry in the list view,
+    /// e.g. `"{time} {type} {coin} {value} {user}"`
+    pub trade_row_template: Option<RowTemplate>,
+    /// Trader images containing any of these count as the site's stock
+    /// placeholder rather than an avatar of their own
+    pub placeholder_avatars: Vec<String>,
+    /// Feed channels to subscribe to
+    pub subscribe_channels: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            watchlist: BTreeSet::new(),
+            follow_list: BTreeSet::new(),
+            muted_coins: BTreeSet::new(),
+            muted_traders: BTreeSet::new(),
+            compact_view: false,
+            group_trades: false,
+            group_window_secs: DEFAULT_GROUP_WINDOW_SECS,
+            millisecond_timestamps: false,
+            split_view: false,
+            split_min_width: DEFAULT_SPLIT_MIN_WIDTH,
+            follow_pane: false,
+            size_histogram: false,
+            volume_chart: false,
+            coin_counts_chart: false,
+            buy_sell_mix: false,
+            status_bar: true,
+            follow_alerts: true,
+            price_chart: false,
+            moving_averages: true,
+            trade_sort: TradeSort::Newest,
+            persist_filters: false,
+            persist_bookmarks: false,
+            coin_sort: CoinSort::Volume,
+            color_basis: ColorBasis::Change24h,
+            net_flow_window: NetFlowWindow::FifteenMinutes,
+            volume_spike_multiplier: DEFAULT_VOLUME_SPIKE_MULTIPLIER,
+            volume_spike_window_secs: DEFAULT_VOLUME_SPIKE_WINDOW_SECS,
+            max_trades: DEFAULT_MAX_TRADES,
+            max_price_updates: DEFAULT_MAX_PRICE_UPDATES,
+            retain_secs: None,
+            dedupe_price_updates: true,
+            price_throttle_ms: DEFAULT_PRICE_THROTTLE_MS,
+            wheel_scroll_rows: DEFAULT_WHEEL_SCROLL_ROWS,
+            confirm_quit: true,
+            stale_price_secs: DEFAULT_STALE_PRICE_SECS,
+            idle_dim_secs: DEFAULT_IDLE_DIM_SECS,
+            trade_dim_age_secs: DEFAULT_TRADE_DIM_AGE_SECS,
+            feed_lag_warn_secs: DEFAULT_FEED_LAG_WARN_SECS,
+            notification_mode: NotificationMode::Immediate,
+            notification_cooldown_secs: DEFAULT_NOTIFICATION_COOLDOWN_SECS,
+            notification_digest_secs: DEFAULT_NOTIFICATION_DIGEST_SECS,
+            coin_url_template: DEFAULT_COIN_URL_TEMPLATE.to_string(),
+            trader_url_template: DEFAULT_TRADER_URL_TEMPLATE.to_string(),
+            image_base_url: DEFAULT_IMAGE_BASE_URL.to_string(),
+            value_breakpoints: ValueBreakpoints::default(),
+            trade_row_template: None,
+            placeholder_avatars: DEFAULT_PLACEHOLDER_AVATARS.iter().map(|pattern| pattern.to_string()).collect(),
+            subscribe_channels: DEFAULT_CHANNELS.iter().map(|channel| channel.to_string()).collect(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config at `path`, falling back to defaults when the file
+    /// doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// `$RUG_LISTENER_CONFIG`, then `$XDG_CONFIG_HOME/rug-listener/config.json`,
+/// then `~/.config/rug-listener/config.json`.
+pub fn default_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV_VAR) {
+        return Some(PathBuf::from(path));
+    }
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+}
@@ -0,0 +1,156 @@
+//! Timestamped log of everything worth telling the user about: alerts, feed
+//! connection changes, server errors and failed saves or exports. The
+//! newest warning shows in the alert banner; `E` lists the rest.
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::VecDeque;
+use tokio::sync::mpsc;
+
+/// Oldest events are dropped past this many.
+pub const MAX_EVENTS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARN",
+            Severity::Error => "ERROR",
+        }
+    }
+}
+
+/// The part of the app an event came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Feed,
+    Alerts,
+    Storage,
+    Export,
+    Exec,
+    /// The `--serve` HTTP API
+    Server,
+}
+
+impl Source {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Source::Feed => "feed",
+            Source::Alerts => "alert",
+            Source::Storage => "storage",
+            Source::Export => "export",
+            Source::Exec => "exec",
+            Source::Server => "server",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Event {
+    pub at: DateTime<Local>,
+    pub severity: Severity,
+    pub source: Source,
+    pub message: String,
+}
+
+impl Event {
+    pub fn new(severity: Severity, source: Source, message: impl Into<String>) -> Self {
+        Self { at: Local::now(), severity, source, message: message.into() }
+    }
+}
+
+/// Where background tasks send their events; the UI loop drains it into
+/// the app's [`EventLog`].
+pub type EventSender = mpsc::UnboundedSender<Event>;
+
+/// Newest-first ring of events, counting the ones added since the log was
+/// last looked at.
+#[derive(Debug, Default)]
+pub struct EventLog {
+    entries: VecDeque<Event>,
+    unseen: usize,
+}
+
+impl EventLog {
+    pub fn push(&mut self, event: Event) {
+        self.entries.push_front(event);
+        self.entries.truncate(MAX_EVENTS);
+        self.unseen = (self.unseen + 1).min(self.entries.len());
+    }
+
+    /// Newest first.
+    pub fn entries(&self) -> &VecDeque<Event> {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Events added since [`Self::mark_seen`].
+    pub fn unseen(&self) -> usize {
+        self.unseen
+    }
+
+    pub fn mark_seen(&mut self) {
+        self.unseen = 0;
+    }
+
+    /// Errors still in the log, oldest first. The UI owns the terminal while
+    /// they happen, so they're repeated on stderr once it has been restored.
+    pub fn errors(&self) -> impl Iterator<Item = &Event> {
+        self.entries.iter().rev().filter(|event| event.severity == Severity::Error)
+    }
+
+    /// The newest warning or error, for the alert banner.
+    pub fn latest_alert(&self) -> Option<&Event> {
+        self.entries.iter().find(|event| event.severity >= Severity::Warning)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_is_bounded_and_counts_unseen_events() {
+        let mut log = EventLog::default();
+        log.push(Event::new(Severity::Warning, Source::Alerts, "spike"));
+        log.push(Event::new(Severity::Info, Source::Feed, "connected"));
+        assert_eq!(log.unseen(), 2);
+        assert_eq!(log.latest_alert().unwrap().message, "spike");
+
+        log.mark_seen();
+        for i in 0..MAX_EVENTS {
+            log.push(Event::new(Severity::Info, Source::Feed, i.to_string()));
+        }
+        assert_eq!(log.len(), MAX_EVENTS);
+        assert_eq!(log.unseen(), MAX_EVENTS);
+        assert_eq!(log.entries()[0].message, (MAX_EVENTS - 1).to_string());
+        // The warning has been pushed out
+        assert!(log.latest_alert().is_none());
+    }
+
+    #[test]
+    fn errors_are_listed_oldest_first() {
+        let mut log = EventLog::default();
+        log.push(Event::new(Severity::Error, Source::Feed, "first"));
+        log.push(Event::new(Severity::Warning, Source::Storage, "unreadable state"));
+        log.push(Event::new(Severity::Error, Source::Export, "second"));
+        let errors: Vec<&str> = log.errors().map(|event| event.message.as_str()).collect();
+        assert_eq!(errors, ["first", "second"]);
+    }
+}
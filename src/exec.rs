@@ -0,0 +1,215 @@
+//! `--exec`: a long-running command that gets every trade in view as a JSON
+//! line on its stdin. Trades go through a bounded queue so a slow or stuck
+//! command never holds up the feed; what doesn't fit is dropped and counted.
+
+use crate::events::{Event, EventSender, Severity, Source};
+use crate::models::Trade;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+
+/// Lines waiting for the command before new trades are dropped.
+pub const QUEUE_CAPACITY: usize = 1024;
+/// How long the command gets to finish once its stdin is closed.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+/// Pause before restarting a command that exited, so one that fails at once
+/// doesn't spin.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Queued,
+    /// The queue was full; `first` is set on the first drop since the last
+    /// trade that made it through.
+    Dropped { first: bool },
+    /// The command has exited for good.
+    Closed,
+}
+
+/// The trade receiver's side of the pipe.
+#[derive(Debug)]
+pub struct ExecPipe {
+    command: String,
+    tx: mpsc::Sender<String>,
+    writer: JoinHandle<()>,
+    events: EventSender,
+    dropped: u64,
+    overflowing: bool,
+}
+
+impl ExecPipe {
+    /// Starts `command` through the shell and a task feeding its stdin.
+    /// Exits are reported to `events`; with `restart` the command is started
+    /// again.
+    pub fn spawn(command: String, restart: bool, events: EventSender) -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        let writer = tokio::spawn(feed_command(command.clone(), restart, rx, events.clone()));
+        Self { command, tx, writer, events, dropped: 0, overflowing: false }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Trades dropped because the command wasn't keeping up.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Queues `trade` without waiting. The first trade dropped since the
+    /// last one that got through is reported.
+    pub fn send(&mut self, trade: &Trade) -> Delivery {
+        // The exit has been reported already
+        if self.tx.is_closed() {
+            return Delivery::Closed;
+        }
+        let Ok(mut line) = serde_json::to_string(trade) else {
+            return Delivery::Queued;
+        };
+        line.push('\n');
+        match self.tx.try_send(line) {
+            Ok(()) => {
+                self.overflowing = false;
+                Delivery::Queued
+            }
+            Err(TrySendError::Full(_)) => {
+                self.dropped += 1;
+                let first = !self.overflowing;
+                self.overflowing = true;
+                if first {
+                    let message = format!("`{}` isn't keeping up; dropping trades ({} so far)", self.command, self.dropped);
+                    report(&self.events, Severity::Warning, message);
+                }
+                Delivery::Dropped { first }
+            }
+            Err(TrySendError::Closed(_)) => Delivery::Closed,
+        }
+    }
+
+    /// Lets the command finish the queued lines, closes its stdin and waits
+    /// briefly for it to exit; it's killed after that.
+    pub async fn shutdown(self) {
+        drop(self.tx);
+        let mut writer = self.writer;
+        if timeout(SHUTDOWN_GRACE * 2, &mut writer).await.is_err() {
+            writer.abort();
+        }
+    }
+}
+
+fn shell_command(command: &str) -> Command {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C");
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.arg("-c");
+        process
+    };
+    process.arg(command);
+    // The terminal belongs to the UI
+    process.stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).kill_on_drop(true);
+    process
+}
+
+fn report(events: &EventSender, severity: Severity, message: String) {
+    let _ = events.send(Event::new(severity, Source::Exec, message));
+}
+
+fn exit_message(command: &str, status: std::io::Result<ExitStatus>) -> String {
+    match status {
+        Ok(status) => format!("`{}` exited ({})", command, status),
+        Err(e) => format!("`{}` stopped: {}", command, e),
+    }
+}
+
+async fn feed_command(command: String, restart: bool, mut rx: mpsc::Receiver<String>, events: EventSender) {
+    loop {
+        let mut child = match shell_command(&command).spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                report(&events, Severity::Error, format!("Couldn't start `{}`: {}", command, e));
+                return;
+            }
+        };
+        let stdin = child.stdin.take().expect("stdin is piped");
+
+        match pump(&mut rx, stdin, &mut child).await {
+            // The app is quitting and stdin has been closed
+            Pumped::QueueClosed => {
+                if timeout(SHUTDOWN_GRACE, child.wait()).await.is_err() {
+                    let _ = child.kill().await;
+                }
+                return;
+            }
+            Pumped::Exited(status) => {
+                let message = exit_message(&command, status);
+                if !restart {
+                    report(&events, Severity::Error, format!("{}; trades are no longer piped", message));
+                    return;
+                }
+                report(&events, Severity::Warning, format!("{}; restarting", message));
+                sleep(RESTART_DELAY).await;
+            }
+        }
+    }
+}
+
+enum Pumped {
+    QueueClosed,
+    Exited(std::io::Result<ExitStatus>),
+}
+
+/// Writes queued lines to the command until the queue closes or the command
+/// goes away.
+async fn pump(rx: &mut mpsc::Receiver<String>, mut stdin: ChildStdin, child: &mut Child) -> Pumped {
+    loop {
+        tokio::select! {
+            line = rx.recv() => {
+                let Some(line) = line else {
+                    drop(stdin);
+                    return Pumped::QueueClosed;
+                };
+                if stdin.write_all(line.as_bytes()).await.is_err() {
+                    // Broken pipe: the command has exited or closed its stdin
+                    drop(stdin);
+                    return Pumped::Exited(child.wait().await);
+                }
+            }
+            status = child.wait() => return Pumped::Exited(status),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn pipes_json_lines_and_reports_the_exit() {
+        let path = std::env::temp_dir().join(format!("rug-listener-exec-{}.jsonl", std::process::id()));
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        let mut pipe = ExecPipe::spawn(format!("head -n 2 > '{}'", path.display()), false, events_tx);
+        for username in ["alice", "bob"] {
+            assert_eq!(pipe.send(&Trade::sample("PEPE", username)), Delivery::Queued);
+        }
+
+        // `head` exits after two lines
+        let event = timeout(Duration::from_secs(5), events_rx.recv()).await.unwrap().unwrap();
+        assert_eq!((event.severity, event.source), (Severity::Error, Source::Exec));
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let usernames: Vec<String> = written
+            .lines()
+            .map(|line| serde_json::from_str::<Trade>(line).unwrap().data.username)
+            .collect();
+        assert_eq!(usernames, ["alice", "bob"]);
+        pipe.shutdown().await;
+    }
+}
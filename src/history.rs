@@ -0,0 +1,241 @@
+//! Per-coin price history in three tiers, so it reaches back hours instead
+//! of the last hundred updates: every update for the last few minutes, then
+//! one-minute bars, then five-minute bars. Updates are compacted into the
+//! coarser tiers as they age, a little on every recorded update, by the
+//! price receiver. A coin that goes quiet is left alone until its next
+//! update, so its last ticks stay ticks however old they get: its history
+//! is finer than the tiers promise, never wrong, and the tick cap still
+//! holds. Every tier is capped, and so is the number of coins.
+
+use crate::models::PriceUpdate;
+use chrono::{DateTime, Local};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+
+/// Updates younger than this are kept individually.
+pub const TICK_WINDOW: chrono::TimeDelta = chrono::TimeDelta::minutes(5);
+/// One-minute bars cover this long before the ticks.
+pub const MINUTE_WINDOW: chrono::TimeDelta = chrono::TimeDelta::hours(3);
+pub const MAX_TICKS: usize = 300;
+/// A day of five-minute bars.
+pub const MAX_FIVE_MINUTE_BARS: usize = 288;
+/// Coins with a history; the one updated longest ago makes room for a new one.
+pub const MAX_COINS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Tick,
+    OneMinute,
+    FiveMinutes,
+}
+
+impl Resolution {
+    fn bucket_secs(self) -> i64 {
+        match self {
+            Resolution::Tick => 1,
+            Resolution::OneMinute => 60,
+            Resolution::FiveMinutes => 300,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Resolution::Tick => "tick",
+            Resolution::OneMinute => "1m",
+            Resolution::FiveMinutes => "5m",
+        }
+    }
+}
+
+/// One row of the history: a single update, or a bar standing in for all
+/// the updates of a minute or five. A bar's `update` is the last one in it.
+#[derive(Debug, Clone)]
+pub struct HistoryPoint {
+    pub update: PriceUpdate,
+    pub low: Decimal,
+    pub high: Decimal,
+    pub resolution: Resolution,
+}
+
+impl HistoryPoint {
+    fn tick(update: PriceUpdate) -> Self {
+        Self {
+            low: update.current_price,
+            high: update.current_price,
+            update,
+            resolution: Resolution::Tick,
+        }
+    }
+
+    fn bucket(&self, resolution: Resolution) -> i64 {
+        self.update.received_at.timestamp().div_euclid(resolution.bucket_secs())
+    }
+}
+
+/// Oldest-first tiers of one coin.
+#[derive(Debug, Default)]
+struct CoinHistory {
+    ticks: VecDeque<HistoryPoint>,
+    minutes: VecDeque<HistoryPoint>,
+    five_minutes: VecDeque<HistoryPoint>,
+}
+
+impl CoinHistory {
+    fn last_at(&self) -> Option<DateTime<Local>> {
+        self.ticks.back().map(|point| point.update.received_at)
+    }
+
+    /// Moves whatever has aged out of a tier into the next one.
+    fn compact(&mut self, now: DateTime<Local>) {
+        while self.ticks.len() > MAX_TICKS
+            || self.ticks.front().is_some_and(|point| point.update.received_at <= now - TICK_WINDOW)
+        {
+            let Some(point) = self.ticks.pop_front() else { break };
+            merge_into(&mut self.minutes, point, Resolution::OneMinute);
+        }
+        while self
+            .minutes
+            .front()
+            .is_some_and(|point| point.update.received_at <= now - TICK_WINDOW - MINUTE_WINDOW)
+        {
+            let Some(point) = self.minutes.pop_front() else { break };
+            merge_into(&mut self.five_minutes, point, Resolution::FiveMinutes);
+        }
+        while self.five_minutes.len() > MAX_FIVE_MINUTE_BARS {
+            self.five_minutes.pop_front();
+        }
+    }
+}
+
+/// Adds `point` to the newest bar of `tier` when it falls in the same
+/// bucket, or starts a new bar with it.
+fn merge_into(tier: &mut VecDeque<HistoryPoint>, point: HistoryPoint, resolution: Resolution) {
+    match tier.back_mut() {
+        Some(bar) if bar.bucket(resolution) == point.bucket(resolution) => {
+            bar.low = bar.low.min(point.low);
+            bar.high = bar.high.max(point.high);
+            bar.update = point.update;
+        }
+        _ => tier.push_back(HistoryPoint { resolution, ..point }),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PriceHistory {
+    coins: HashMap<String, CoinHistory>,
+}
+
+impl PriceHistory {
+    pub fn record(&mut self, update: &PriceUpdate) {
+        let coin_symbol = update.coin_symbol.to_uppercase();
+        if !self.coins.contains_key(&coin_symbol) && self.coins.len() >= MAX_COINS {
+            let oldest = self
+                .coins
+                .iter()
+                .min_by_key(|(_, history)| history.last_at())
+                .map(|(symbol, _)| symbol.clone());
+            if let Some(oldest) = oldest {
+                self.coins.remove(&oldest);
+            }
+        }
+        let history = self.coins.entry(coin_symbol).or_default();
+        history.ticks.push_back(HistoryPoint::tick(update.clone()));
+        history.compact(update.received_at);
+    }
+
+    /// The coin's history from `since` on (all of it for `None`), newest
+    /// first. Each stretch of time comes at the finest resolution still
+    /// kept for it, so a longer range reaches into coarser bars.
+    pub fn points(&self, coin_symbol: &str, since: Option<DateTime<Local>>) -> Vec<HistoryPoint> {
+        let Some(history) = self.coins.get(&coin_symbol.to_uppercase()) else {
+            return Vec::new();
+        };
+        history
+            .ticks
+            .iter()
+            .rev()
+            .chain(history.minutes.iter().rev())
+            .chain(history.five_minutes.iter().rev())
+            .take_while(|point| since.is_none_or(|since| point.update.received_at >= since))
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.coins.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn price_at(coin_symbol: &str, cents: i64, at: DateTime<Local>) -> PriceUpdate {
+        PriceUpdate {
+            current_price: Decimal::new(cents, 2),
+            received_at: at,
+            ..PriceUpdate::sample(coin_symbol)
+        }
+    }
+
+    fn start() -> DateTime<Local> {
+        Local.timestamp_opt(1_700_000_100, 0).unwrap()
+    }
+
+    #[test]
+    fn aged_updates_become_minute_bars_of_last_low_and_high() {
+        let mut history = PriceHistory::default();
+        // Three updates in one minute, then one six minutes later
+        for (secs, cents) in [(0, 50), (20, 90), (40, 70)] {
+            history.record(&price_at("PEPE", cents, start() + chrono::TimeDelta::seconds(secs)));
+        }
+        history.record(&price_at("pepe", 60, start() + chrono::TimeDelta::minutes(6)));
+
+        let points = history.points("PEPE", None);
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[0].resolution, points[0].update.current_price), (Resolution::Tick, Decimal::new(60, 2)));
+        let bar = &points[1];
+        assert_eq!(bar.resolution, Resolution::OneMinute);
+        assert_eq!((bar.low, bar.high), (Decimal::new(50, 2), Decimal::new(90, 2)));
+        assert_eq!(bar.update.current_price, Decimal::new(70, 2));
+        assert_eq!(bar.update.received_at, start() + chrono::TimeDelta::seconds(40));
+    }
+
+    #[test]
+    fn old_minute_bars_fold_into_capped_five_minute_bars() {
+        let mut history = PriceHistory::default();
+        // One update a minute for two days
+        for minute in 0..(48 * 60) {
+            history.record(&price_at("PEPE", 100 + minute % 7, start() + chrono::TimeDelta::minutes(minute)));
+        }
+        let points = history.points("PEPE", None);
+        let count = |resolution| points.iter().filter(|point| point.resolution == resolution).count();
+        assert_eq!(count(Resolution::Tick), 5);
+        assert!((180..=181).contains(&count(Resolution::OneMinute)), "{}", count(Resolution::OneMinute));
+        assert_eq!(count(Resolution::FiveMinutes), MAX_FIVE_MINUTE_BARS);
+        // Newest first across the tiers
+        assert!(points.windows(2).all(|pair| pair[0].update.received_at > pair[1].update.received_at));
+
+        // A range only as long as the ticks doesn't reach the bars
+        let now = points[0].update.received_at;
+        let recent = history.points("PEPE", Some(now - chrono::TimeDelta::minutes(4)));
+        assert!(recent.iter().all(|point| point.resolution == Resolution::Tick));
+    }
+
+    #[test]
+    fn a_busy_coin_is_held_to_the_tick_cap_and_coins_are_capped() {
+        let mut history = PriceHistory::default();
+        for second in 0..(MAX_TICKS as i64 + 50) {
+            history.record(&price_at("PEPE", 1, start() + chrono::TimeDelta::milliseconds(second * 100)));
+        }
+        assert_eq!(history.points("PEPE", None).iter().filter(|point| point.resolution == Resolution::Tick).count(), MAX_TICKS);
+
+        for i in 0..MAX_COINS {
+            history.record(&price_at(&format!("COIN{}", i), 1, start() + chrono::TimeDelta::minutes(1)));
+        }
+        // PEPE was updated longest ago
+        assert!(history.points("PEPE", None).is_empty());
+        assert_eq!(history.coins.len(), MAX_COINS);
+    }
+}
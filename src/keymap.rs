@@ -0,0 +1,460 @@
+use crate::models::{AppPage, PriceRange};
+use crate::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Everything a key can do. The input handlers dispatch on these and the
+/// help overlay lists them, so both are driven by the tables below.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    ShowHelp,
+    OpenCommand,
+    Complete,
+    Quit,
+    SwitchPage,
+    ToggleSplit,
+    ShrinkSplit,
+    GrowSplit,
+    TogglePause,
+    ClearPage,
+    ClearAll,
+    ScrollUp,
+    ScrollDown,
+    FastScrollUp,
+    FastScrollDown,
+    SwitchTradeFilter,
+    // Only reachable from the command line
+    ShowAllTrades,
+    ShowLargeTrades,
+    SetTheme(Theme),
+    CoinFilter,
+    TraderFilter,
+    ClearFilters,
+    StartSearch,
+    NextMatch,
+    PreviousMatch,
+    ClearSearch,
+    ToggleCompact,
+    ToggleGroupTrades,
+    ToggleMilliseconds,
+    ToggleStatusBar,
+    ToggleAvatarOnly,
+    CycleSort,
+    CycleColorBasis,
+    SelectPriceRange(PriceRange),
+    WatchCoin,
+    FollowTrader,
+    MuteCoin,
+    MuteTrader,
+    OpenMutes,
+    SelectCoin,
+    Confirm,
+    Cancel,
+    DeleteChar,
+    TypeSymbol,
+    Unmute,
+    ForceQuit,
+    ToggleChart,
+    OpenCoinPage,
+    OpenTraderPage,
+    OpenCoinIcon,
+    EditPosition,
+    CopySummary,
+    CopyJson,
+    CopyVisibleTrades,
+    CoinStats,
+    ToggleAllCoins,
+    ToggleFollowPane,
+    ToggleSizeHistogram,
+    ToggleVolumeChart,
+    ToggleCoinCounts,
+    ToggleBuySellMix,
+    ToggleMovingAverages,
+    ToggleCoinLink,
+    FollowPaneUp,
+    FollowPaneDown,
+    OpenFollows,
+    Unfollow,
+    ToggleBookmark,
+    OpenBookmarks,
+    Unbookmark,
+    OpenPortfolio,
+    OpenEvents,
+    PaperBuy,
+    PaperSell,
+    ToggleSide,
+    PreviousField,
+    NextField,
+}
+
+#[derive(Debug)]
+pub struct Binding {
+    pub keys: &'static [(KeyCode, KeyModifiers)],
+    pub action: Action,
+    pub description: &'static str,
+}
+
+#[derive(Debug)]
+pub struct KeySection {
+    pub title: &'static str,
+    pub bindings: &'static [Binding],
+    /// Behaviour that isn't tied to a single key, e.g. typing into an input
+    pub note: Option<&'static str>,
+}
+
+const NONE: KeyModifiers = KeyModifiers::NONE;
+
+const fn key(code: KeyCode) -> (KeyCode, KeyModifiers) {
+    (code, NONE)
+}
+
+const fn char_key(c: char) -> (KeyCode, KeyModifiers) {
+    (KeyCode::Char(c), NONE)
+}
+
+/// Checked before the bindings of whatever mode is active.
+pub const ANY_MODE: KeySection = KeySection {
+    title: "Anywhere",
+    bindings: &[
+        Binding {
+            keys: &[(KeyCode::Char('c'), KeyModifiers::CONTROL)],
+            action: Action::ForceQuit,
+            description: "Press twice within 2s to quit without asking",
+        },
+    ],
+    note: None,
+};
+
+pub const GLOBAL: KeySection = KeySection {
+    title: "Global",
+    bindings: &[
+        Binding { keys: &[char_key('?')], action: Action::ShowHelp, description: "Show this help" },
+        Binding { keys: &[char_key(':')], action: Action::OpenCommand, description: "Open the command line" },
+        Binding { keys: &[char_key('q')], action: Action::Quit, description: "Quit" },
+        Binding { keys: &[char_key('p')], action: Action::SwitchPage, description: "Switch page (or focused pane in split view)" },
+        Binding { keys: &[char_key('$')], action: Action::OpenPortfolio, description: "Open/leave the paper trading portfolio" },
+        Binding { keys: &[char_key('E')], action: Action::OpenEvents, description: "Event log: alerts, feed events and failures" },
+        Binding { keys: &[char_key('T')], action: Action::ToggleMilliseconds, description: "Show times to the millisecond" },
+        Binding { keys: &[char_key('I')], action: Action::ToggleStatusBar, description: "Show/hide the feed status bar" },
+        Binding { keys: &[char_key('v')], action: Action::ToggleSplit, description: "Toggle side-by-side split view" },
+        Binding { keys: &[(KeyCode::Left, KeyModifiers::CONTROL)], action: Action::ShrinkSplit, description: "Move the split view divider left" },
+        Binding { keys: &[(KeyCode::Right, KeyModifiers::CONTROL)], action: Action::GrowSplit, description: "Move the split view divider right" },
+        Binding { keys: &[char_key('P')], action: Action::TogglePause, description: "Pause/resume the display" },
+        Binding { keys: &[(KeyCode::Char('l'), KeyModifiers::CONTROL)], action: Action::ClearPage, description: "Clear the current page's buffer" },
+        Binding { keys: &[char_key('x')], action: Action::ClearAll, description: "Clear trades and price history" },
+        Binding { keys: &[char_key('y')], action: Action::CopySummary, description: "Copy the selected row as a one-line summary" },
+        Binding { keys: &[char_key('Y')], action: Action::CopyJson, description: "Copy the selected row as JSON" },
+        Binding { keys: &[key(KeyCode::Up)], action: Action::ScrollUp, description: "Scroll up" },
+        Binding { keys: &[key(KeyCode::Down)], action: Action::ScrollDown, description: "Scroll down" },
+        Binding { keys: &[(KeyCode::Up, KeyModifiers::SHIFT)], action: Action::FastScrollUp, description: "Scroll up 10 rows" },
+        Binding { keys: &[(KeyCode::Down, KeyModifiers::SHIFT)], action: Action::FastScrollDown, description: "Scroll down 10 rows" },
+    ],
+    note: Some("Mouse: click tabs and filters, wheel to scroll (Shift+wheel: a page), drag scrollbars and the split divider"),
+};
+
+pub const TRADES: KeySection = KeySection {
+    title: "Trade Monitor",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Tab)], action: Action::SwitchTradeFilter, description: "Switch All/Large trades" },
+        Binding { keys: &[char_key('c')], action: Action::CoinFilter, description: "Filter by coin (comma-separated)" },
+        Binding { keys: &[char_key('t')], action: Action::TraderFilter, description: "Filter by trader" },
+        Binding { keys: &[char_key('X')], action: Action::ClearFilters, description: "Clear the coin and trader filters" },
+        Binding { keys: &[char_key('/')], action: Action::StartSearch, description: "Search coins, coin names and traders" },
+        Binding { keys: &[char_key('n')], action: Action::NextMatch, description: "Next search match" },
+        Binding { keys: &[char_key('N')], action: Action::PreviousMatch, description: "Previous search match" },
+        Binding { keys: &[key(KeyCode::Esc)], action: Action::ClearSearch, description: "Clear the search" },
+        Binding { keys: &[char_key('z')], action: Action::ToggleCompact, description: "Toggle compact table" },
+        Binding { keys: &[char_key('G')], action: Action::ToggleGroupTrades, description: "Group repeated trades by the same trader" },
+        Binding { keys: &[char_key('A')], action: Action::ToggleAvatarOnly, description: "Only show traders with an avatar" },
+        Binding { keys: &[char_key('o')], action: Action::CycleSort, description: "Cycle sort order" },
+        Binding { keys: &[char_key('w')], action: Action::WatchCoin, description: "Watch/unwatch the selected coin" },
+        Binding { keys: &[char_key('f')], action: Action::FollowTrader, description: "Follow/unfollow the selected trader" },
+        Binding { keys: &[char_key('m')], action: Action::MuteCoin, description: "Mute the selected coin" },
+        Binding { keys: &[char_key('M')], action: Action::MuteTrader, description: "Mute the selected trader" },
+        Binding { keys: &[char_key('u')], action: Action::OpenMutes, description: "Manage mutes" },
+        Binding { keys: &[char_key('b')], action: Action::OpenCoinPage, description: "Open the selected coin's page in the browser" },
+        Binding { keys: &[char_key('B')], action: Action::OpenTraderPage, description: "Open the selected trader's profile in the browser" },
+        Binding { keys: &[char_key('i')], action: Action::OpenCoinIcon, description: "Open the selected coin's icon in the browser" },
+        Binding { keys: &[char_key('*')], action: Action::ToggleBookmark, description: "Bookmark/unbookmark the selected trade" },
+        Binding { keys: &[char_key('\'')], action: Action::OpenBookmarks, description: "Show bookmarked trades" },
+        Binding { keys: &[char_key('C')], action: Action::CopyVisibleTrades, description: "Copy every visible trade as tab-separated text" },
+        Binding { keys: &[char_key('S')], action: Action::CoinStats, description: "Session stats for the filtered or selected coin" },
+        Binding { keys: &[char_key('F')], action: Action::ToggleFollowPane, description: "Show/hide the followed traders pane" },
+        Binding { keys: &[char_key('K')], action: Action::FollowPaneUp, description: "Scroll the followed traders pane up" },
+        Binding { keys: &[char_key('J')], action: Action::FollowPaneDown, description: "Scroll the followed traders pane down" },
+        Binding { keys: &[char_key('L')], action: Action::OpenFollows, description: "Manage followed traders" },
+        Binding { keys: &[char_key('l')], action: Action::ToggleCoinLink, description: "Link the coin filter and the tracked coin" },
+        Binding { keys: &[char_key('H')], action: Action::ToggleSizeHistogram, description: "Show/hide the trade size histogram" },
+        Binding { keys: &[char_key('V')], action: Action::ToggleVolumeChart, description: "Show/hide traded value per minute" },
+        Binding { keys: &[char_key('R')], action: Action::ToggleCoinCounts, description: "Show/hide the busiest coins by trade count" },
+        Binding { keys: &[char_key('%')], action: Action::ToggleBuySellMix, description: "Show/hide the buy/sell split of the trades in view" },
+    ],
+    note: None,
+};
+
+pub const PRICE_TRACKER: KeySection = KeySection {
+    title: "Price Tracker",
+    bindings: &[
+        Binding { keys: &[char_key('s')], action: Action::SelectCoin, description: "Pick a coin to track" },
+        Binding { keys: &[char_key('g')], action: Action::ToggleChart, description: "Toggle price history list/chart" },
+        Binding { keys: &[char_key('m')], action: Action::ToggleMovingAverages, description: "Show/hide moving averages on the chart" },
+        Binding { keys: &[char_key('a')], action: Action::ToggleAllCoins, description: "Toggle the all-coins price table" },
+        Binding { keys: &[char_key('h')], action: Action::EditPosition, description: "Simulate holding the tracked coin (entry price and quantity)" },
+        Binding { keys: &[char_key('o')], action: Action::CycleSort, description: "Cycle the all-coins table's sort column" },
+        Binding { keys: &[char_key('r')], action: Action::CycleColorBasis, description: "Measure the price change over 24h or since startup" },
+        Binding { keys: &[char_key('1')], action: Action::SelectPriceRange(PriceRange::FiveMinutes), description: "Show the last 5 minutes of price history" },
+        Binding { keys: &[char_key('2')], action: Action::SelectPriceRange(PriceRange::ThirtyMinutes), description: "Show the last 30 minutes of price history" },
+        Binding { keys: &[char_key('3')], action: Action::SelectPriceRange(PriceRange::TwoHours), description: "Show the last 2 hours of price history" },
+        Binding { keys: &[char_key('4')], action: Action::SelectPriceRange(PriceRange::All), description: "Show all of the price history" },
+        Binding { keys: &[key(KeyCode::Enter)], action: Action::Confirm, description: "Track the highlighted coin of the all-coins table" },
+    ],
+    note: None,
+};
+
+pub const PORTFOLIO: KeySection = KeySection {
+    title: "Portfolio",
+    bindings: &[
+        Binding { keys: &[char_key('b')], action: Action::PaperBuy, description: "Paper-buy a coin at its latest price" },
+        Binding { keys: &[char_key('s')], action: Action::PaperSell, description: "Paper-sell a coin at its latest price" },
+    ],
+    note: Some("↑/↓ scroll the order log; Ctrl+L resets the paper portfolio"),
+};
+
+pub const ORDER_ENTRY: KeySection = KeySection {
+    title: "Paper order",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Enter)], action: Action::Confirm, description: "Place the order" },
+        Binding { keys: &[key(KeyCode::Esc)], action: Action::Cancel, description: "Cancel" },
+        Binding { keys: &[key(KeyCode::Tab)], action: Action::Complete, description: "Complete the coin, or go to the next field" },
+        Binding { keys: &[key(KeyCode::Up)], action: Action::PreviousField, description: "Previous field" },
+        Binding { keys: &[key(KeyCode::Down)], action: Action::NextField, description: "Next field" },
+        Binding { keys: &[key(KeyCode::Left), key(KeyCode::Right)], action: Action::ToggleSide, description: "Switch between buy and sell" },
+        Binding { keys: &[key(KeyCode::Backspace)], action: Action::DeleteChar, description: "Delete a character" },
+    ],
+    note: Some("The amount is in base currency; orders fill at the coin's latest streamed price"),
+};
+
+pub const TEXT_INPUT: KeySection = KeySection {
+    title: "Filter and coin input",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Enter)], action: Action::Confirm, description: "Apply" },
+        Binding { keys: &[key(KeyCode::Esc)], action: Action::Cancel, description: "Cancel" },
+        Binding { keys: &[key(KeyCode::Backspace)], action: Action::DeleteChar, description: "Delete a character" },
+        Binding { keys: &[key(KeyCode::Up)], action: Action::ScrollUp, description: "Scroll the filtered trades" },
+        Binding { keys: &[key(KeyCode::Down)], action: Action::ScrollDown, description: "Scroll the filtered trades" },
+    ],
+    note: Some("Any other character is typed into the input; filters apply as you type"),
+};
+
+pub const COMMAND_INPUT: KeySection = KeySection {
+    title: "Command line",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Enter)], action: Action::Confirm, description: "Run the command" },
+        Binding { keys: &[key(KeyCode::Tab)], action: Action::Complete, description: "Complete the command name" },
+        Binding { keys: &[key(KeyCode::Esc)], action: Action::Cancel, description: "Close the command line" },
+        Binding { keys: &[key(KeyCode::Backspace)], action: Action::DeleteChar, description: "Delete a character" },
+    ],
+    note: Some("Commands are listed under \"Commands\" below"),
+};
+
+pub const SEARCH_INPUT: KeySection = KeySection {
+    title: "Search",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Enter)], action: Action::Confirm, description: "Keep the search and step through it with n/N" },
+        Binding { keys: &[key(KeyCode::Esc)], action: Action::Cancel, description: "Clear the search" },
+        Binding { keys: &[key(KeyCode::Backspace)], action: Action::DeleteChar, description: "Delete a character" },
+    ],
+    note: Some("The list jumps to the first match as you type; other trades stay visible"),
+};
+
+pub const COIN_LIST: KeySection = KeySection {
+    title: "Coin picker",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Up)], action: Action::ScrollUp, description: "Previous coin" },
+        Binding { keys: &[key(KeyCode::Down)], action: Action::ScrollDown, description: "Next coin" },
+        Binding { keys: &[key(KeyCode::Enter)], action: Action::Confirm, description: "Track the selected coin" },
+        Binding { keys: &[char_key('/'), key(KeyCode::Tab)], action: Action::TypeSymbol, description: "Type a symbol instead" },
+        Binding { keys: &[key(KeyCode::Esc)], action: Action::Cancel, description: "Cancel" },
+    ],
+    note: None,
+};
+
+pub const MUTE_LIST: KeySection = KeySection {
+    title: "Mute list",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Up)], action: Action::ScrollUp, description: "Previous entry" },
+        Binding { keys: &[key(KeyCode::Down)], action: Action::ScrollDown, description: "Next entry" },
+        Binding {
+            keys: &[key(KeyCode::Enter), key(KeyCode::Delete), key(KeyCode::Backspace), char_key('d')],
+            action: Action::Unmute,
+            description: "Unmute the selected entry",
+        },
+        Binding { keys: &[key(KeyCode::Esc), char_key('u')], action: Action::Cancel, description: "Close" },
+    ],
+    note: None,
+};
+
+pub const FOLLOW_LIST: KeySection = KeySection {
+    title: "Followed traders",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Up)], action: Action::ScrollUp, description: "Previous trader" },
+        Binding { keys: &[key(KeyCode::Down)], action: Action::ScrollDown, description: "Next trader" },
+        Binding {
+            keys: &[key(KeyCode::Enter), key(KeyCode::Delete), key(KeyCode::Backspace), char_key('d')],
+            action: Action::Unfollow,
+            description: "Unfollow the selected trader",
+        },
+        Binding { keys: &[key(KeyCode::Esc), char_key('L')], action: Action::Cancel, description: "Close" },
+    ],
+    note: None,
+};
+
+pub const EVENT_LOG: KeySection = KeySection {
+    title: "Event log",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Up)], action: Action::ScrollUp, description: "Scroll up" },
+        Binding { keys: &[key(KeyCode::Down)], action: Action::ScrollDown, description: "Scroll down" },
+        Binding { keys: &[key(KeyCode::Esc), char_key('E'), char_key('q')], action: Action::Cancel, description: "Close" },
+    ],
+    note: Some("Keeps the last 500 events; the help bar counts the ones added since it was last opened"),
+};
+
+pub const BOOKMARK_LIST: KeySection = KeySection {
+    title: "Bookmarks",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Up)], action: Action::ScrollUp, description: "Previous bookmark" },
+        Binding { keys: &[key(KeyCode::Down)], action: Action::ScrollDown, description: "Next bookmark" },
+        Binding {
+            keys: &[key(KeyCode::Enter), key(KeyCode::Delete), key(KeyCode::Backspace), char_key('d')],
+            action: Action::Unbookmark,
+            description: "Remove the selected bookmark (the trade stays in the feed)",
+        },
+        Binding { keys: &[key(KeyCode::Esc), char_key('\'')], action: Action::Cancel, description: "Close" },
+    ],
+    note: None,
+};
+
+pub const CONFIRM_CLEAR: KeySection = KeySection {
+    title: "Clear confirmation",
+    bindings: &[
+        Binding { keys: &[char_key('y'), char_key('Y')], action: Action::Confirm, description: "Clear" },
+    ],
+    note: Some("Any other key cancels"),
+};
+
+pub const CONFIRM_LINK_FILTER: KeySection = KeySection {
+    title: "Linked coin filter offer",
+    bindings: &[
+        Binding { keys: &[char_key('y'), char_key('Y'), key(KeyCode::Enter)], action: Action::Confirm, description: "Filter trades to the tracked coin" },
+    ],
+    note: Some("Any other key keeps the current filter"),
+};
+
+pub const CONFIRM_QUIT: KeySection = KeySection {
+    title: "Quit confirmation",
+    bindings: &[
+        Binding { keys: &[char_key('y'), char_key('Y'), key(KeyCode::Enter)], action: Action::Confirm, description: "Quit" },
+        Binding { keys: &[char_key('n'), char_key('N'), key(KeyCode::Esc)], action: Action::Cancel, description: "Back to the app" },
+    ],
+    note: None,
+};
+
+pub const HELP: KeySection = KeySection {
+    title: "Help",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Up)], action: Action::ScrollUp, description: "Scroll up" },
+        Binding { keys: &[key(KeyCode::Down)], action: Action::ScrollDown, description: "Scroll down" },
+        Binding { keys: &[char_key('?'), key(KeyCode::Esc), char_key('q')], action: Action::Cancel, description: "Close help" },
+    ],
+    note: None,
+};
+
+pub const COIN_STATS: KeySection = KeySection {
+    title: "Coin stats",
+    bindings: &[
+        Binding { keys: &[key(KeyCode::Esc), char_key('S'), char_key('q')], action: Action::Cancel, description: "Close" },
+    ],
+    note: Some("Figures update live while the popup is open"),
+};
+
+/// Every section, in the order the help overlay lists them.
+pub const SECTIONS: [&KeySection; 19] = [
+    &ANY_MODE,
+    &GLOBAL,
+    &TRADES,
+    &PRICE_TRACKER,
+    &PORTFOLIO,
+    &TEXT_INPUT,
+    &COMMAND_INPUT,
+    &SEARCH_INPUT,
+    &ORDER_ENTRY,
+    &COIN_LIST,
+    &MUTE_LIST,
+    &FOLLOW_LIST,
+    &BOOKMARK_LIST,
+    &EVENT_LOG,
+    &CONFIRM_CLEAR,
+    &CONFIRM_LINK_FILTER,
+    &CONFIRM_QUIT,
+    &HELP,
+    &COIN_STATS,
+];
+
+/// Normal-mode action for `key`: global bindings first, then the ones for
+/// the current page.
+pub fn normal_action(key: &KeyEvent, page: &AppPage) -> Option<Action> {
+    let page_section = match page {
+        AppPage::Trades => &TRADES,
+        AppPage::PriceTracker => &PRICE_TRACKER,
+        AppPage::Portfolio => &PORTFOLIO,
+    };
+    lookup(&GLOBAL, key).or_else(|| lookup(page_section, key))
+}
+
+pub fn lookup(section: &KeySection, key: &KeyEvent) -> Option<Action> {
+    section
+        .bindings
+        .iter()
+        .find(|binding| binding.keys.iter().any(|&(code, modifiers)| key_matches(key, code, modifiers)))
+        .map(|binding| binding.action)
+}
+
+fn key_matches(key: &KeyEvent, code: KeyCode, modifiers: KeyModifiers) -> bool {
+    // Shift is already part of the character for letters and symbols
+    let mut pressed = key.modifiers;
+    if matches!(key.code, KeyCode::Char(_)) {
+        pressed.remove(KeyModifiers::SHIFT);
+    }
+    key.code == code && pressed == modifiers
+}
+
+/// Display form of a binding's keys, e.g. "Enter/Del/d" or "Ctrl+L".
+pub fn keys_label(keys: &[(KeyCode, KeyModifiers)]) -> String {
+    keys.iter()
+        .map(|&(code, modifiers)| key_label(code, modifiers))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn key_label(code: KeyCode, modifiers: KeyModifiers) -> String {
+    let name = match code {
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => c.to_ascii_uppercase().to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        other => format!("{:?}", other),
+    };
+    let mut label = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        label.push_str("Ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        label.push_str("Shift+");
+    }
+    label.push_str(&name);
+    label
+}
@@ -0,0 +1,30 @@
+//! Everything behind the `rugplay-terminal` binary, exposed as a library so
+//! the integration tests in `tests/` can drive the pieces directly.
+
+pub mod app;
+pub mod browser;
+pub mod clipboard;
+pub mod cli;
+pub mod command;
+pub mod config;
+pub mod events;
+pub mod exec;
+pub mod history;
+pub mod keymap;
+pub mod logging;
+pub mod metrics;
+pub mod models;
+pub mod notify;
+pub mod portfolio;
+pub mod row_template;
+pub mod server;
+#[cfg(unix)]
+pub mod socket;
+pub mod state;
+pub mod stats;
+pub mod store;
+pub mod theme;
+pub mod throttle;
+pub mod ui;
+pub mod value_tier;
+pub mod websocket;
@@ -0,0 +1,152 @@
+//! Diagnostics written with `tracing` to a log file, never to the terminal
+//! the UI is drawn on. The file is rotated by size: once it would pass
+//! [`MAX_LOG_BYTES`] it becomes `<name>.1`, the older ones shift up and
+//! only [`KEPT_LOGS`] of them are kept.
+
+use crate::state;
+use anyhow::Result;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+
+const LOG_FILE_NAME: &str = "rug-listener.log";
+const STATE_HOME_DIR_NAME: &str = "rug-listener";
+pub const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files kept besides the current one
+pub const KEPT_LOGS: usize = 3;
+/// Longest part of a feed message quoted in the log
+const SNIPPET_CHARS: usize = 200;
+
+/// `$XDG_STATE_HOME/rug-listener/rug-listener.log`, then
+/// `~/.local/state/rug-listener/rug-listener.log`.
+pub fn default_path() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state")))
+        .or_else(|| state::data_dir().and_then(|dir| dir.parent().map(Path::to_path_buf)))?;
+    Some(state_home.join(STATE_HOME_DIR_NAME).join(LOG_FILE_NAME))
+}
+
+/// Sends every `tracing` event at `level` or above to `path`. Call once,
+/// before the terminal is taken over.
+pub fn init(path: &Path, level: LevelFilter) -> Result<()> {
+    if level == LevelFilter::OFF {
+        return Ok(());
+    }
+    let file = RotatingFile::open(path.to_path_buf(), MAX_LOG_BYTES, KEPT_LOGS)?;
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_max_level(level)
+        .with_target(false)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+/// The start of `text`, for quoting a feed message without flooding the log.
+pub fn snippet(text: &str) -> String {
+    match text.char_indices().nth(SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// A log file that moves itself aside once it reaches `max_bytes`.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    /// Appends to `path`, creating it and its directory if needed.
+    pub fn open(path: PathBuf, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self { path, max_bytes, keep, file, written })
+    }
+
+    /// `path` with `.n` appended, the name of the `n`th newest rotated file.
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            // The oldest is overwritten by the one before it
+            for n in (1..self.keep).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    fs::rename(from, self.rotated_path(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A record bigger than the limit still goes in whole, on its own
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_by_size_and_keeps_the_newest_files() {
+        let dir = std::env::temp_dir().join(format!("rug-listener-log-{}", std::process::id()));
+        let path = dir.join("test.log");
+        let mut file = RotatingFile::open(path.clone(), 10, 2).unwrap();
+        for line in ["aaaaaa\n", "bbbbbb\n", "cccccc\n", "dddddd\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).ok();
+        assert_eq!(read("test.log").as_deref(), Some("dddddd\n"));
+        assert_eq!(read("test.log.1").as_deref(), Some("cccccc\n"));
+        assert_eq!(read("test.log.2").as_deref(), Some("bbbbbb\n"));
+        assert_eq!(read("test.log.3"), None);
+
+        // Reopening carries on from the current size
+        let mut file = RotatingFile::open(path, 10, 2).unwrap();
+        file.write_all(b"ee\n").unwrap();
+        assert_eq!(read("test.log").as_deref(), Some("dddddd\nee\n"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn snippets_stop_at_a_character_boundary() {
+        assert_eq!(snippet("short"), "short");
+        let long = "é".repeat(SNIPPET_CHARS + 5);
+        assert_eq!(snippet(&long), format!("{}…", "é".repeat(SNIPPET_CHARS)));
+    }
+}
@@ -1,218 +1,967 @@
-mod app;
-mod models;
-mod ui;
-mod websocket;
-
-use anyhow::Result;
-use app::{App, MAX_PRICE_UPDATES, MAX_TRADES};
+use anyhow::{Context, Result};
+use rugplay_terminal::{app, cli, command, config, events, exec, keymap, logging, metrics, models, portfolio, server, state, stats, store, theme, throttle, ui, websocket};
+#[cfg(unix)]
+use rugplay_terminal::socket;
+use app::App;
+use command::Command;
+use clap::Parser;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseEvent, MouseEventKind, MouseButton},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind, MouseButton},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use models::{AppPage, InputMode, TradeFilter};
+use events::{Severity, Source};
+use keymap::Action;
+use metrics::Metrics;
+use models::{AppPage, ClearScope, InputMode, MuteKind, PriceUpdate, Trade, TradeFilter};
+use portfolio::Side;
 use std::{
-    collections::VecDeque,
     io,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
-use tokio::sync::mpsc;
+use stats::SessionStats;
+use store::{Accept, Retention, SharedBuffer};
+use tokio::sync::{broadcast, mpsc, watch, Notify};
+use websocket::{FeedNotice, FeedState};
+
+// Relative timestamps and transient messages need at least this refresh rate
+const PERIODIC_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+// Upper bound on how often new data alone triggers a redraw
+const MIN_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+// The periodic refresh while idle; the UI is dimmed and nothing is moving
+const IDLE_REDRAW_INTERVAL: Duration = Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Setup terminal
+    let args = cli::Args::parse();
+
+    // Load config before touching the terminal so errors print normally
+    let config_path = args.config.clone().or_else(config::default_path);
+    let config = match config_path {
+        Some(ref path) => config::Config::load(path)
+            .with_context(|| format!("failed to load config from {}", path.display()))?,
+        None => config::Config::default(),
+    };
+
+    // Command-line flags take precedence over the config file
+    let retain = args.retain.or(config.retain_secs.map(Duration::from_secs));
+    let max_trades = args.max_trades.unwrap_or(config.max_trades);
+    let max_price_updates = args.max_price_updates.unwrap_or(config.max_price_updates);
+    let trade_retention = Retention::new(max_trades, retain).context("invalid trade retention")?;
+    let price_retention = Retention::new(max_price_updates, retain).context("invalid price update retention")?;
+    let dedupe_price_updates = config.dedupe_price_updates && !args.keep_duplicate_prices;
+    let confirm_quit = config.confirm_quit && !args.no_confirm_quit;
+    let mouse_enabled = !args.no_mouse;
+    let desktop_notifications = args.desktop_notifications;
+    let theme = theme::Theme::detect(args.light);
+    let exec_command = args.exec.clone();
+    let exec_restart = args.exec_restart;
+    let inline = args.inline;
+
+    // A broken state file only costs the restore, so it's reported in the
+    // alert banner instead of stopping startup
+    let state_path = state::default_path();
+    let (saved_state, state_warning) = match state_path {
+        Some(ref path) if !args.fresh => match state::UiState::load(path) {
+            Ok(saved_state) => (saved_state, None),
+            Err(e) => (None, Some(format!("Ignored saved state in {}: {}", path.display(), e))),
+        },
+        _ => (None, None),
+    };
+
+    // Diagnostics go to a file since the terminal belongs to the UI. A log
+    // that can't be opened only costs the diagnostics.
+    let log_warning = args.log.clone().or_else(logging::default_path).and_then(|path| {
+        logging::init(&path, args.log_level)
+            .err()
+            .map(|e| format!("Not logging to {}: {}", path.display(), e))
+    });
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "starting");
+
+    // Bind before taking over the terminal so a busy port is reported
+    // normally
+    let http_listener = match args.serve {
+        Some(port) => Some(
+            tokio::net::TcpListener::bind(("127.0.0.1", port))
+                .await
+                .with_context(|| format!("failed to serve on port {}", port))?,
+        ),
+        None => None,
+    };
+    #[cfg(unix)]
+    let (socket_listener, _socket_file) = match args.socket {
+        Some(ref path) => {
+            let (listener, file) = socket::bind(path)
+                .with_context(|| format!("failed to listen on {}", path.display()))?;
+            (Some(listener), Some(file))
+        }
+        None => (None, None),
+    };
+
+    // Restore the terminal before anything is printed if we panic mid-run
+    install_panic_hook(mouse_enabled, inline.is_some());
+
+    // Setup terminal. Inline mode draws in a fixed-height area below the
+    // prompt and leaves the rest of the scrollback alone.
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    if inline.is_none() {
+        execute!(stdout, EnterAlternateScreen)?;
+    }
+    if mouse_enabled {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = ratatui::backend::CrosstermBackend::new(stdout);
-    let mut terminal = ratatui::Terminal::new(backend)?;
+    let viewport = match inline {
+        Some(rows) => ratatui::Viewport::Inline(rows),
+        None => ratatui::Viewport::Fullscreen,
+    };
+    // An inline viewport asks the terminal where the cursor is, which can
+    // fail; don't leave raw mode on behind the error
+    let mut terminal = match ratatui::Terminal::with_options(backend, ratatui::TerminalOptions { viewport }) {
+        Ok(terminal) => terminal,
+        Err(err) => {
+            restore_terminal(mouse_enabled, inline.is_some());
+            return Err(err.into());
+        }
+    };
 
-    // Shared storage
-    let trades = Arc::new(Mutex::new(VecDeque::new()));
-    let price_updates = Arc::new(Mutex::new(VecDeque::new()));
-    let trades_clone = trades.clone();
-    let price_updates_clone = price_updates.clone();
+    // Shared storage. Receivers write to these and publish snapshots; the
+    // UI only ever reads the latest snapshot.
+    let trades = Arc::new(SharedBuffer::new());
+    let price_updates = Arc::new(SharedBuffer::new());
+    let session_stats = Arc::new(Mutex::new(SessionStats::new(config.net_flow_window)));
+    let data_notify = Arc::new(Notify::new());
+    let metrics = Arc::new(Metrics::new(max_trades, max_price_updates));
 
     // Channels for WebSocket messages
-    let (trade_tx, mut trade_rx) = mpsc::channel(100);
-    let (price_tx, mut price_rx) = mpsc::channel(100);
+    let (trade_tx, trade_rx) = mpsc::channel(100);
+    let (price_tx, price_rx) = mpsc::channel(100);
     let (coin_tx, coin_rx) = mpsc::channel(10);
+    let (notice_tx, notice_rx) = mpsc::channel(10);
+    let (feed_state_tx, feed_state_rx) = watch::channel(FeedState::Connecting);
+    // Background tasks report to the event log through this one channel
+    let (events_tx, events_rx) = mpsc::unbounded_channel();
 
     // Spawn WebSocket handler
+    let channels = config.subscribe_channels.clone();
+    let feed_events = events_tx.clone();
     tokio::spawn(async move {
-        if let Err(e) = websocket::websocket_handler(trade_tx, price_tx, coin_rx).await {
-            eprintln!("WebSocket error: {}", e);
+        if let Err(e) = websocket::websocket_handler(websocket::WS_URL, &channels, trade_tx, price_tx, notice_tx, feed_state_tx, coin_rx).await {
+            // The feed isn't retried, so say so rather than leave it looking
+            // like a blip
+            let _ = feed_events.send(events::Event::new(
+                Severity::Error,
+                Source::Feed,
+                format!("WebSocket error: {} - the feed won't reconnect until the app is restarted", e),
+            ));
         }
     });
+    tokio::spawn(log_feed_state_changes(feed_state_rx.clone(), events_tx.clone(), metrics.clone()));
 
-    // Spawn trade receiver
-    tokio::spawn(async move {
-        while let Some(trade) = trade_rx.recv().await {
-            let mut trades = trades_clone.lock().unwrap();
-            trades.push_front(trade);
-            if trades.len() > MAX_TRADES {
-                trades.pop_back();
+    if let Some(listener) = http_listener {
+        let server = server::serve(listener, trades.clone(), price_updates.clone(), feed_state_rx.clone());
+        let server_events = events_tx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                let _ = server_events.send(events::Event::new(
+                    Severity::Error,
+                    Source::Server,
+                    format!("HTTP API stopped: {} - --serve is unavailable until the app is restarted", e),
+                ));
             }
-        }
+        });
+    }
+    #[cfg(unix)]
+    let socket_trades = socket_listener.map(|listener| {
+        let (socket_trades, _) = broadcast::channel(socket::CHANNEL_CAPACITY);
+        tokio::spawn(socket::serve(listener, socket_trades.clone()));
+        socket_trades
     });
+    #[cfg(not(unix))]
+    let socket_trades: Option<broadcast::Sender<Trade>> = None;
 
-    // Spawn price update receiver
-    tokio::spawn(async move {
-        while let Some(price_update) = price_rx.recv().await {
-            let mut updates = price_updates_clone.lock().unwrap();
-            updates.push_front(price_update);
-            if updates.len() > MAX_PRICE_UPDATES {
-                updates.pop_back();
+    // Spawn trade receiver. Large trades arrive on both feeds, so the second
+    // copy only marks the buffered one as large. Every stored trade also
+    // goes into the per-coin stats, which outlive the buffer's retention.
+    // Stored trades go out to any --socket clients, and a trade goes out
+    // again once it's been marked large.
+    // New trades that pass the trades view's filters are piped to --exec
+    // from here too, so the pipe doesn't wait on the display
+    let exec_pipe = exec_command
+        .map(|command| Arc::new(Mutex::new(Some(exec::ExecPipe::spawn(command, exec_restart, events_tx.clone())))));
+    // Nothing is piped until the UI has published its filters
+    let (exec_filter, exec_filter_rx) = watch::channel(None::<app::ExecFilter>);
+    let trade_exec_pipe = exec_pipe.clone();
+    let trade_stats = session_stats.clone();
+    let trade_metrics = metrics.clone();
+    tokio::spawn(store::ingest(
+        trade_rx,
+        trades.clone(),
+        trade_retention,
+        move |trades, trade: &Trade| {
+            trade_metrics.record_message();
+            let (trade, accepted) = match store::find_recent_duplicate(trades, trade, Trade::is_duplicate_of) {
+                Some(existing) if trade.is_large && !existing.is_large => {
+                    existing.is_large = true;
+                    (existing.clone(), Accept::Updated)
+                }
+                Some(_) => return Accept::Skip,
+                None => {
+                    trade_stats.lock().unwrap().record_trade(trade);
+                    (trade.clone(), Accept::Insert)
+                }
+            };
+            if let Some(exec_pipe) = &trade_exec_pipe {
+                if let Some(pipe) = exec_pipe.lock().unwrap().as_mut() {
+                    if exec_filter_rx.borrow().as_ref().is_some_and(|filter| filter.matches(&trade)) {
+                        pipe.send(&trade);
+                    }
+                }
+            }
+            if let Some(socket_trades) = &socket_trades {
+                // Fails only while no client is connected
+                let _ = socket_trades.send(trade);
+            }
+            accepted
+        },
+        data_notify.clone(),
+    ));
+
+    // Spawn price update receiver. Every update goes into the session stats
+    // (and so the latest-price map) as it arrives; the history only gets
+    // what the throttle lets through, and compacts its older entries into
+    // bars as they come in.
+    let price_stats = session_stats.clone();
+    let price_metrics = metrics.clone();
+    let price_notify = data_notify.clone();
+    let (price_throttle, price_throttle_rx) = watch::channel(Duration::from_millis(config.price_throttle_ms));
+    let (history_tx, history_rx) = mpsc::channel(100);
+    tokio::spawn(throttle::run(price_rx, history_tx, price_throttle_rx, move |price_update: &PriceUpdate| {
+        price_metrics.record_message();
+        let mut stats = price_stats.lock().unwrap();
+        if dedupe_price_updates {
+            let previous = stats.latest_price(&price_update.coin_symbol);
+            if previous.is_some_and(|previous| price_update.is_duplicate_of(previous)) {
+                return false;
             }
         }
-    });
+        stats.record_price(price_update);
+        price_notify.notify_one();
+        true
+    }));
+    let history_stats = session_stats.clone();
+    tokio::spawn(store::ingest(
+        history_rx,
+        price_updates.clone(),
+        price_retention,
+        move |_, price_update: &PriceUpdate| {
+            history_stats.lock().unwrap().record_history(price_update);
+            Accept::Insert
+        },
+        data_notify.clone(),
+    ));
 
     // Create app
-    let mut app = App::new(trades, price_updates);
+    let mut app = App::new(trades, price_updates, session_stats);
+    app.price_throttle = price_throttle;
+    app.exec_filter = exec_filter;
+    app.apply_config(config, config_path);
+    app.confirm_quit = confirm_quit;
+    app.theme = theme;
+    app.metrics = metrics;
+    if desktop_notifications {
+        app.enable_desktop_notifications();
+    }
+    if let Some(saved_state) = saved_state {
+        if let Some(coin) = app.restore_ui_state(saved_state) {
+            let _ = coin_tx.try_send(coin);
+        }
+    }
+    for warning in [state_warning, log_warning].into_iter().flatten() {
+        app.log_event(events::Event::new(Severity::Warning, Source::Storage, warning));
+    }
+    // An unreadable portfolio is left alone on disk rather than overwritten
+    if let Some(path) = portfolio::default_path() {
+        match portfolio::Portfolio::load(&path) {
+            Ok(loaded) => {
+                app.portfolio = loaded;
+                app.portfolio_path = Some(path);
+            }
+            Err(e) => app.log_event(events::Event::new(
+                Severity::Warning,
+                Source::Storage,
+                format!("Ignored paper portfolio in {}: {}; paper trades won't be saved", path.display(), e),
+            )),
+        }
+    }
 
     // Main loop
-    let result = run_app(&mut terminal, &mut app, coin_tx);
+    let input_rx = spawn_input_thread();
+    let result = run_app(&mut terminal, &mut app, coin_tx, input_rx, notice_rx, feed_state_rx, events_rx, data_notify, mouse_enabled).await;
 
-    // Cleanup
+    // Cleanup. The inline area is wiped so the prompt comes back where the
+    // app started.
+    if inline.is_some() {
+        terminal.clear()?;
+    }
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    if inline.is_none() {
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    }
+    if mouse_enabled {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
     terminal.show_cursor()?;
 
+    // Close the command's stdin and give it a moment to finish
+    if let Some(pipe) = exec_pipe.and_then(|pipe| pipe.lock().unwrap().take()) {
+        pipe.shutdown().await;
+    }
+
+    // Nothing may write to the terminal while the UI is drawn on it, so the
+    // session's errors are repeated here, where they stay readable
+    for event in app.events.errors() {
+        eprintln!("{} {} {}: {}", event.at.format("%H:%M:%S"), event.severity.label(), event.source.label(), event.message);
+    }
+
+    if result.is_ok() {
+        if let Some(ref path) = state_path {
+            if let Err(e) = app.ui_state().save(path) {
+                tracing::warn!(path = %path.display(), error = %e, "failed to save UI state");
+                eprintln!("Failed to save UI state to {}: {}", path.display(), e);
+            }
+        }
+    }
+
     result
 }
 
-fn run_app<B: ratatui::backend::Backend>(
+fn install_panic_hook(mouse_enabled: bool, inline: bool) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        restore_terminal(mouse_enabled, inline);
+        default_hook(panic_info);
+    }));
+}
+
+/// Best-effort terminal cleanup for the panic path. Every step ignores its
+/// error so a failure here can't turn into a panic inside the panic hook.
+fn restore_terminal(mouse_enabled: bool, inline: bool) {
+    let _ = disable_raw_mode();
+    if mouse_enabled {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+    }
+    if !inline {
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    }
+    let _ = execute!(io::stdout(), crossterm::cursor::Show);
+}
+
+/// Reads terminal events on a dedicated thread so the blocking crossterm
+/// calls never run on the async runtime.
+fn spawn_input_thread() -> mpsc::UnboundedReceiver<Event> {
+    let (input_tx, input_rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        while let Ok(event) = event::read() {
+            if input_tx.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    input_rx
+}
+
+/// Logs the feed connecting and dropping, and counts the connections.
+async fn log_feed_state_changes(mut state_rx: watch::Receiver<FeedState>, events_tx: events::EventSender, metrics: Arc<Metrics>) {
+    while state_rx.changed().await.is_ok() {
+        let event = match *state_rx.borrow_and_update() {
+            FeedState::Connecting => continue,
+            FeedState::Connected => {
+                metrics.record_connection();
+                events::Event::new(Severity::Info, Source::Feed, "Connected to the feed")
+            }
+            FeedState::Disconnected => events::Event::new(Severity::Warning, Source::Feed, "Disconnected from the feed"),
+        };
+        if events_tx.send(event).is_err() {
+            break;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut ratatui::Terminal<B>,
     app: &mut App,
     coin_tx: mpsc::Sender<String>,
+    mut input_rx: mpsc::UnboundedReceiver<Event>,
+    mut notice_rx: mpsc::Receiver<FeedNotice>,
+    mut feed_state_rx: watch::Receiver<FeedState>,
+    mut events_rx: mpsc::UnboundedReceiver<events::Event>,
+    data_notify: Arc<Notify>,
+    mouse_enabled: bool,
 ) -> Result<()> {
+    // Only redraw when input arrived, the data changed, or the periodic
+    // refresh for relative timestamps is due. Data-driven redraws are capped
+    // at one per frame interval so a busy feed can't monopolise the loop.
+    let mut dirty = true;
+    let mut last_generation = None;
+    let mut last_draw: Option<Instant> = None;
+    app.publish_exec_filter();
+
     loop {
-        // Update latest price if we have price updates
-        if let Some(tracked) = app.tracked_coin.clone() {
-            let latest_update = {
-                let updates = app.price_updates.lock().unwrap();
-                updates.iter().find(|u| u.coin_symbol == tracked).cloned()
-            };
-            if let Some(latest) = latest_update {
-                app.update_latest_price(latest);
-            }
-        }
-
-        terminal.draw(|f| ui::draw(f, app))?;
-
-        if event::poll(Duration::from_millis(100))? {
-            match event::read()? {
-                Event::Key(key) => {
-                    if key.kind == KeyEventKind::Press {
-                        match app.input_mode {
-                            InputMode::Normal => {
-                                if handle_normal_mode_input(app, key.code, &coin_tx)? {
-                                    break;
-                                }
-                            }
-                            InputMode::CoinFilter | InputMode::TraderFilter => {
-                                handle_filter_mode_input(app, key.code);
-                            }
-                            InputMode::CoinSelection => {
-                                handle_coin_selection_input(app, key.code, &coin_tx);
-                            }
-                        }
-                    }
+        dirty |= app.update_idle(Instant::now());
+        let periodic_interval = if app.idle { IDLE_REDRAW_INTERVAL } else { PERIODIC_REDRAW_INTERVAL };
+        let generation = app.data_generation();
+        let data_pending = last_generation != Some(generation);
+        let since_draw = last_draw.map(|t| t.elapsed());
+        let frame_due = since_draw.is_none_or(|elapsed| elapsed >= MIN_FRAME_INTERVAL);
+        let periodic_due = since_draw.is_none_or(|elapsed| elapsed >= periodic_interval);
+
+        if dirty || periodic_due || (data_pending && frame_due) {
+            if dirty || data_pending {
+                last_generation = Some(generation);
+                refresh_derived_state(app);
+            }
+            terminal.draw(|f| ui::draw(f, app))?;
+            last_draw = Some(Instant::now());
+            dirty = false;
+        }
+
+        let last_draw_at = last_draw.unwrap_or_else(Instant::now);
+        let data_pending = last_generation != Some(app.data_generation());
+        let wake_at = if data_pending {
+            last_draw_at + MIN_FRAME_INTERVAL
+        } else {
+            last_draw_at + periodic_interval
+        };
+
+        tokio::select! {
+            event = input_rx.recv() => {
+                let Some(event) = event else { break };
+                app.note_input();
+                if handle_event(app, event, &coin_tx, mouse_enabled)? {
+                    break;
                 }
-                Event::Mouse(mouse) => {
-                    handle_mouse_input(app, mouse, &coin_tx);
+                app.publish_exec_filter();
+                dirty = true;
+            }
+            // Server errors, new message types and unreadable or repaired
+            // messages go into the event log, and so into the alert banner
+            Some(notice) = notice_rx.recv() => {
+                match notice {
+                    FeedNotice::ServerError(message) => {
+                        app.log_event(events::Event::new(Severity::Error, Source::Feed, format!("Server error: {}", message)));
+                    }
+                    FeedNotice::UnknownMessageType(msg_type) => app.record_unknown_message_type(msg_type),
+                    FeedNotice::ParseError(message) => {
+                        app.metrics.record_unreadable();
+                        app.log_event(events::Event::new(Severity::Warning, Source::Feed, format!("Unreadable feed message - {}", message)));
+                    }
+                    FeedNotice::UnknownTradeFields(fields) => app.record_unknown_trade_fields(fields),
+                    FeedNotice::Repaired(message) => {
+                        app.metrics.record_repaired();
+                        app.log_event(events::Event::new(Severity::Warning, Source::Feed, format!("Missing or negative numbers set to 0 - {}", message)));
+                    }
                 }
-                _ => {}
+                dirty = true;
+            }
+            Ok(()) = feed_state_rx.changed() => {
+                app.set_feed_state(*feed_state_rx.borrow_and_update());
+                dirty = true;
             }
+            Some(event) = events_rx.recv() => {
+                app.log_event(event);
+                dirty = true;
+            }
+            _ = data_notify.notified(), if !data_pending => {}
+            _ = tokio::time::sleep_until(wake_at.into()) => {}
         }
     }
     Ok(())
 }
 
-fn handle_normal_mode_input(app: &mut App, key_code: KeyCode, _coin_tx: &mpsc::Sender<String>) -> Result<bool> {
-    match key_code {
-        KeyCode::Char('q') => Ok(true),
-        KeyCode::Char('p') => {
-            app.switch_page();
-            Ok(false)
+/// Brings state derived from the shared buffers up to date before a draw.
+fn refresh_derived_state(app: &mut App) {
+    // The latest-price map sees every update, including ones the throttle
+    // keeps out of the history
+    if let Some(tracked) = app.tracked_coin.clone() {
+        let latest_update = app.session_stats.lock().unwrap().latest_price(&tracked).cloned();
+        if let Some(latest) = latest_update {
+            app.update_latest_price(latest);
         }
-        KeyCode::Tab => {
-            if app.current_page == AppPage::Trades {
-                app.switch_trade_filter();
+    }
+
+    app.check_new_trades();
+}
+
+/// Dispatches one terminal event. Returns `true` when the app should quit.
+fn handle_event(app: &mut App, event: Event, coin_tx: &mpsc::Sender<String>, mouse_enabled: bool) -> Result<bool> {
+    match event {
+        Event::Key(key) if key.kind == KeyEventKind::Press => {
+            if keymap::lookup(&keymap::ANY_MODE, &key) == Some(Action::ForceQuit) {
+                return Ok(app.register_ctrl_c());
             }
-            Ok(false)
+            match app.input_mode {
+                InputMode::Normal => {
+                    return handle_normal_mode_input(app, key, coin_tx);
+                }
+                InputMode::CoinFilter | InputMode::TraderFilter => {
+                    handle_filter_mode_input(app, key, coin_tx);
+                }
+                InputMode::CoinSelection => {
+                    handle_coin_selection_input(app, key, coin_tx);
+                }
+                InputMode::CoinList => {
+                    handle_coin_list_input(app, key, coin_tx);
+                }
+                InputMode::MuteList => {
+                    handle_mute_list_input(app, key);
+                }
+                InputMode::FollowList => {
+                    handle_follow_list_input(app, key);
+                }
+                InputMode::BookmarkList => {
+                    handle_bookmark_list_input(app, key);
+                }
+                InputMode::EventLog => {
+                    handle_event_log_input(app, key);
+                }
+                InputMode::ConfirmClear => {
+                    handle_confirm_clear_input(app, key);
+                }
+                InputMode::ConfirmQuit => {
+                    return Ok(handle_confirm_quit_input(app, key));
+                }
+                InputMode::Help => {
+                    handle_help_input(app, key);
+                }
+                InputMode::CoinStats => {
+                    handle_coin_stats_input(app, key);
+                }
+                InputMode::PositionEntry => {
+                    handle_position_entry_input(app, key);
+                }
+                InputMode::OrderEntry => {
+                    handle_order_entry_input(app, key);
+                }
+                InputMode::ConfirmLinkFilter => {
+                    handle_confirm_link_filter_input(app, key);
+                }
+                InputMode::Command => {
+                    return handle_command_input(app, key, coin_tx);
+                }
+                InputMode::Search => {
+                    handle_search_input(app, key);
+                }
+            }
+        }
+        // Nothing on screen to point at while it's too small to lay out
+        Event::Mouse(mouse) if mouse_enabled && !app.too_small.get() => {
+            handle_mouse_input(app, mouse, coin_tx);
         }
-        KeyCode::Char('c') => {
-            if app.current_page == AppPage::Trades {
-                app.start_coin_filter();
+        // The caller redraws after every event
+        Event::Resize(_, _) => app.handle_resize(),
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn handle_normal_mode_input(app: &mut App, key: KeyEvent, coin_tx: &mpsc::Sender<String>) -> Result<bool> {
+    let Some(action) = keymap::normal_action(&key, &app.current_page) else {
+        return Ok(false);
+    };
+    dispatch_action(app, action, coin_tx)
+}
+
+/// Runs a normal-mode action, whether it came from a key or the command
+/// line. Returns `true` when the app should quit.
+fn dispatch_action(app: &mut App, action: Action, coin_tx: &mpsc::Sender<String>) -> Result<bool> {
+    match action {
+        Action::Quit => return Ok(app.request_quit()),
+        Action::ShowHelp => app.open_help(),
+        Action::OpenCommand => app.start_command(),
+        Action::SwitchPage => app.switch_page(),
+        Action::OpenPortfolio => app.toggle_portfolio(),
+        Action::PaperBuy => app.start_order(Side::Buy),
+        Action::PaperSell => app.start_order(Side::Sell),
+        Action::ToggleSplit => app.toggle_split_view(),
+        Action::ShrinkSplit => app.resize_split(-1),
+        Action::GrowSplit => app.resize_split(1),
+        Action::TogglePause => app.toggle_pause(),
+        Action::ClearPage => app.start_clear_confirmation(ClearScope::Page),
+        Action::ClearAll => app.start_clear_confirmation(ClearScope::All),
+        Action::ScrollUp => app.scroll_up(),
+        Action::ScrollDown => app.scroll_down(),
+        Action::FastScrollUp => app.scroll_up_by(app::FAST_SCROLL_ROWS),
+        Action::FastScrollDown => app.scroll_down_by(app::FAST_SCROLL_ROWS),
+        Action::SwitchTradeFilter => app.switch_trade_filter(),
+        Action::ShowAllTrades => app.set_trade_filter(TradeFilter::All),
+        Action::ShowLargeTrades => app.set_trade_filter(TradeFilter::Large),
+        Action::SetTheme(theme) => app.set_theme(theme),
+        Action::CoinFilter => app.start_coin_filter(),
+        Action::TraderFilter => app.start_trader_filter(),
+        Action::ClearFilters => app.clear_filters(),
+        Action::StartSearch => app.start_search(),
+        Action::NextMatch => app.step_search(true),
+        Action::PreviousMatch => app.step_search(false),
+        Action::ClearSearch => app.clear_search(),
+        Action::ToggleCompact => app.toggle_compact_view(),
+        Action::ToggleGroupTrades => app.toggle_group_trades(),
+        Action::ToggleMilliseconds => app.toggle_millisecond_timestamps(),
+        Action::ToggleStatusBar => app.toggle_status_bar(),
+        Action::ToggleAvatarOnly => app.toggle_avatar_only(),
+        Action::CycleSort => match app.current_page {
+            AppPage::Trades => app.cycle_trade_sort(),
+            AppPage::PriceTracker => app.cycle_coin_sort(),
+            AppPage::Portfolio => {}
+        },
+        Action::WatchCoin => app.toggle_watch_selected_coin(),
+        Action::FollowTrader => app.toggle_follow_selected_trader(),
+        Action::MuteCoin => app.mute_selected(MuteKind::Coin),
+        Action::MuteTrader => app.mute_selected(MuteKind::Trader),
+        Action::OpenMutes => app.open_mute_list(),
+        Action::SelectCoin => app.open_coin_list(),
+        Action::ToggleChart => app.toggle_price_chart(),
+        Action::ToggleMovingAverages => app.toggle_moving_averages(),
+        Action::EditPosition => app.start_position_entry(),
+        Action::ToggleCoinLink => app.toggle_coin_link(),
+        Action::ToggleAllCoins => {
+            if let Some(coin) = app.toggle_all_coins_view() {
+                let _ = coin_tx.try_send(coin);
             }
-            Ok(false)
         }
-        KeyCode::Char('t') => {
-            if app.current_page == AppPage::Trades {
-                app.start_trader_filter();
+        Action::Confirm => {
+            if let Some(coin) = app.track_selected_coin() {
+                let _ = coin_tx.try_send(coin);
             }
-            Ok(false)
         }
-        KeyCode::Char('s') => {
-            if app.current_page == AppPage::PriceTracker {
-                app.start_coin_selection();
+        Action::OpenCoinPage => app.open_selected_coin_page(),
+        Action::OpenTraderPage => app.open_selected_trader_page(),
+        Action::OpenCoinIcon => app.open_selected_coin_icon(),
+        Action::CoinStats => app.open_coin_stats(),
+        Action::ToggleFollowPane => app.toggle_follow_pane(),
+        Action::ToggleSizeHistogram => app.toggle_size_histogram(),
+        Action::ToggleVolumeChart => app.toggle_volume_chart(),
+        Action::ToggleCoinCounts => app.toggle_coin_counts_chart(),
+        Action::ToggleBuySellMix => app.toggle_buy_sell_mix(),
+        Action::CycleColorBasis => app.cycle_color_basis(),
+        Action::SelectPriceRange(range) => app.select_price_range(range),
+        Action::FollowPaneUp => app.follow_scroll_up_by(1),
+        Action::FollowPaneDown => app.follow_scroll_down_by(1),
+        Action::OpenFollows => app.open_follow_list(),
+        Action::ToggleBookmark => app.toggle_bookmark_selected(),
+        Action::OpenBookmarks => app.open_bookmark_list(),
+        Action::OpenEvents => app.open_event_log(),
+        Action::CopySummary => app.copy_selected(false),
+        Action::CopyJson => app.copy_selected(true),
+        Action::CopyVisibleTrades => app.copy_visible_trades(),
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn handle_command_input(app: &mut App, key: KeyEvent, coin_tx: &mpsc::Sender<String>) -> Result<bool> {
+    let action = keymap::lookup(&keymap::COMMAND_INPUT, &key);
+    if action != Some(Action::Confirm) {
+        app.command_error = None;
+    }
+    match action {
+        // A command that can't run leaves the line open for fixing
+        Some(Action::Confirm) => match command::parse(&app.input_buffer) {
+            Ok(command) => {
+                app.input_mode = InputMode::Normal;
+                return run_command(app, command, coin_tx);
             }
-            Ok(false)
+            Err(message) => app.command_error = Some(message),
+        },
+        Some(Action::Complete) => app.complete_command(),
+        Some(Action::Cancel) => app.cancel_filter(),
+        Some(Action::DeleteChar) => app.delete_from_input(),
+        _ => {
+            if let KeyCode::Char(c) = key.code {
+                app.add_to_input(c);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn run_command(app: &mut App, command: Command, coin_tx: &mpsc::Sender<String>) -> Result<bool> {
+    let coin = match command {
+        Command::Run(action) => return dispatch_action(app, action, coin_tx),
+        Command::CoinFilter(filter) => app.set_coin_filter(filter),
+        Command::TraderFilter(filter) => {
+            app.set_trader_filter(filter);
+            None
+        }
+        Command::Track(symbol) => app.track_coin(&symbol),
+        Command::Export(format, path) => {
+            app.export_visible_trades(format, &path);
+            None
         }
-        KeyCode::Up => {
-            app.scroll_up();
-            Ok(false)
+        Command::ExportBookmarks(format, path) => {
+            app.export_bookmarks(format, &path);
+            None
         }
-        KeyCode::Down => {
-            app.scroll_down();
-            Ok(false)
+        Command::PriceThrottle(interval) => {
+            app.set_price_throttle(interval);
+            None
         }
-        _ => Ok(false),
+        Command::TradeSince(window) => {
+            app.set_trade_since(window);
+            None
+        }
+    };
+    if let Some(coin) = coin {
+        let _ = coin_tx.try_send(coin);
     }
+    Ok(false)
 }
 
-fn handle_filter_mode_input(app: &mut App, key_code: KeyCode) {
-    match key_code {
-        KeyCode::Enter => app.confirm_filter(),
-        KeyCode::Esc => app.cancel_filter(),
-        KeyCode::Char(c) => app.add_to_input(c),
-        KeyCode::Backspace => app.delete_from_input(),
-        _ => {}
+fn handle_search_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::SEARCH_INPUT, &key) {
+        Some(Action::Confirm) => app.confirm_search(),
+        Some(Action::Cancel) => app.cancel_filter(),
+        Some(Action::DeleteChar) => app.delete_from_input(),
+        _ => {
+            if let KeyCode::Char(c) = key.code {
+                app.add_to_input(c);
+            }
+        }
     }
 }
 
-fn handle_coin_selection_input(app: &mut App, key_code: KeyCode, coin_tx: &mpsc::Sender<String>) {
-    match key_code {
-        KeyCode::Enter => {
+fn handle_filter_mode_input(app: &mut App, key: KeyEvent, coin_tx: &mpsc::Sender<String>) {
+    match keymap::lookup(&keymap::TEXT_INPUT, &key) {
+        Some(Action::Confirm) => {
+            if let Some(coin_symbol) = app.confirm_filter() {
+                let _ = coin_tx.try_send(coin_symbol);
+            }
+        }
+        Some(Action::Cancel) => app.cancel_filter(),
+        Some(Action::DeleteChar) => app.delete_from_input(),
+        Some(Action::ScrollUp) => app.scroll_up(),
+        Some(Action::ScrollDown) => app.scroll_down(),
+        _ => {
+            if let KeyCode::Char(c) = key.code {
+                app.add_to_input(c);
+            }
+        }
+    }
+}
+
+fn handle_coin_selection_input(app: &mut App, key: KeyEvent, coin_tx: &mpsc::Sender<String>) {
+    match keymap::lookup(&keymap::TEXT_INPUT, &key) {
+        Some(Action::Confirm) => {
             if let Some(coin_symbol) = app.confirm_coin_selection() {
                 let _ = coin_tx.try_send(coin_symbol);
             }
         }
-        KeyCode::Esc => app.cancel_filter(),
-        KeyCode::Char(c) => app.add_to_input(c),
-        KeyCode::Backspace => app.delete_from_input(),
+        Some(Action::Cancel) => app.cancel_filter(),
+        Some(Action::DeleteChar) => app.delete_from_input(),
+        _ => {
+            if let KeyCode::Char(c) = key.code {
+                app.add_to_input(c);
+            }
+        }
+    }
+}
+
+fn handle_coin_list_input(app: &mut App, key: KeyEvent, coin_tx: &mpsc::Sender<String>) {
+    match keymap::lookup(&keymap::COIN_LIST, &key) {
+        Some(Action::ScrollUp) => app.coin_list_up(),
+        Some(Action::ScrollDown) => app.coin_list_down(),
+        Some(Action::Confirm) => {
+            if let Some(coin_symbol) = app.confirm_coin_list_selection() {
+                let _ = coin_tx.try_send(coin_symbol);
+            }
+        }
+        // Fall back to typing the symbol
+        Some(Action::TypeSymbol) => app.start_coin_selection(),
+        Some(Action::Cancel) => app.cancel_filter(),
+        _ => {}
+    }
+}
+
+fn handle_position_entry_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::TEXT_INPUT, &key) {
+        Some(Action::Confirm) => app.confirm_position_entry(),
+        Some(Action::Cancel) => app.cancel_filter(),
+        Some(Action::DeleteChar) => app.delete_from_input(),
+        _ => {
+            if let KeyCode::Char(c) = key.code {
+                app.add_to_input(c);
+            }
+        }
+    }
+}
+
+fn handle_order_entry_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::ORDER_ENTRY, &key) {
+        Some(Action::Confirm) => app.place_order(),
+        Some(Action::Cancel) => app.cancel_filter(),
+        Some(Action::Complete) => app.complete_order_coin(),
+        Some(Action::PreviousField) => app.cycle_order_field(false),
+        Some(Action::NextField) => app.cycle_order_field(true),
+        Some(Action::ToggleSide) => app.toggle_order_side(),
+        Some(Action::DeleteChar) => app.order_backspace(),
+        _ => {
+            if let KeyCode::Char(c) = key.code {
+                app.order_input(c);
+            }
+        }
+    }
+}
+
+fn handle_event_log_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::EVENT_LOG, &key) {
+        Some(Action::ScrollUp) => app.event_log_up(),
+        Some(Action::ScrollDown) => app.event_log_down(),
+        Some(Action::Cancel) => app.close_event_log(),
+        _ => {}
+    }
+}
+
+fn handle_bookmark_list_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::BOOKMARK_LIST, &key) {
+        Some(Action::ScrollUp) => app.bookmark_list_up(),
+        Some(Action::ScrollDown) => app.bookmark_list_down(),
+        Some(Action::Unbookmark) => app.remove_selected_bookmark(),
+        Some(Action::Cancel) => app.close_bookmark_list(),
+        _ => {}
+    }
+}
+
+fn handle_mute_list_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::MUTE_LIST, &key) {
+        Some(Action::ScrollUp) => app.mute_list_up(),
+        Some(Action::ScrollDown) => app.mute_list_down(),
+        Some(Action::Unmute) => app.remove_selected_mute(),
+        Some(Action::Cancel) => app.close_mute_list(),
+        _ => {}
+    }
+}
+
+fn handle_follow_list_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::FOLLOW_LIST, &key) {
+        Some(Action::ScrollUp) => app.follow_list_up(),
+        Some(Action::ScrollDown) => app.follow_list_down(),
+        Some(Action::Unfollow) => app.unfollow_selected(),
+        Some(Action::Cancel) => app.close_follow_list(),
+        _ => {}
+    }
+}
+
+fn handle_confirm_clear_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::CONFIRM_CLEAR, &key) {
+        Some(Action::Confirm) => app.confirm_clear(),
+        _ => app.cancel_filter(),
+    }
+}
+
+fn handle_confirm_link_filter_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::CONFIRM_LINK_FILTER, &key) {
+        Some(Action::Confirm) => app.accept_link_filter_offer(),
+        _ => app.decline_link_filter_offer(),
+    }
+}
+
+/// Returns `true` when the user confirmed. Other keys are ignored so the
+/// dialog can't be dismissed by accident.
+fn handle_confirm_quit_input(app: &mut App, key: KeyEvent) -> bool {
+    match keymap::lookup(&keymap::CONFIRM_QUIT, &key) {
+        Some(Action::Confirm) => true,
+        Some(Action::Cancel) => {
+            app.input_mode = InputMode::Normal;
+            false
+        }
+        _ => false,
+    }
+}
+
+fn handle_help_input(app: &mut App, key: KeyEvent) {
+    match keymap::lookup(&keymap::HELP, &key) {
+        Some(Action::ScrollUp) => app.help_scroll_up(),
+        Some(Action::ScrollDown) => app.help_scroll_down(),
+        Some(Action::Cancel) => app.close_help(),
         _ => {}
     }
 }
 
+fn handle_coin_stats_input(app: &mut App, key: KeyEvent) {
+    if keymap::lookup(&keymap::COIN_STATS, &key) == Some(Action::Cancel) {
+        app.close_coin_stats();
+    }
+}
+
 fn handle_mouse_input(app: &mut App, mouse: MouseEvent, coin_tx: &mpsc::Sender<String>) {
     match mouse.kind {
+        // A confirmation waits for an answer; nothing behind it scrolls
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+            if matches!(app.input_mode, InputMode::ConfirmQuit | InputMode::ConfirmClear | InputMode::ConfirmLinkFilter) => {}
+        // The help overlay scrolls on its own
+        MouseEventKind::ScrollUp if app.input_mode == InputMode::Help => {
+            app.help_scroll_up();
+        }
+        MouseEventKind::ScrollDown if app.input_mode == InputMode::Help => {
+            app.help_scroll_down();
+        }
+        // The followed-traders pane scrolls on its own when hovered
+        MouseEventKind::ScrollUp if app.in_follow_pane(mouse.column, mouse.row) => {
+            app.follow_scroll_up_by(app.wheel_scroll_rows);
+        }
+        MouseEventKind::ScrollDown if app.in_follow_pane(mouse.column, mouse.row) => {
+            app.follow_scroll_down_by(app.wheel_scroll_rows);
+        }
+        // Shift+wheel moves a page at a time. In split view the wheel
+        // scrolls (and focuses) the pane under the pointer.
         MouseEventKind::ScrollUp => {
-            app.scroll_up();
+            app.focus_pane_at(mouse.column, mouse.row);
+            let rows = if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+                app.page_size()
+            } else {
+                app.wheel_scroll_rows
+            };
+            app.scroll_up_by(rows);
         }
         MouseEventKind::ScrollDown => {
-            app.scroll_down();
+            app.focus_pane_at(mouse.column, mouse.row);
+            let rows = if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+                app.page_size()
+            } else {
+                app.wheel_scroll_rows
+            };
+            app.scroll_down_by(rows);
         }
-        MouseEventKind::Down(button) => {
-            if button == MouseButton::Left {
-                handle_click(app, mouse.column, mouse.row, coin_tx);
+        MouseEventKind::Down(MouseButton::Left) => {
+            if app.input_mode == InputMode::Normal
+                && (app.press_scrollbar(mouse.column, mouse.row) || app.press_divider(mouse.column, mouse.row))
+            {
+                return;
+            }
+            // Ctrl- or Alt-clicking a coin symbol or username filters to it
+            let quick_filter = mouse
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+                .then(|| app.quick_filter_at(mouse.column, mouse.row))
+                .flatten();
+            if let Some(filter) = quick_filter.filter(|_| app.input_mode == InputMode::Normal) {
+                if let Some(coin_symbol) = app.apply_quick_filter(filter) {
+                    let _ = coin_tx.try_send(coin_symbol);
+                }
+                return;
             }
+            handle_click(app, mouse.column, mouse.row, coin_tx);
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            app.drag_scrollbar(mouse.row);
+            app.drag_divider(mouse.column);
+        }
+        MouseEventKind::Up(MouseButton::Left) => {
+            app.release_scrollbar();
+            app.release_divider();
         }
         _ => {}
     }
@@ -223,72 +972,73 @@ fn handle_click(app: &mut App, x: u16, y: u16, _coin_tx: &mpsc::Sender<String>)
     if app.input_mode != InputMode::Normal {
         return;
     }
+    // The rows below count from the top of the app, which isn't the top of
+    // the screen when drawing inline
+    let y = y.saturating_sub(app.viewport_top.get());
 
     // Page tabs are at y=0-2 (including borders), full width
     if y <= 2 {
-        // More precise tab detection for page tabs
-        if let Ok(size) = crossterm::terminal::size() {
-            let tab_width = size.0 / 2;
-            // Add some margin for better click detection
-            if x <= tab_width + 2 {
-                // Trade Monitor tab clicked (left half)
-                if app.current_page != AppPage::Trades {
-                    app.switch_page();
-                }
-            } else {
-                // Price Tracker tab clicked (right half)
-                if app.current_page != AppPage::PriceTracker {
-                    app.switch_page();
-                }
-            }
+        // The renderer records where each tab was drawn
+        if let Some(page) = app.page_tab_at(x) {
+            app.show_page(page);
         }
         return;
     }
 
+    let Ok((width, _)) = crossterm::terminal::size() else {
+        return;
+    };
+
+    // In split view each pane is laid out on its own; clicking one also
+    // focuses it
+    let (page, x, pane_width) = match app.split_panes.get() {
+        Some((trades, prices)) if x < prices.x => (AppPage::Trades, x - trades.x, trades.width),
+        Some((_, prices)) => (AppPage::PriceTracker, x - prices.x, prices.width),
+        None => (app.current_page.clone(), x, width),
+    };
+    if y >= 3 && app.current_page != page {
+        app.switch_page();
+    }
+
     // Content area starts at y=3
-    match app.current_page {
+    match page {
         AppPage::Trades => {
             // Filter area is at y=3-5
-            if y >= 3 && y <= 5 {
-                if let Ok(size) = crossterm::terminal::size() {
-                    let filter_width = size.0 / 2;
-                    if x <= filter_width {
-                        // Coin filter clicked (left half)
-                        app.start_coin_filter();
-                    } else {
-                        // Trader filter clicked (right half)
-                        app.start_trader_filter();
-                    }
+            if (3..=5).contains(&y) {
+                let filter_width = pane_width / 2;
+                if x <= filter_width {
+                    // Coin filter clicked (left half)
+                    app.start_coin_filter();
+                } else {
+                    // Trader filter clicked (right half)
+                    app.start_trader_filter();
                 }
                 return;
             }
             
             // Trade type tabs are at y=6-8 (the trade tabs within the trades page)
-            if y >= 6 && y <= 8 {
-                if let Ok(size) = crossterm::terminal::size() {
-                    // More precise detection for trade type tabs
-                    let tab_width = size.0 / 2;
-                    if x <= tab_width + 2 {
-                        // All Trades tab clicked (left half)
-                        if app.trade_filter != TradeFilter::All {
-                            app.switch_trade_filter();
-                        }
-                    } else {
-                        // Large Trades tab clicked (right half)
-                        if app.trade_filter != TradeFilter::Large {
-                            app.switch_trade_filter();
-                        }
+            if (6..=8).contains(&y) {
+                // More precise detection for trade type tabs
+                let tab_width = pane_width / 2;
+                if x <= tab_width + 2 {
+                    // All Trades tab clicked (left half)
+                    if app.trade_filter != TradeFilter::All {
+                        app.switch_trade_filter();
+                    }
+                } else {
+                    // Large Trades tab clicked (right half)
+                    if app.trade_filter != TradeFilter::Large {
+                        app.switch_trade_filter();
                     }
                 }
-                return;
             }
         }
         AppPage::PriceTracker => {
             // Coin selection area is at y=3-5
-            if y >= 3 && y <= 5 {
-                app.start_coin_selection();
-                return;
+            if (3..=5).contains(&y) {
+                app.open_coin_list();
             }
         }
+        AppPage::Portfolio => {}
     }
 }
\ No newline at end of file
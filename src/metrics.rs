@@ -0,0 +1,165 @@
+//! Feed counters for the status bar, shared between the receivers that
+//! update them and the UI that reads them.
+
+use crate::config::{DEFAULT_MAX_PRICE_UPDATES, DEFAULT_MAX_TRADES};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Seconds the message rate is averaged over.
+pub const RATE_WINDOW_SECS: u64 = 10;
+
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    /// Most trades and price updates the buffers keep
+    pub trade_capacity: usize,
+    pub price_capacity: usize,
+    connections: AtomicU64,
+    /// Trade and price messages dropped as unreadable
+    unreadable: AtomicU64,
+    /// Trade and price messages kept after numbers in them were set to 0
+    repaired: AtomicU64,
+    /// Milliseconds after `started` of the latest message, plus one; zero
+    /// until the first
+    last_message: AtomicU64,
+    rate: Mutex<RateCounter>,
+}
+
+impl Metrics {
+    pub fn new(trade_capacity: usize, price_capacity: usize) -> Self {
+        Self {
+            started: Instant::now(),
+            trade_capacity,
+            price_capacity,
+            connections: AtomicU64::new(0),
+            unreadable: AtomicU64::new(0),
+            repaired: AtomicU64::new(0),
+            last_message: AtomicU64::new(0),
+            rate: Mutex::new(RateCounter::default()),
+        }
+    }
+
+    /// Counts a trade or price update arriving from the feed.
+    pub fn record_message(&self) {
+        let elapsed = self.started.elapsed();
+        self.last_message.store(elapsed.as_millis() as u64 + 1, Ordering::Relaxed);
+        self.rate.lock().unwrap().record(elapsed.as_secs());
+    }
+
+    /// When the latest trade or price update arrived, if any has.
+    pub fn last_message_at(&self) -> Option<Instant> {
+        match self.last_message.load(Ordering::Relaxed) {
+            0 => None,
+            millis => Some(self.started + Duration::from_millis(millis - 1)),
+        }
+    }
+
+    pub fn record_connection(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_unreadable(&self) {
+        self.unreadable.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_repaired(&self) {
+        self.repaired.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn unreadable(&self) -> u64 {
+        self.unreadable.load(Ordering::Relaxed)
+    }
+
+    pub fn repaired(&self) -> u64 {
+        self.repaired.load(Ordering::Relaxed)
+    }
+
+    /// Connections made after the first one.
+    pub fn reconnects(&self) -> u64 {
+        self.connections.load(Ordering::Relaxed).saturating_sub(1)
+    }
+
+    pub fn session_uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Feed messages per second over the last [`RATE_WINDOW_SECS`].
+    pub fn messages_per_second(&self) -> f64 {
+        let now = self.started.elapsed().as_secs();
+        self.rate.lock().unwrap().per_second(now)
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_TRADES, DEFAULT_MAX_PRICE_UPDATES)
+    }
+}
+
+/// Messages counted per second of the session, for the last
+/// [`RATE_WINDOW_SECS`] seconds.
+#[derive(Debug, Default)]
+pub struct RateCounter {
+    buckets: VecDeque<(u64, u64)>,
+}
+
+impl RateCounter {
+    pub fn record(&mut self, second: u64) {
+        match self.buckets.back_mut() {
+            Some((last, count)) if *last == second => *count += 1,
+            _ => self.buckets.push_back((second, 1)),
+        }
+        while self.buckets.front().is_some_and(|(first, _)| first + RATE_WINDOW_SECS <= second) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Average over the window ending with second `now`, or over the whole
+    /// session while it's shorter than the window.
+    pub fn per_second(&self, now: u64) -> f64 {
+        let count: u64 = self
+            .buckets
+            .iter()
+            .filter(|(second, _)| second + RATE_WINDOW_SECS > now)
+            .map(|(_, count)| count)
+            .sum();
+        count as f64 / RATE_WINDOW_SECS.min(now + 1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_averages_the_last_ten_seconds() {
+        let mut rate = RateCounter::default();
+        assert_eq!(rate.per_second(0), 0.0);
+        for _ in 0..4 {
+            rate.record(0);
+        }
+        rate.record(1);
+        assert_eq!(rate.per_second(1), 2.5);
+
+        for second in 2..20 {
+            rate.record(second);
+        }
+        assert_eq!(rate.per_second(19), 1.0);
+        // Nothing arrived for a while
+        assert_eq!(rate.per_second(24), 0.5);
+        assert_eq!(rate.per_second(40), 0.0);
+    }
+
+    #[test]
+    fn the_first_connection_is_not_a_reconnect() {
+        let metrics = Metrics::default();
+        assert_eq!(metrics.reconnects(), 0);
+        metrics.record_connection();
+        assert_eq!(metrics.reconnects(), 0);
+        metrics.record_connection();
+        metrics.record_connection();
+        assert_eq!(metrics.reconnects(), 2);
+    }
+}
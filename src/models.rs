@@ -1,26 +1,63 @@
+use crate::store::Timestamped;
 use chrono::{DateTime, Local};
-use serde::{Deserialize, Serialize};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A money amount as `f64`, for charts and ratios where rounding in the
+/// last digits can't be seen.
+pub fn lossy_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+/// `value` to `decimals` places, rounded half away from zero. Formatting a
+/// `Decimal` with `{:.2}` cuts the remaining digits off instead.
+pub fn round_to(value: Decimal, decimals: u32) -> Decimal {
+    value.round_dp_with_strategy(decimals, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// A trade as the feed sends it. Only what a trade can't be shown without is
+/// required; fields the feed has added since are kept in `extra` so they
+/// show up in exports and under "Feed diagnostics" instead of vanishing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TradeData {
     #[serde(rename = "type")]
     pub trade_type: String,
     pub username: String,
-    #[serde(rename = "userImage")]
-    pub user_image: String,
-    pub amount: f64,
+    #[serde(rename = "userImage", default)]
+    pub user_image: Option<String>,
+    pub amount: Decimal,
     #[serde(rename = "coinSymbol")]
     pub coin_symbol: String,
-    #[serde(rename = "coinName")]
+    /// Empty when the feed leaves it out
+    #[serde(rename = "coinName", default)]
     pub coin_name: String,
-    #[serde(rename = "coinIcon")]
-    pub coin_icon: String,
+    #[serde(rename = "coinIcon", default)]
+    pub coin_icon: Option<String>,
     #[serde(rename = "totalValue")]
-    pub total_value: f64,
-    pub price: f64,
+    pub total_value: Decimal,
+    pub price: Decimal,
     pub timestamp: i64,
-    #[serde(rename = "userId")]
+    #[serde(rename = "userId", default)]
     pub user_id: String,
+    /// Fields this version doesn't know about, by feed name, in name order
+    /// so exports list them the same way every time
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl TradeData {
+    /// Whether the trader has a picture of their own: a non-empty image that
+    /// doesn't contain any of the `placeholders` (compared case-insensitively).
+    pub fn has_avatar(&self, placeholders: &[String]) -> bool {
+        let image = self.user_image.as_deref().unwrap_or_default().trim().to_lowercase();
+        !image.is_empty()
+            && !placeholders
+                .iter()
+                .any(|placeholder| !placeholder.is_empty() && image.contains(&placeholder.to_lowercase()))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,81 +65,878 @@ pub struct PriceUpdateData {
     #[serde(rename = "coinSymbol")]
     pub coin_symbol: String,
     #[serde(rename = "currentPrice")]
-    pub current_price: f64,
+    pub current_price: Decimal,
     #[serde(rename = "marketCap")]
-    pub market_cap: f64,
-    #[serde(rename = "change24h")]
+    pub market_cap: Decimal,
+    #[serde(rename = "change24h", deserialize_with = "number_as_f64")]
     pub change_24h: f64,
     #[serde(rename = "volume24h")]
-    pub volume_24h: f64,
+    pub volume_24h: Decimal,
     #[serde(rename = "poolCoinAmount")]
-    pub pool_coin_amount: f64,
+    pub pool_coin_amount: Decimal,
     #[serde(rename = "poolBaseCurrencyAmount")]
-    pub pool_base_currency_amount: f64,
+    pub pool_base_currency_amount: Decimal,
+}
+
+/// Reads an `f64` by way of `serde_json::Number`. With exact numbers on,
+/// a flattened struct hands its numbers over in a form only `Number` (and
+/// `Decimal`) can read back.
+fn number_as_f64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let number = serde_json::Number::deserialize(deserializer)?;
+    number.as_f64().ok_or_else(|| serde::de::Error::custom(format!("{} is out of range", number)))
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// The numbers in a trade message's `data`, by feed name, and whether each
+/// can be negative. A repaired `timestamp` is replaced with the time the
+/// trade arrived once it's parsed.
+pub const TRADE_NUMBERS: [(&str, bool); 4] = [("amount", false), ("totalValue", false), ("price", false), ("timestamp", false)];
+
+/// The numbers in a price update message, which sit at its top level.
+pub const PRICE_NUMBERS: [(&str, bool); 6] = [
+    ("currentPrice", false),
+    ("marketCap", false),
+    ("change24h", true),
+    ("volume24h", false),
+    ("poolCoinAmount", false),
+    ("poolBaseCurrencyAmount", false),
+];
+
+/// Fixes up the `fields` of a feed message before it's parsed: a missing or
+/// null number becomes 0, and so does a negative one where that can't
+/// happen. Returns the names of the fields it changed, or why the message
+/// can't be used when a field is NaN, infinite or not a number at all.
+pub fn repair_numbers(message: &mut Value, fields: &[(&'static str, bool)]) -> Result<Vec<&'static str>, String> {
+    let Some(message) = message.as_object_mut() else {
+        return Err("not an object".to_string());
+    };
+    let mut repaired = Vec::new();
+    for &(field, can_be_negative) in fields {
+        let number = match message.get(field) {
+            None | Some(Value::Null) => None,
+            Some(Value::Number(number)) => number.as_f64(),
+            Some(Value::String(text)) => Some(text.trim().parse::<f64>().map_err(|_| format!("{} is not a number", field))?),
+            Some(_) => return Err(format!("{} is not a number", field)),
+        };
+        match number {
+            Some(number) if !number.is_finite() => return Err(format!("non-finite {}", field)),
+            Some(number) if number >= 0.0 || can_be_negative => continue,
+            _ => {}
+        }
+        message.insert(field.to_string(), Value::from(0));
+        repaired.push(field);
+    }
+    Ok(repaired)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WSMessage {
     #[serde(rename = "type")]
     pub msg_type: String,
     pub data: TradeData,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceWSMessage {
     #[serde(rename = "type")]
     pub msg_type: String,
-    #[serde(rename = "coinSymbol")]
-    pub coin_symbol: String,
-    #[serde(rename = "currentPrice")]
-    pub current_price: f64,
-    #[serde(rename = "marketCap")]
-    pub market_cap: f64,
-    #[serde(rename = "change24h")]
-    pub change_24h: f64,
-    #[serde(rename = "volume24h")]
-    pub volume_24h: f64,
-    #[serde(rename = "poolCoinAmount")]
-    pub pool_coin_amount: f64,
-    #[serde(rename = "poolBaseCurrencyAmount")]
-    pub pool_base_currency_amount: f64,
+    #[serde(flatten)]
+    pub data: PriceUpdateData,
 }
 
-#[derive(Debug, Clone)]
+/// Serialized with snake_case keys and an RFC 3339 `received_at`, so
+/// exported trades can be read back in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub struct Trade {
+    /// Channel the trade first arrived on
     pub msg_type: String,
     pub data: TradeData,
     pub received_at: DateTime<Local>,
+    /// Also (or only) seen on the large trades channel
+    #[serde(default)]
+    pub is_large: bool,
+    /// Arrived with a missing or negative number that was set to 0
+    #[serde(default)]
+    pub repaired: bool,
 }
 
-#[derive(Debug, Clone)]
+/// One row of the trades list: a single trade, or with grouping on a run of
+/// repeated trades by the same trader on the same coin and side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupedTrade {
+    /// The newest trade of the run, which the row shows and selects
+    pub trade: Trade,
+    pub count: usize,
+    pub amount: Decimal,
+    pub total_value: Decimal,
+}
+
+/// Converts a feed timestamp to local time. The feed sends milliseconds,
+/// but plain seconds are accepted too: anything below 10^11 (early 1973 as
+/// milliseconds, the year 5138 as seconds) is taken as seconds.
+pub fn epoch_to_local(timestamp: i64) -> Option<DateTime<Local>> {
+    if timestamp <= 0 {
+        return None;
+    }
+    let utc = if timestamp < 100_000_000_000 {
+        DateTime::from_timestamp(timestamp, 0)
+    } else {
+        DateTime::from_timestamp_millis(timestamp)
+    };
+    utc.map(|time| time.with_timezone(&Local))
+}
+
+/// Text formats trades can be exported in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    /// Tab-separated, for pasting into a spreadsheet
+    Tsv,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "tsv" => Some(ExportFormat::Tsv),
+            _ => None,
+        }
+    }
+
+    /// Header row followed by one row per trade, in the given order.
+    pub fn trades_text(self, trades: &[Trade]) -> String {
+        let mut text = self.join(TRADE_EXPORT_COLUMNS.map(String::from));
+        for trade in trades {
+            text.push('\n');
+            text.push_str(&self.join(trade.export_fields()));
+        }
+        text
+    }
+
+    fn join(self, fields: [String; 8]) -> String {
+        match self {
+            // Tabs and line breaks in names are replaced so they can't shift
+            // the columns
+            ExportFormat::Tsv => fields.map(|field| field.replace(['\t', '\n', '\r'], " ")).join("\t"),
+            ExportFormat::Csv => fields
+                .map(|field| {
+                    if field.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", field.replace('"', "\"\""))
+                    } else {
+                        field
+                    }
+                })
+                .join(","),
+        }
+    }
+}
+
+/// Column names of an exported trade row.
+pub const TRADE_EXPORT_COLUMNS: [&str; 8] = ["time", "type", "coin", "amount", "price", "value", "trader", "large"];
+
+impl Trade {
+    /// How long after its own timestamp the trade reached us. Negative when
+    /// the server's clock runs ahead of ours.
+    pub fn feed_lag(&self) -> Option<chrono::Duration> {
+        Some(self.received_at - epoch_to_local(self.data.timestamp)?)
+    }
+
+    fn export_fields(&self) -> [String; 8] {
+        [
+            self.received_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            self.data.trade_type.clone(),
+            self.data.coin_symbol.clone(),
+            self.data.amount.to_string(),
+            self.data.price.to_string(),
+            self.data.total_value.to_string(),
+            self.data.username.clone(),
+            self.is_large.to_string(),
+        ]
+    }
+
+    /// One-line description suitable for pasting into chat.
+    pub fn summary(&self) -> String {
+        format!(
+            "[{}] {} {:.2} {} @ ${:.8} = ${:.2} by {}",
+            self.received_at.format("%Y-%m-%d %H:%M:%S"),
+            self.data.trade_type,
+            round_to(self.data.amount, 2),
+            self.data.coin_symbol,
+            round_to(self.data.price, 8),
+            round_to(self.data.total_value, 2),
+            self.data.username,
+        )
+    }
+}
+
+impl Trade {
+    /// A BUY of one `coin_symbol` at 1 by `username` on the all-trades
+    /// channel, received now. Tests start from this and change what they
+    /// need.
+    pub fn sample(coin_symbol: &str, username: &str) -> Trade {
+        let received_at = Local::now();
+        Trade {
+            msg_type: "all-trades".to_string(),
+            data: TradeData {
+                trade_type: "BUY".to_string(),
+                username: username.to_string(),
+                user_image: None,
+                amount: Decimal::ONE,
+                coin_symbol: coin_symbol.to_string(),
+                coin_name: coin_symbol.to_string(),
+                coin_icon: None,
+                total_value: Decimal::ONE,
+                price: Decimal::ONE,
+                timestamp: received_at.timestamp_millis(),
+                user_id: "1".to_string(),
+                extra: BTreeMap::new(),
+            },
+            received_at,
+            is_large: false,
+            repaired: false,
+        }
+    }
+
+    /// The same trade reported on another channel: both the all-trades and
+    /// the large-trades feed carry every large trade.
+    pub fn is_duplicate_of(&self, other: &Trade) -> bool {
+        self.data.user_id == other.data.user_id
+            && self.data.coin_symbol == other.data.coin_symbol
+            && self.data.timestamp == other.data.timestamp
+            && self.data.total_value == other.data.total_value
+    }
+}
+
+impl Timestamped for Trade {
+    fn received_at(&self) -> DateTime<Local> {
+        self.received_at
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub struct PriceUpdate {
     pub coin_symbol: String,
-    pub current_price: f64,
-    pub market_cap: f64,
+    pub current_price: Decimal,
+    pub market_cap: Decimal,
     pub change_24h: f64,
-    pub volume_24h: f64,
-    pub pool_coin_amount: f64,
-    pub pool_base_currency_amount: f64,
+    pub volume_24h: Decimal,
+    pub pool_coin_amount: Decimal,
+    pub pool_base_currency_amount: Decimal,
     pub received_at: DateTime<Local>,
+    /// Arrived with a missing or negative number that was set to 0
+    #[serde(default)]
+    pub repaired: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl Timestamped for PriceUpdate {
+    fn received_at(&self) -> DateTime<Local> {
+        self.received_at
+    }
+}
+
+impl PriceUpdate {
+    /// A price of 1 for `coin_symbol` with nothing else reported, received
+    /// now. Tests start from this and change what they need.
+    pub fn sample(coin_symbol: &str) -> PriceUpdate {
+        PriceUpdate {
+            coin_symbol: coin_symbol.to_string(),
+            current_price: Decimal::ONE,
+            market_cap: Decimal::ZERO,
+            change_24h: 0.0,
+            volume_24h: Decimal::ZERO,
+            pool_coin_amount: Decimal::ZERO,
+            pool_base_currency_amount: Decimal::ZERO,
+            received_at: Local::now(),
+            repaired: false,
+        }
+    }
+
+    /// One-line description suitable for pasting into chat.
+    pub fn summary(&self) -> String {
+        format!(
+            "[{}] {} ${:.8} ({:+.2}% 24h), market cap ${:.2}, volume ${:.2}",
+            self.received_at.format("%Y-%m-%d %H:%M:%S"),
+            self.coin_symbol,
+            round_to(self.current_price, 8),
+            self.change_24h,
+            round_to(self.market_cap, 2),
+            round_to(self.volume_24h, 2),
+        )
+    }
+
+    /// Price change since `previous`, absolute and in percent. The percent
+    /// is `None` when the previous price was zero.
+    pub fn delta_from(&self, previous: &PriceUpdate) -> (Decimal, Option<f64>) {
+        let delta = self.current_price - previous.current_price;
+        let percent = (!previous.current_price.is_zero()).then(|| lossy_f64(delta) / lossy_f64(previous.current_price) * 100.0);
+        (delta, percent)
+    }
+
+    /// Same coin and same market data, ignoring when it was received.
+    pub fn is_duplicate_of(&self, other: &PriceUpdate) -> bool {
+        self.coin_symbol == other.coin_symbol
+            && self.current_price == other.current_price
+            && self.market_cap == other.market_cap
+            && self.volume_24h == other.volume_24h
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TradeFilter {
+    #[default]
     All,
     Large,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSort {
+    Newest,
+    Value,
+    Amount,
+    Price,
+}
+
+impl TradeSort {
+    pub fn next(self) -> Self {
+        match self {
+            TradeSort::Newest => TradeSort::Value,
+            TradeSort::Value => TradeSort::Amount,
+            TradeSort::Amount => TradeSort::Price,
+            TradeSort::Price => TradeSort::Newest,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TradeSort::Newest => "Newest",
+            TradeSort::Value => "Value ↓",
+            TradeSort::Amount => "Amount ↓",
+            TradeSort::Price => "Price ↓",
+        }
+    }
+}
+
+/// Column the all-coins price table is sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoinSort {
+    Symbol,
+    Price,
+    Change,
+    Volume,
+    MarketCap,
+    Updated,
+}
+
+impl CoinSort {
+    pub fn next(self) -> Self {
+        match self {
+            CoinSort::Symbol => CoinSort::Price,
+            CoinSort::Price => CoinSort::Change,
+            CoinSort::Change => CoinSort::Volume,
+            CoinSort::Volume => CoinSort::MarketCap,
+            CoinSort::MarketCap => CoinSort::Updated,
+            CoinSort::Updated => CoinSort::Symbol,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CoinSort::Symbol => "Symbol",
+            CoinSort::Price => "Price ↓",
+            CoinSort::Change => "24h ↓",
+            CoinSort::Volume => "Volume ↓",
+            CoinSort::MarketCap => "Market Cap ↓",
+            CoinSort::Updated => "Updated ↓",
+        }
+    }
+}
+
+/// How far back the Price Tracker's history list and chart reach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceRange {
+    FiveMinutes,
+    ThirtyMinutes,
+    TwoHours,
+    All,
+}
+
+impl PriceRange {
+    pub fn duration(self) -> Option<chrono::Duration> {
+        match self {
+            PriceRange::FiveMinutes => Some(chrono::Duration::minutes(5)),
+            PriceRange::ThirtyMinutes => Some(chrono::Duration::minutes(30)),
+            PriceRange::TwoHours => Some(chrono::Duration::hours(2)),
+            PriceRange::All => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PriceRange::FiveMinutes => "5m",
+            PriceRange::ThirtyMinutes => "30m",
+            PriceRange::TwoHours => "2h",
+            PriceRange::All => "all",
+        }
+    }
+}
+
+/// What the tracked coin's change figure, and its color, is measured
+/// against.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorBasis {
+    /// The feed's own 24h change
+    Change24h,
+    /// The first price seen for the coin since the app started
+    Session,
+}
+
+impl ColorBasis {
+    pub fn next(self) -> Self {
+        match self {
+            ColorBasis::Change24h => ColorBasis::Session,
+            ColorBasis::Session => ColorBasis::Change24h,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorBasis::Change24h => "24h",
+            ColorBasis::Session => "Session",
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum InputMode {
     Normal,
     CoinFilter,
     TraderFilter,
     CoinSelection,
+    CoinList,
+    MuteList,
+    FollowList,
+    BookmarkList,
+    EventLog,
+    OrderEntry,
+    ConfirmClear,
+    ConfirmQuit,
+    Help,
+    CoinStats,
+    PositionEntry,
+    ConfirmLinkFilter,
+    Command,
+    Search,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// What a numeric prompt lets through as it's typed: digits, one decimal
+/// point per number and, when signed, a leading `+` or `-`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericInput {
+    /// Space-separated numbers the prompt takes
+    pub values: usize,
+    pub signed: bool,
+}
+
+impl NumericInput {
+    /// Whether typing `c` after `typed` keeps the input well-formed.
+    pub fn accepts(&self, typed: &str, c: char) -> bool {
+        let current = typed.rsplit(' ').next().unwrap_or_default();
+        match c {
+            '0'..='9' => true,
+            '.' => !current.contains('.'),
+            '+' | '-' => self.signed && current.is_empty(),
+            // Starts the next number once this one has a digit
+            ' ' => current.chars().any(|c| c.is_ascii_digit()) && typed.split(' ').count() < self.values,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MuteKind {
+    Coin,
+    Trader,
+}
+
+/// What a confirmed clear empties.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClearScope {
+    /// The buffer behind the current page
+    Page,
+    /// Both buffers and everything derived from them
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AppPage {
     Trades,
     PriceTracker,
-}
\ No newline at end of file
+    Portfolio,
+}
+
+/// Buy and sell value traded during one minute.
+#[derive(Debug, Clone)]
+pub struct FlowBucket {
+    pub minute: DateTime<Local>,
+    pub buy_value: f64,
+    pub sell_value: f64,
+    pub trade_count: usize,
+}
+
+impl FlowBucket {
+    pub fn net(&self) -> f64 {
+        self.buy_value - self.sell_value
+    }
+}
+
+/// A simulated holding, used only to show what it would be worth at the live
+/// price. Nothing is ever traded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub entry_price: Decimal,
+    pub quantity: Decimal,
+}
+
+impl Position {
+    /// Parses "<entry price> <quantity>", e.g. "0.0042 1000". Both must be
+    /// positive numbers.
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut parts = input.split_whitespace();
+        let entry_price: Decimal = parts.next()?.parse().ok()?;
+        let quantity: Decimal = parts.next()?.parse().ok()?;
+        let valid = |value: Decimal| value > Decimal::ZERO;
+        if parts.next().is_some() || !valid(entry_price) || !valid(quantity) {
+            return None;
+        }
+        Some(Self { entry_price, quantity })
+    }
+
+    pub fn cost(&self) -> Decimal {
+        self.entry_price.saturating_mul(self.quantity)
+    }
+
+    /// Unrealized profit or loss at `current_price`.
+    pub fn pnl(&self, current_price: Decimal) -> Decimal {
+        (current_price - self.entry_price).saturating_mul(self.quantity)
+    }
+
+    pub fn pnl_percent(&self, current_price: Decimal) -> f64 {
+        (lossy_f64(current_price) / lossy_f64(self.entry_price) - 1.0) * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(text: &str) -> Decimal {
+        Decimal::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn numeric_input_allows_one_point_per_number_and_leading_signs() {
+        let single = NumericInput { values: 1, signed: false };
+        assert!(single.accepts("", '2'));
+        assert!(single.accepts("2", '.'));
+        assert!(!single.accepts("2.5", '.'));
+        assert!(!single.accepts("", '-'));
+        assert!(!single.accepts("2", ' '));
+        assert!(!single.accepts("2", 'x'));
+
+        let signed = NumericInput { values: 1, signed: true };
+        assert!(signed.accepts("", '-'));
+        assert!(!signed.accepts("-", '+'));
+        assert!(!signed.accepts("1", '-'));
+
+        let pair = NumericInput { values: 2, signed: false };
+        assert!(!pair.accepts("", ' '));
+        assert!(!pair.accepts(".", ' '));
+        assert!(pair.accepts("0.5", ' '));
+        assert!(pair.accepts("0.5 ", '.'));
+        assert!(!pair.accepts("0.5 10", ' '));
+    }
+
+    fn sample_trade() -> Trade {
+        let mut trade = Trade::sample("PEPE", "alice");
+        trade.data = TradeData {
+            trade_type: "SELL".to_string(),
+            user_image: Some("avatars/1.png".to_string()),
+            amount: dec("1234.5"),
+            coin_name: "Pepe".to_string(),
+            coin_icon: Some("coins/pepe.png".to_string()),
+            total_value: dec("42.25"),
+            price: dec("0.00001234"),
+            timestamp: 1_700_000_000_000,
+            user_id: "17".to_string(),
+            ..trade.data
+        };
+        trade
+    }
+
+    #[test]
+    fn epoch_timestamps_in_seconds_or_milliseconds_give_the_same_time() {
+        let from_millis = epoch_to_local(1_700_000_000_000).unwrap();
+        assert_eq!(epoch_to_local(1_700_000_000), Some(from_millis));
+        assert_eq!(epoch_to_local(1_700_000_000_250).unwrap() - from_millis, chrono::Duration::milliseconds(250));
+        assert_eq!(epoch_to_local(0), None);
+    }
+
+    #[test]
+    fn feed_lag_is_the_gap_to_the_trade_timestamp() {
+        let mut trade = sample_trade();
+        trade.received_at = epoch_to_local(1_700_000_001_500).unwrap();
+        assert_eq!(trade.feed_lag(), Some(chrono::Duration::milliseconds(1500)));
+        trade.data.timestamp = 0;
+        assert_eq!(trade.feed_lag(), None);
+    }
+
+    #[test]
+    fn tsv_export_keeps_names_in_their_columns() {
+        let mut trade = sample_trade();
+        trade.data.username = "al\tice".to_string();
+        let text = ExportFormat::Tsv.trades_text(&[trade]);
+        let (header, row) = text.split_once('\n').unwrap();
+        assert_eq!(header, TRADE_EXPORT_COLUMNS.join("\t"));
+        let fields: Vec<&str> = row.split('\t').collect();
+        assert_eq!(&fields[1..], ["SELL", "PEPE", "1234.5", "0.00001234", "42.25", "al ice", "false"]);
+    }
+
+    #[test]
+    fn csv_export_quotes_fields_that_need_it() {
+        let mut trade = sample_trade();
+        trade.data.username = "smith, \"al\"".to_string();
+        let text = ExportFormat::Csv.trades_text(&[trade]);
+        let row = text.lines().nth(1).unwrap();
+        assert!(row.ends_with(",SELL,PEPE,1234.5,0.00001234,42.25,\"smith, \"\"al\"\"\",false"));
+    }
+
+    #[test]
+    fn trade_round_trips_through_json() {
+        let trade = sample_trade();
+        let json = serde_json::to_string(&trade).unwrap();
+        let restored: Trade = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.msg_type, trade.msg_type);
+        assert_eq!(restored.received_at, trade.received_at);
+        assert_eq!(restored.data.username, trade.data.username);
+        assert_eq!(restored.data.price, trade.data.price);
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+    }
+
+    #[test]
+    fn received_at_is_serialized_as_rfc3339() {
+        let value = serde_json::to_value(sample_trade()).unwrap();
+        let received_at = value["received_at"].as_str().unwrap();
+        assert!(DateTime::parse_from_rfc3339(received_at).is_ok());
+        assert!(value.get("msg_type").is_some());
+    }
+
+    #[test]
+    fn price_update_round_trips_through_json() {
+        let update = PriceUpdate {
+            current_price: dec("0.5"),
+            market_cap: dec("1000"),
+            change_24h: -3.5,
+            volume_24h: dec("250"),
+            pool_coin_amount: dec("10"),
+            pool_base_currency_amount: dec("5"),
+            ..PriceUpdate::sample("PEPE")
+        };
+        let json = serde_json::to_string(&update).unwrap();
+        let restored: PriceUpdate = serde_json::from_str(&json).unwrap();
+        assert!(restored.is_duplicate_of(&update));
+        assert_eq!(restored.received_at, update.received_at);
+        // Still plain JSON numbers for anything reading the export or the API
+        assert!(json.contains(r#""current_price":0.5"#), "{}", json);
+    }
+
+    #[test]
+    fn feed_amounts_parse_exactly() {
+        let json = |amount: &str| {
+            format!(
+                r#"{{"type":"BUY","username":"alice","userImage":"","amount":{},"coinSymbol":"PEPE","coinName":"Pepe",
+                "coinIcon":"","totalValue":0.1,"price":4.31e-6,"timestamp":0,"userId":"1"}}"#,
+                amount
+            )
+        };
+        let data: TradeData = serde_json::from_str(&json("1.5e20")).unwrap();
+        assert_eq!(data.price, dec("0.00000431"));
+        assert_eq!(data.total_value, dec("0.1"));
+        assert_eq!(data.amount, dec("150000000000000000000"));
+        // Past what a Decimal holds, the message is dropped as unparseable
+        assert!(serde_json::from_str::<TradeData>(&json("1e30")).is_err());
+    }
+
+    #[test]
+    fn prices_keep_digits_an_f64_would_lose() {
+        const EXACT: &str = "0.1000000000000000055511";
+        let json = format!(
+            r#"{{"type":"price_update","coinSymbol":"PEPE","currentPrice":{},"marketCap":1000,
+            "change24h":-3.5,"volume24h":250,"poolCoinAmount":10,"poolBaseCurrencyAmount":5}}"#,
+            EXACT
+        );
+        // The feed path goes through a `Value` first
+        let value: Value = serde_json::from_str(&json).unwrap();
+        let message: PriceWSMessage = serde_json::from_value(value).unwrap();
+        assert_eq!(message.data.current_price, dec(EXACT));
+        assert_ne!(dec(&lossy_f64(dec(EXACT)).to_string()), dec(EXACT));
+
+        // And back out the same way
+        let serialized = serde_json::to_string(&message.data).unwrap();
+        assert!(serialized.contains(&format!(r#""currentPrice":{}"#, EXACT)), "{}", serialized);
+    }
+
+    #[test]
+    fn position_parses_price_and_quantity_and_values_pnl() {
+        let position = Position::parse(" 0.5  200 ").unwrap();
+        assert_eq!(position, Position { entry_price: dec("0.5"), quantity: dec("200") });
+        assert_eq!(position.cost(), dec("100"));
+        assert_eq!(position.pnl(dec("0.75")), dec("50"));
+        assert_eq!(position.pnl_percent(dec("0.75")), 50.0);
+        assert_eq!(position.pnl(dec("0.25")), dec("-50"));
+        assert_eq!(position.pnl_percent(dec("0.25")), -50.0);
+
+        for invalid in ["", "0.5", "0.5 200 1", "abc 200", "0 200", "0.5 -1", "NaN 1", "inf 1"] {
+            assert_eq!(Position::parse(invalid), None, "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn price_delta_is_relative_to_the_previous_update() {
+        let at = |price: &str| PriceUpdate {
+            current_price: dec(price),
+            ..PriceUpdate::sample("PEPE")
+        };
+        assert_eq!(at("1.5").delta_from(&at("1")), (dec("0.5"), Some(50.0)));
+        assert_eq!(at("0.5").delta_from(&at("1")), (dec("-0.5"), Some(-50.0)));
+        assert_eq!(at("1").delta_from(&at("1")), (Decimal::ZERO, Some(0.0)));
+        assert_eq!(at("1").delta_from(&at("0")), (dec("1"), None));
+        assert_eq!(at("0.3").delta_from(&at("0.1")).0, dec("0.2"));
+    }
+
+    #[test]
+    fn missing_and_negative_numbers_are_repaired_and_garbage_is_rejected() {
+        let trade = serde_json::json!({
+            "type": "BUY", "username": "alice", "userImage": "", "amount": 2, "coinSymbol": "PEPE", "coinName": "Pepe",
+            "coinIcon": "", "totalValue": 0.5, "price": 0.25, "timestamp": 1_700_000_000_000i64, "userId": "1"
+        });
+        let price = serde_json::json!({
+            "type": "price_update", "coinSymbol": "PEPE", "currentPrice": 0.5, "marketCap": 1000, "change24h": -3.5,
+            "volume24h": 250, "poolCoinAmount": 10, "poolBaseCurrencyAmount": 5
+        });
+        let parses = |message: &Value| match message.get("coinName") {
+            Some(_) => serde_json::from_value::<TradeData>(message.clone()).is_ok(),
+            None => serde_json::from_value::<PriceWSMessage>(message.clone()).is_ok(),
+        };
+
+        for (message, fields) in [(&trade, &TRADE_NUMBERS[..]), (&price, &PRICE_NUMBERS[..])] {
+            let mut untouched = message.clone();
+            assert_eq!(repair_numbers(&mut untouched, fields), Ok(Vec::new()));
+            assert_eq!(&untouched, message);
+
+            for &(field, can_be_negative) in fields {
+                let with = |value: Value| {
+                    let mut message = message.clone();
+                    message[field] = value;
+                    message
+                };
+                let mut missing = message.clone();
+                missing.as_object_mut().unwrap().remove(field);
+                for mut broken in [missing, with(Value::Null)] {
+                    assert_eq!(repair_numbers(&mut broken, fields), Ok(vec![field]));
+                    assert_eq!(broken[field], 0, "{}", field);
+                    assert!(parses(&broken), "{}", field);
+                }
+
+                let mut negative = with(Value::from(-1));
+                let repaired = repair_numbers(&mut negative, fields).unwrap();
+                assert_eq!(repaired.is_empty(), can_be_negative, "{}", field);
+                assert_eq!(negative[field], if can_be_negative { -1 } else { 0 }, "{}", field);
+                let mut negative_text = with(Value::from("-2"));
+                assert_eq!(repair_numbers(&mut negative_text, fields).unwrap().is_empty(), can_be_negative, "{}", field);
+
+                for garbage in ["NaN", "Infinity", "-inf"] {
+                    let error = repair_numbers(&mut with(Value::from(garbage)), fields).unwrap_err();
+                    assert_eq!(error, format!("non-finite {}", field));
+                }
+                for garbage in [Value::from("lots"), Value::from(true), serde_json::json!([1])] {
+                    let error = repair_numbers(&mut with(garbage), fields).unwrap_err();
+                    assert_eq!(error, format!("{} is not a number", field));
+                }
+            }
+        }
+        assert!(repair_numbers(&mut Value::from(1), &PRICE_NUMBERS).is_err());
+    }
+
+    #[test]
+    fn price_message_fields_flatten_into_price_update_data() {
+        let json = r#"{"type":"price_update","coinSymbol":"PEPE","currentPrice":0.5,"marketCap":1000,
+            "change24h":-3.5,"volume24h":250,"poolCoinAmount":10,"poolBaseCurrencyAmount":5}"#;
+        let message: PriceWSMessage = serde_json::from_str(json).unwrap();
+        assert_eq!(message.msg_type, "price_update");
+        assert_eq!(message.data.coin_symbol, "PEPE");
+        assert_eq!(message.data.change_24h, -3.5);
+    }
+
+    #[test]
+    fn cosmetic_trade_fields_can_be_missing_and_new_ones_are_kept() {
+        let payload = serde_json::json!({
+            "type": "BUY", "username": "alice", "userImage": "avatars/1.png", "amount": 2, "coinSymbol": "PEPE",
+            "coinName": "Pepe", "coinIcon": "coins/pepe.png", "totalValue": 0.5, "price": 0.25, "timestamp": 1, "userId": "1"
+        });
+        for optional in ["userImage", "coinName", "coinIcon", "userId"] {
+            let mut missing = payload.clone();
+            missing.as_object_mut().unwrap().remove(optional);
+            let data: TradeData = serde_json::from_value(missing).unwrap_or_else(|e| panic!("without {}: {}", optional, e));
+            assert!(data.extra.is_empty(), "{}", optional);
+        }
+        let mut bare = payload.clone();
+        for optional in ["userImage", "coinName", "coinIcon", "userId"] {
+            bare.as_object_mut().unwrap().remove(optional);
+        }
+        let data: TradeData = serde_json::from_value(bare).unwrap();
+        assert_eq!((data.user_image, data.coin_icon), (None, None));
+        assert_eq!((data.coin_name.as_str(), data.user_id.as_str()), ("", ""));
+        let mut null_images = payload.clone();
+        null_images["userImage"] = Value::Null;
+        null_images["coinIcon"] = Value::Null;
+        assert!(serde_json::from_value::<TradeData>(null_images).is_ok());
+
+        for required in ["type", "username", "coinSymbol"] {
+            let mut missing = payload.clone();
+            missing.as_object_mut().unwrap().remove(required);
+            assert!(serde_json::from_value::<TradeData>(missing).is_err(), "{}", required);
+        }
+
+        let mut extended = payload.clone();
+        extended["verified"] = Value::from(true);
+        extended["badge"] = serde_json::json!({ "tier": 2 });
+        let data: TradeData = serde_json::from_value(extended.clone()).unwrap();
+        assert_eq!(data.extra.len(), 2);
+        assert_eq!(data.extra["badge"]["tier"], 2);
+        assert_eq!(data.coin_name, "Pepe");
+        // Exports and the API pass them on
+        assert_eq!(serde_json::to_value(&data).unwrap()["verified"], true);
+    }
+
+    #[test]
+    fn placeholder_or_missing_images_are_not_avatars() {
+        let placeholders = vec!["default".to_string()];
+        let mut trade = sample_trade();
+        assert!(trade.data.has_avatar(&placeholders));
+        trade.data.user_image = Some("avatars/Default-User.png".to_string());
+        assert!(!trade.data.has_avatar(&placeholders));
+        assert!(trade.data.has_avatar(&[]));
+        trade.data.user_image = Some("  ".to_string());
+        assert!(!trade.data.has_avatar(&[]));
+        trade.data.user_image = None;
+        assert!(!trade.data.has_avatar(&[]));
+    }
+}
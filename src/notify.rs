@@ -0,0 +1,208 @@
+//! Desktop notifications for alerts, sent alongside the in-app banner when
+//! the app runs with `--desktop-notifications`. In the default immediate
+//! mode each alert rule notifies at most once per cooldown so a busy coin
+//! can't flood the desktop; in digest mode alerts are collected and sent as
+//! one summary per interval instead.
+//!
+//! Only builds with the `desktop-notifications` feature on Linux or macOS
+//! can actually show anything; elsewhere sending quietly does nothing.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+
+/// Shortest digest interval, so a zero in the config can't spin.
+const MIN_DIGEST_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationMode {
+    /// One notification per alert, rate limited per rule
+    #[default]
+    Immediate,
+    /// One summary of every alert raised during each interval
+    Digest,
+}
+
+/// An alert as it fired, before it's turned into a notification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggeredAlert {
+    pub rule: String,
+    pub coin: String,
+    pub value: f64,
+    pub message: String,
+}
+
+impl TriggeredAlert {
+    /// Summary and body of a notification for just this alert.
+    fn notification(&self) -> (String, String) {
+        (format!("{}: {}", self.rule, self.coin), format!("{}\nValue: ${:.2}", self.message, self.value))
+    }
+}
+
+#[derive(Debug)]
+pub struct DesktopNotifier {
+    cooldown: Duration,
+    /// When each rule last sent a notification
+    last_sent: HashMap<String, Instant>,
+    /// The digest task, in digest mode
+    digest: Option<mpsc::UnboundedSender<TriggeredAlert>>,
+}
+
+impl DesktopNotifier {
+    pub fn new(cooldown: Duration) -> Self {
+        Self { cooldown, last_sent: HashMap::new(), digest: None }
+    }
+
+    /// A notifier that hands every alert to a task sending one digest per
+    /// `interval`. Must be called from within the tokio runtime.
+    pub fn with_digest(interval: Duration) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(send_digests(interval.max(MIN_DIGEST_INTERVAL), rx));
+        Self { digest: Some(tx), ..Self::new(Duration::ZERO) }
+    }
+
+    /// Whether this build can show desktop notifications at all.
+    pub fn is_supported() -> bool {
+        cfg!(all(feature = "desktop-notifications", any(target_os = "linux", target_os = "macos")))
+    }
+
+    /// Records a notification for `rule` at `now` unless one was sent within
+    /// the cooldown.
+    pub fn should_send(&mut self, rule: &str, now: Instant) -> bool {
+        if let Some(last) = self.last_sent.get(rule) {
+            if now.duration_since(*last) < self.cooldown {
+                return false;
+            }
+        }
+        self.last_sent.insert(rule.to_string(), now);
+        true
+    }
+
+    /// Shows a notification titled with the rule and coin, rate limited per
+    /// rule, or queues the alert for the next digest. A missing or failing
+    /// notification daemon is ignored; the banner still shows the alert.
+    pub fn notify(&mut self, alert: TriggeredAlert) {
+        if let Some(digest) = &self.digest {
+            let _ = digest.send(alert);
+            return;
+        }
+        if !self.should_send(&alert.rule, Instant::now()) {
+            return;
+        }
+        let (summary, body) = alert.notification();
+        send_in_background(summary, body);
+    }
+}
+
+/// Alerts waiting for the next digest.
+#[derive(Debug, Default)]
+pub struct AlertAggregator {
+    pending: Vec<TriggeredAlert>,
+}
+
+impl AlertAggregator {
+    pub fn push(&mut self, alert: TriggeredAlert) {
+        self.pending.push(alert);
+    }
+
+    /// Summary and body of one notification covering every alert since the
+    /// last flush, or `None` when there were none. A lone alert reads as it
+    /// would have in immediate mode.
+    pub fn flush(&mut self) -> Option<(String, String)> {
+        let alerts = std::mem::take(&mut self.pending);
+        if alerts.len() == 1 {
+            return Some(alerts[0].notification());
+        }
+        let biggest = alerts.iter().max_by(|a, b| a.value.total_cmp(&b.value))?;
+
+        // Rules in the order they first fired
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for alert in &alerts {
+            match counts.iter_mut().find(|(rule, _)| *rule == alert.rule) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((&alert.rule, 1)),
+            }
+        }
+        let breakdown: Vec<String> = counts.iter().map(|(rule, count)| format!("{} × {}", count, rule)).collect();
+        Some((
+            format!("{} alerts", alerts.len()),
+            format!("{}\nBiggest: ${:.2} {} - {}", breakdown.join(", "), biggest.value, biggest.coin, biggest.message),
+        ))
+    }
+}
+
+/// Collects alerts from `rx` and sends whatever arrived every `interval`,
+/// until the notifier is dropped.
+async fn send_digests(interval: Duration, mut rx: mpsc::UnboundedReceiver<TriggeredAlert>) {
+    let mut aggregator = AlertAggregator::default();
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            alert = rx.recv() => match alert {
+                Some(alert) => aggregator.push(alert),
+                None => return,
+            },
+            _ = ticker.tick() => {
+                if let Some((summary, body)) = aggregator.flush() {
+                    send_in_background(summary, body);
+                }
+            }
+        }
+    }
+}
+
+/// The daemon can take a while to answer, so nothing waits on it.
+fn send_in_background(summary: String, body: String) {
+    std::thread::spawn(move || show(&summary, &body));
+}
+
+#[cfg(all(feature = "desktop-notifications", any(target_os = "linux", target_os = "macos")))]
+fn show(summary: &str, body: &str) {
+    let _ = notify_rust::Notification::new().appname("rug-listener").summary(summary).body(body).show();
+}
+
+#[cfg(not(all(feature = "desktop-notifications", any(target_os = "linux", target_os = "macos"))))]
+fn show(_summary: &str, _body: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_rule_is_rate_limited_separately() {
+        let mut notifier = DesktopNotifier::new(Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(notifier.should_send("Volume spike", start));
+        assert!(!notifier.should_send("Volume spike", start + Duration::from_secs(59)));
+        assert!(notifier.should_send("Followed trader", start + Duration::from_secs(59)));
+        assert!(notifier.should_send("Volume spike", start + Duration::from_secs(60)));
+    }
+
+    fn alert(rule: &str, coin: &str, value: f64) -> TriggeredAlert {
+        TriggeredAlert { rule: rule.to_string(), coin: coin.to_string(), value, message: format!("{} on {}", rule, coin) }
+    }
+
+    #[test]
+    fn digest_summarises_the_burst_and_names_the_biggest() {
+        let mut aggregator = AlertAggregator::default();
+        assert_eq!(aggregator.flush(), None);
+
+        aggregator.push(alert("Followed trader", "PEPE", 12000.0));
+        assert_eq!(
+            aggregator.flush(),
+            Some(("Followed trader: PEPE".to_string(), "Followed trader on PEPE\nValue: $12000.00".to_string()))
+        );
+
+        aggregator.push(alert("Volume spike", "DOGE", 300.0));
+        aggregator.push(alert("Followed trader", "PEPE", 12000.0));
+        aggregator.push(alert("Volume spike", "BONK", 50.0));
+        let (summary, body) = aggregator.flush().unwrap();
+        assert_eq!(summary, "3 alerts");
+        assert_eq!(body, "2 × Volume spike, 1 × Followed trader\nBiggest: $12000.00 PEPE - Followed trader on PEPE");
+        assert_eq!(aggregator.flush(), None);
+    }
+}
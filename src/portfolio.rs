@@ -0,0 +1,276 @@
+//! Paper trading against the streamed prices. Orders fill instantly at the
+//! latest price the app has seen; nothing is ever sent anywhere.
+
+use crate::models::round_to;
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PORTFOLIO_FILE_NAME: &str = "portfolio.json";
+/// Sells within this fraction of the whole holding close it, so rounding
+/// in the quantity doesn't leave dust or refuse the sale.
+const DUST_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 9); // 1e-9
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
+        }
+    }
+
+    pub fn toggled(&self) -> Self {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
+/// A filled paper order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperOrder {
+    pub at: DateTime<Local>,
+    pub coin: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    /// Base currency spent or received
+    pub value: Decimal,
+    /// Profit or loss locked in by a sell; 0 for buys
+    pub realized_pnl: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Holding {
+    pub quantity: Decimal,
+    pub average_entry: Decimal,
+}
+
+impl Holding {
+    pub fn cost(&self) -> Decimal {
+        self.quantity.saturating_mul(self.average_entry)
+    }
+
+    pub fn unrealized_pnl(&self, price: Decimal) -> Decimal {
+        (price - self.average_entry).saturating_mul(self.quantity)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Portfolio {
+    /// Open positions by uppercase coin symbol
+    pub holdings: BTreeMap<String, Holding>,
+    pub realized_pnl: Decimal,
+    /// Every fill, newest first
+    pub orders: Vec<PaperOrder>,
+}
+
+impl PaperOrder {
+    /// One-line description suitable for pasting into chat.
+    pub fn summary(&self) -> String {
+        format!(
+            "[{}] paper {} {:.4} {} @ ${:.8} = ${:.2}",
+            self.at.format("%Y-%m-%d %H:%M:%S"),
+            self.side.label(),
+            round_to(self.quantity, 4),
+            self.coin,
+            round_to(self.price, 8),
+            round_to(self.value, 2),
+        )
+    }
+}
+
+/// Totals over every open position.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PortfolioSummary {
+    pub market_value: Decimal,
+    pub cost: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub realized_pnl: Decimal,
+    /// Holdings without a streamed price, counted at cost
+    pub unpriced: usize,
+}
+
+impl PortfolioSummary {
+    pub fn total_pnl(&self) -> Decimal {
+        self.unrealized_pnl.saturating_add(self.realized_pnl)
+    }
+}
+
+impl Portfolio {
+    /// Marks every holding to `price_of`, which returns the latest price of
+    /// an uppercase symbol.
+    pub fn summary(&self, price_of: impl Fn(&str) -> Option<Decimal>) -> PortfolioSummary {
+        let mut summary = PortfolioSummary { realized_pnl: self.realized_pnl, ..PortfolioSummary::default() };
+        for (coin, holding) in &self.holdings {
+            summary.cost = summary.cost.saturating_add(holding.cost());
+            match price_of(coin) {
+                Some(price) => {
+                    summary.market_value = summary.market_value.saturating_add(holding.quantity.saturating_mul(price));
+                    summary.unrealized_pnl = summary.unrealized_pnl.saturating_add(holding.unrealized_pnl(price));
+                }
+                None => {
+                    summary.market_value = summary.market_value.saturating_add(holding.cost());
+                    summary.unpriced += 1;
+                }
+            }
+        }
+        summary
+    }
+
+    /// Fills an order for `amount` of base currency at `price`. A sell can't
+    /// exceed the holding.
+    pub fn execute(&mut self, coin: &str, side: Side, amount: Decimal, price: Decimal, at: DateTime<Local>) -> Result<PaperOrder, String> {
+        if amount <= Decimal::ZERO {
+            return Err("The amount must be a positive number".to_string());
+        }
+        if price <= Decimal::ZERO {
+            return Err(format!("No usable price for {}", coin));
+        }
+        let too_large = || "The amount is too large to track".to_string();
+        let coin = coin.to_uppercase();
+        let mut quantity = amount.checked_div(price).ok_or_else(too_large)?;
+        let mut realized_pnl = Decimal::ZERO;
+
+        match side {
+            Side::Buy => {
+                let holding = self.holdings.get(&coin).copied().unwrap_or(Holding { quantity: Decimal::ZERO, average_entry: Decimal::ZERO });
+                let total = holding.quantity.checked_add(quantity).ok_or_else(too_large)?;
+                let cost = holding.cost().checked_add(amount).ok_or_else(too_large)?;
+                let average_entry = cost.checked_div(total).ok_or_else(too_large)?;
+                self.holdings.insert(coin.clone(), Holding { quantity: total, average_entry });
+            }
+            Side::Sell => {
+                let Some(holding) = self.holdings.get_mut(&coin) else {
+                    return Err(format!("No {} to sell", coin));
+                };
+                if quantity > holding.quantity.saturating_mul(Decimal::ONE + DUST_TOLERANCE) {
+                    return Err(format!(
+                        "Only {:.4} {} held (${:.2} at this price)",
+                        round_to(holding.quantity, 4),
+                        coin,
+                        round_to(holding.quantity.saturating_mul(price), 2)
+                    ));
+                }
+                let held = holding.quantity;
+                quantity = quantity.min(held);
+                realized_pnl = (price - holding.average_entry).saturating_mul(quantity);
+                holding.quantity -= quantity;
+                if holding.quantity <= held * DUST_TOLERANCE {
+                    self.holdings.remove(&coin);
+                }
+                self.realized_pnl = self.realized_pnl.saturating_add(realized_pnl);
+            }
+        }
+
+        let order = PaperOrder { at, coin, side, price, quantity, value: quantity.saturating_mul(price), realized_pnl };
+        self.orders.insert(0, order.clone());
+        Ok(order)
+    }
+
+    /// Returns an empty portfolio when there's no file yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// `portfolio.json` next to the saved UI state.
+pub fn default_path() -> Option<PathBuf> {
+    Some(crate::state::data_dir()?.join(PORTFOLIO_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: f64) -> Decimal {
+        Decimal::try_from(value).unwrap()
+    }
+
+    #[test]
+    fn buys_average_the_entry_and_sells_realize_against_it() {
+        let mut portfolio = Portfolio::default();
+        let now = Local::now();
+        portfolio.execute("pepe", Side::Buy, dec(100.0), dec(1.0), now).unwrap();
+        portfolio.execute("PEPE", Side::Buy, dec(100.0), dec(2.0), now).unwrap();
+        let holding = portfolio.holdings["PEPE"];
+        assert_eq!(holding.quantity, dec(150.0));
+        assert_eq!(holding.average_entry, dec(200.0) / dec(150.0));
+
+        let sell = portfolio.execute("PEPE", Side::Sell, dec(150.0), dec(3.0), now).unwrap();
+        assert_eq!(sell.quantity, dec(50.0));
+        assert_eq!(sell.realized_pnl, (dec(3.0) - dec(200.0) / dec(150.0)) * dec(50.0));
+        assert_eq!(portfolio.holdings["PEPE"].quantity, dec(100.0));
+        assert_eq!(portfolio.orders.len(), 3);
+        assert_eq!(portfolio.orders[0].side, Side::Sell);
+
+        // Selling the rest closes the position
+        portfolio.execute("PEPE", Side::Sell, dec(300.0), dec(3.0), now).unwrap();
+        assert!(portfolio.holdings.is_empty());
+    }
+
+    #[test]
+    fn summary_marks_holdings_to_the_latest_price() {
+        let mut portfolio = Portfolio::default();
+        let now = Local::now();
+        portfolio.execute("PEPE", Side::Buy, dec(100.0), dec(1.0), now).unwrap();
+        portfolio.execute("DOGE", Side::Buy, dec(50.0), dec(1.0), now).unwrap();
+        portfolio.execute("DOGE", Side::Sell, dec(10.0), dec(2.0), now).unwrap();
+
+        let summary = portfolio.summary(|coin| (coin == "PEPE").then_some(dec(1.5)));
+        assert_eq!(summary.unpriced, 1);
+        assert_eq!(summary.unrealized_pnl, dec(50.0));
+        assert_eq!(summary.realized_pnl, dec(5.0));
+        assert_eq!(summary.market_value, dec(150.0 + 45.0));
+        assert_eq!(summary.total_pnl(), dec(55.0));
+    }
+
+    #[test]
+    fn oversized_or_invalid_orders_are_refused() {
+        let mut portfolio = Portfolio::default();
+        let now = Local::now();
+        assert!(portfolio.execute("PEPE", Side::Sell, dec(10.0), dec(1.0), now).is_err());
+        portfolio.execute("PEPE", Side::Buy, dec(10.0), dec(1.0), now).unwrap();
+        assert!(portfolio.execute("PEPE", Side::Sell, dec(11.0), dec(1.0), now).is_err());
+        assert!(portfolio.execute("PEPE", Side::Buy, dec(-1.0), dec(1.0), now).is_err());
+        assert!(portfolio.execute("PEPE", Side::Buy, dec(1.0), dec(0.0), now).is_err());
+        assert_eq!(portfolio.orders.len(), 1);
+    }
+
+    #[test]
+    fn portfolio_round_trips_through_a_file() {
+        let path = std::env::temp_dir().join(format!("rug-listener-portfolio-{}.json", std::process::id()));
+        let mut portfolio = Portfolio::default();
+        portfolio.execute("PEPE", Side::Buy, dec(10.0), dec(0.5), Local::now()).unwrap();
+        portfolio.save(&path).unwrap();
+        let loaded = Portfolio::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, portfolio);
+        assert_eq!(Portfolio::load(&path).unwrap(), Portfolio::default());
+    }
+}
@@ -0,0 +1,187 @@
+//! The `trade_row_template` config setting: a line such as
+//! `"{time} {type} {coin} {value} {user}"` drawn for every trade in place of
+//! the default multi-line entry. Placeholders are checked when the config is
+//! loaded, so a typo is reported at startup rather than drawn.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A trade detail a placeholder stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeField {
+    Time,
+    Age,
+    Type,
+    Coin,
+    Name,
+    Amount,
+    Price,
+    Value,
+    User,
+    Count,
+}
+
+impl TradeField {
+    pub const ALL: [TradeField; 10] = [
+        TradeField::Time,
+        TradeField::Age,
+        TradeField::Type,
+        TradeField::Coin,
+        TradeField::Name,
+        TradeField::Amount,
+        TradeField::Price,
+        TradeField::Value,
+        TradeField::User,
+        TradeField::Count,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TradeField::Time => "time",
+            TradeField::Age => "age",
+            TradeField::Type => "type",
+            TradeField::Coin => "coin",
+            TradeField::Name => "name",
+            TradeField::Amount => "amount",
+            TradeField::Price => "price",
+            TradeField::Value => "value",
+            TradeField::User => "user",
+            TradeField::Count => "count",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    Literal(String),
+    Field(TradeField),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    UnknownPlaceholder(String),
+    Unclosed(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnknownPlaceholder(name) => write!(f, "unknown placeholder {{{}}} in trade_row_template", name)?,
+            TemplateError::Unclosed(rest) => write!(f, "unclosed placeholder \"{}\" in trade_row_template", rest)?,
+        }
+        let names: Vec<String> = TradeField::ALL.iter().map(|field| format!("{{{}}}", field.name())).collect();
+        write!(f, " (use {}, or {{{{ and }}}} for literal braces)", names.join(", "))
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// A parsed row template; it's stored in the config as the text it was
+/// parsed from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RowTemplate {
+    source: String,
+    parts: Vec<TemplatePart>,
+}
+
+impl RowTemplate {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|&(_, next)| next) == Some('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek().map(|&(_, next)| next) == Some('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = &source[start + 1..];
+                    let end = rest.find('}').ok_or_else(|| TemplateError::Unclosed(source[start..].to_string()))?;
+                    let name = &rest[..end];
+                    let field = TradeField::from_name(name).ok_or_else(|| TemplateError::UnknownPlaceholder(name.to_string()))?;
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(TemplatePart::Field(field));
+                    // Skip the name and the closing brace
+                    for _ in 0..=name.chars().count() {
+                        chars.next();
+                    }
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Ok(Self { source: source.to_string(), parts })
+    }
+
+    pub fn parts(&self) -> &[TemplatePart] {
+        &self.parts
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl TryFrom<String> for RowTemplate {
+    type Error = TemplateError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl From<RowTemplate> for String {
+    fn from(template: RowTemplate) -> Self {
+        template.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_split_the_literal_text() {
+        let template = RowTemplate::parse("{time} {type} {{{coin}}} for ${value}").unwrap();
+        assert_eq!(
+            template.parts(),
+            [
+                TemplatePart::Field(TradeField::Time),
+                TemplatePart::Literal(" ".to_string()),
+                TemplatePart::Field(TradeField::Type),
+                TemplatePart::Literal(" {".to_string()),
+                TemplatePart::Field(TradeField::Coin),
+                TemplatePart::Literal("} for $".to_string()),
+                TemplatePart::Field(TradeField::Value),
+            ]
+        );
+        assert_eq!(RowTemplate::parse("").unwrap().parts(), []);
+    }
+
+    #[test]
+    fn mistakes_are_reported_when_the_config_loads() {
+        assert_eq!(RowTemplate::parse("{time} {usr}"), Err(TemplateError::UnknownPlaceholder("usr".to_string())));
+        assert_eq!(RowTemplate::parse("{coin} {value"), Err(TemplateError::Unclosed("{value".to_string())));
+
+        let error = serde_json::from_str::<RowTemplate>(r#""{Coin}""#).unwrap_err().to_string();
+        assert!(error.contains("unknown placeholder {Coin}"), "{}", error);
+        assert!(error.contains("{user}"), "{}", error);
+
+        let template: RowTemplate = serde_json::from_str(r#""{user} – {coin}""#).unwrap();
+        assert_eq!(serde_json::to_string(&template).unwrap(), r#""{user} – {coin}""#);
+    }
+}
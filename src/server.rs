@@ -0,0 +1,79 @@
+//! Optional read-only HTTP endpoint (`--serve PORT`) for scraping the
+//! buffers from other tools. It serves the same snapshots the UI reads and
+//! never changes anything.
+
+use crate::models::{PriceUpdate, Trade};
+use crate::store::SharedBuffer;
+use crate::websocket::FeedState;
+use anyhow::Result;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+#[derive(Debug, Clone)]
+struct Shared {
+    trades: Arc<SharedBuffer<Trade>>,
+    price_updates: Arc<SharedBuffer<PriceUpdate>>,
+    feed_state: watch::Receiver<FeedState>,
+}
+
+/// Body of `/health`.
+#[derive(Debug, Serialize)]
+struct Health {
+    feed: FeedState,
+    trades: usize,
+    price_updates: usize,
+    last_trade_at: Option<DateTime<Local>>,
+    last_price_at: Option<DateTime<Local>>,
+}
+
+/// `/trades` and `/prices` return the buffers newest first; `/health`
+/// answers 503 unless the feed is connected.
+pub fn router(
+    trades: Arc<SharedBuffer<Trade>>,
+    price_updates: Arc<SharedBuffer<PriceUpdate>>,
+    feed_state: watch::Receiver<FeedState>,
+) -> Router {
+    Router::new()
+        .route("/trades", get(get_trades))
+        .route("/prices", get(get_prices))
+        .route("/health", get(get_health))
+        .with_state(Shared { trades, price_updates, feed_state })
+}
+
+/// Serves [`router`] on `listener` until the process exits.
+pub async fn serve(
+    listener: TcpListener,
+    trades: Arc<SharedBuffer<Trade>>,
+    price_updates: Arc<SharedBuffer<PriceUpdate>>,
+    feed_state: watch::Receiver<FeedState>,
+) -> Result<()> {
+    axum::serve(listener, router(trades, price_updates, feed_state)).await?;
+    Ok(())
+}
+
+async fn get_trades(State(shared): State<Shared>) -> Json<Vec<Trade>> {
+    Json(shared.trades.snapshot().to_vec())
+}
+
+async fn get_prices(State(shared): State<Shared>) -> Json<Vec<PriceUpdate>> {
+    Json(shared.price_updates.snapshot().to_vec())
+}
+
+async fn get_health(State(shared): State<Shared>) -> (StatusCode, Json<Health>) {
+    let trades = shared.trades.snapshot();
+    let price_updates = shared.price_updates.snapshot();
+    let feed = *shared.feed_state.borrow();
+    let status = if feed == FeedState::Connected { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let health = Health {
+        feed,
+        trades: trades.len(),
+        price_updates: price_updates.len(),
+        last_trade_at: trades.first().map(|trade| trade.received_at),
+        last_price_at: price_updates.first().map(|update| update.received_at),
+    };
+    (status, Json(health))
+}
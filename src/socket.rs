@@ -0,0 +1,88 @@
+//! Optional trade stream on a Unix domain socket (`--socket PATH`) for local
+//! tools that want each trade as it arrives instead of polling `--serve`.
+//! Every connected client gets every new trade as one JSON object per line.
+
+use crate::models::Trade;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Trades a client can fall behind by before it skips ahead.
+pub const CHANNEL_CAPACITY: usize = 1024;
+/// Pause after a failed accept, doubled for every failure in a row up to
+/// `MAX_ACCEPT_BACKOFF`, so an error that persists (e.g. running out of
+/// file descriptors) doesn't spin a core.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Removes the socket file when dropped, i.e. when the app exits.
+#[derive(Debug)]
+pub struct SocketFile {
+    path: PathBuf,
+}
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Listens on `path`. A socket file left behind by a run that didn't exit
+/// cleanly is replaced; one another process is still listening on is not,
+/// and neither is anything that isn't a socket.
+pub fn bind(path: &Path) -> io::Result<(UnixListener, SocketFile)> {
+    let is_socket = std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket());
+    if is_socket {
+        match std::os::unix::net::UnixStream::connect(path) {
+            Ok(_) => return Err(io::Error::new(io::ErrorKind::AddrInUse, "another process is listening on it")),
+            Err(_) => std::fs::remove_file(path)?,
+        }
+    }
+    let listener = UnixListener::bind(path)?;
+    Ok((listener, SocketFile { path: path.to_path_buf() }))
+}
+
+/// Accepts clients until the process exits, sending each one the trades
+/// published on `trades` after it connected.
+pub async fn serve(listener: UnixListener, trades: broadcast::Sender<Trade>) {
+    let mut backoff = ACCEPT_BACKOFF;
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tracing::debug!("socket client connected");
+                tokio::spawn(forward(stream, trades.subscribe()));
+                backoff = ACCEPT_BACKOFF;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, retry_in = ?backoff, "failed to accept a socket client");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_ACCEPT_BACKOFF);
+            }
+        }
+    }
+}
+
+async fn forward(mut stream: UnixStream, mut trades: broadcast::Receiver<Trade>) {
+    loop {
+        let trade = match trades.recv().await {
+            Ok(trade) => trade,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped, "socket client fell behind, skipping trades");
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        let Ok(mut line) = serde_json::to_string(&trade) else {
+            continue;
+        };
+        line.push('\n');
+        if stream.write_all(line.as_bytes()).await.is_err() {
+            tracing::debug!("socket client disconnected");
+            return;
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::app::DEFAULT_SPLIT_PERCENT;
+use crate::models::{AppPage, Trade, TradeFilter};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const STATE_DIR_NAME: &str = "rug-listener";
+const STATE_FILE_NAME: &str = "state.json";
+
+/// Bumped whenever the layout changes; files written by another version are
+/// ignored rather than half-restored.
+pub const STATE_VERSION: u32 = 1;
+
+/// Where the user left off: saved on a clean exit and restored at the next
+/// start unless `--fresh` is given. Unlike the config this is never edited
+/// by hand.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiState {
+    pub version: u32,
+    pub tracked_coin: Option<String>,
+    /// Only restored when `persist_filters` is set in the config
+    pub coin_filter: String,
+    pub trader_filter: String,
+    pub trade_filter: TradeFilter,
+    pub page: AppPage,
+    /// Width share of the trades pane in split view
+    #[serde(default = "default_split_percent")]
+    pub split_percent: u16,
+    /// Only saved when `persist_bookmarks` is set in the config
+    #[serde(default)]
+    pub bookmarks: Vec<Trade>,
+}
+
+fn default_split_percent() -> u16 {
+    DEFAULT_SPLIT_PERCENT
+}
+
+impl UiState {
+    /// Returns `None` when there's no state file yet. A file that can't be
+    /// parsed or was written by another version is an error.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path)?;
+        let state: Self = serde_json::from_str(&contents)?;
+        if state.version != STATE_VERSION {
+            bail!("unsupported state version {} (expected {})", state.version, STATE_VERSION);
+        }
+        Ok(Some(state))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// `$XDG_DATA_HOME/rug-listener/state.json`, then
+/// `~/.local/share/rug-listener/state.json`.
+pub fn default_path() -> Option<PathBuf> {
+    Some(data_dir()?.join(STATE_FILE_NAME))
+}
+
+/// `$XDG_DATA_HOME/rug-listener`, then `~/.local/share/rug-listener`: where
+/// files the app writes for itself live.
+pub fn data_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))?;
+    Some(data_home.join(STATE_DIR_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_state() -> UiState {
+        UiState {
+            version: STATE_VERSION,
+            tracked_coin: Some("PEPE".to_string()),
+            coin_filter: "pepe,doge".to_string(),
+            trader_filter: "alice".to_string(),
+            trade_filter: TradeFilter::Large,
+            page: AppPage::PriceTracker,
+            split_percent: 45,
+            bookmarks: Vec::new(),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rug-listener-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn state_round_trips_through_json() {
+        let state = sample_state();
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<UiState>(&json).unwrap(), state);
+
+        let untracked = UiState { tracked_coin: None, page: AppPage::Trades, ..state };
+        let json = serde_json::to_string(&untracked).unwrap();
+        assert_eq!(serde_json::from_str::<UiState>(&json).unwrap(), untracked);
+    }
+
+    #[test]
+    fn files_without_newer_fields_get_the_defaults() {
+        let json = r#"{"version":1,"tracked_coin":null,"coin_filter":"","trader_filter":"","trade_filter":"all","page":"trades"}"#;
+        let state: UiState = serde_json::from_str(json).unwrap();
+        assert_eq!(state.split_percent, DEFAULT_SPLIT_PERCENT);
+        assert!(state.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn state_round_trips_through_a_file() {
+        let path = temp_path("state");
+        sample_state().save(&path).unwrap();
+        let loaded = UiState::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, Some(sample_state()));
+        assert_eq!(UiState::load(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn corrupt_or_other_version_files_are_rejected() {
+        let path = temp_path("corrupt-state");
+        fs::write(&path, "{ not json").unwrap();
+        assert!(UiState::load(&path).is_err());
+
+        UiState { version: STATE_VERSION + 1, ..sample_state() }.save(&path).unwrap();
+        let result = UiState::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+}
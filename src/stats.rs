@@ -0,0 +1,779 @@
+use crate::history::{HistoryPoint, PriceHistory};
+use crate::models::{lossy_f64, FlowBucket, PriceUpdate, Trade};
+use chrono::{DateTime, Local, Timelike};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Running totals of one coin's trades this session.
+#[derive(Debug, Clone)]
+pub struct CoinTradeStats {
+    pub trade_count: usize,
+    pub buy_count: usize,
+    pub sell_count: usize,
+    pub buy_value: Decimal,
+    pub sell_value: Decimal,
+    pub largest_value: Decimal,
+    pub largest_trader: String,
+    pub first_trade_at: DateTime<Local>,
+    pub last_trade_at: DateTime<Local>,
+}
+
+impl CoinTradeStats {
+    fn new(trade: &Trade) -> Self {
+        Self {
+            trade_count: 0,
+            buy_count: 0,
+            sell_count: 0,
+            buy_value: Decimal::ZERO,
+            sell_value: Decimal::ZERO,
+            largest_value: Decimal::ZERO,
+            largest_trader: String::new(),
+            first_trade_at: trade.received_at,
+            last_trade_at: trade.received_at,
+        }
+    }
+
+    fn record(&mut self, trade: &Trade) {
+        let value = trade.data.total_value;
+        self.trade_count += 1;
+        if trade.data.trade_type == "BUY" {
+            self.buy_count += 1;
+            self.buy_value = self.buy_value.saturating_add(value);
+        } else {
+            self.sell_count += 1;
+            self.sell_value = self.sell_value.saturating_add(value);
+        }
+        if self.trade_count == 1 || value > self.largest_value {
+            self.largest_value = value;
+            self.largest_trader = trade.data.username.clone();
+        }
+        self.first_trade_at = self.first_trade_at.min(trade.received_at);
+        self.last_trade_at = self.last_trade_at.max(trade.received_at);
+    }
+
+    pub fn total_value(&self) -> Decimal {
+        self.buy_value.saturating_add(self.sell_value)
+    }
+
+    pub fn average_value(&self) -> Decimal {
+        self.total_value().checked_div(Decimal::from(self.trade_count)).unwrap_or_default()
+    }
+}
+
+/// Running totals of one trader's trades this session.
+#[derive(Debug, Clone, Default)]
+pub struct TraderTotals {
+    pub trade_count: usize,
+    pub buy_value: Decimal,
+    pub sell_value: Decimal,
+}
+
+/// First and latest price seen for a coin.
+#[derive(Debug, Clone)]
+pub struct PriceSpan {
+    pub first_price: Decimal,
+    pub first_at: DateTime<Local>,
+    pub last_price: Decimal,
+    pub last_at: DateTime<Local>,
+}
+
+impl PriceSpan {
+    pub fn change(&self) -> Decimal {
+        self.last_price - self.first_price
+    }
+
+    /// Change as a percentage of the first price, if that isn't zero.
+    pub fn change_percent(&self) -> Option<f64> {
+        (!self.first_price.is_zero()).then(|| lossy_f64(self.change()) / lossy_f64(self.first_price) * 100.0)
+    }
+}
+
+/// Simple moving average over the last `window` samples, kept as a running
+/// sum. Until `window` samples have been pushed it averages the ones it has.
+#[derive(Debug)]
+pub struct MovingAverage {
+    window: usize,
+    samples: VecDeque<f64>,
+    sum: f64,
+}
+
+impl MovingAverage {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            samples: VecDeque::with_capacity(window),
+            sum: 0.0,
+        }
+    }
+
+    /// Adds a sample and returns the average including it.
+    pub fn push(&mut self, value: f64) -> f64 {
+        self.samples.push_back(value);
+        self.sum += value;
+        if self.samples.len() > self.window {
+            self.sum -= self.samples.pop_front().unwrap_or_default();
+        }
+        self.sum / self.samples.len() as f64
+    }
+
+    /// Whether a full window of samples has been seen.
+    pub fn is_warm(&self) -> bool {
+        self.samples.len() >= self.window
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Average of the samples held, or `None` before the first one.
+    pub fn average(&self) -> Option<f64> {
+        (!self.samples.is_empty()).then(|| self.sum / self.samples.len() as f64)
+    }
+}
+
+/// Trades the feed lag is averaged over.
+pub const FEED_LAG_SAMPLES: usize = 20;
+
+/// Seconds between each trade's own timestamp and its arrival, averaged over
+/// the last `FEED_LAG_SAMPLES` trades.
+#[derive(Debug)]
+struct FeedLag(MovingAverage);
+
+impl Default for FeedLag {
+    fn default() -> Self {
+        Self(MovingAverage::new(FEED_LAG_SAMPLES))
+    }
+}
+
+/// Minutes of traded value kept for the volume chart.
+pub const VOLUME_MINUTES: usize = 30;
+
+pub fn truncate_to_minute(time: DateTime<Local>) -> DateTime<Local> {
+    time.with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(time)
+}
+
+/// Buy and sell value per minute over the last `VOLUME_MINUTES` minutes,
+/// oldest first. Only minutes with trades have a bucket.
+#[derive(Debug, Default)]
+pub struct MinuteVolume {
+    buckets: VecDeque<FlowBucket>,
+}
+
+impl MinuteVolume {
+    pub fn record(&mut self, trade: &Trade) {
+        let minute = truncate_to_minute(trade.received_at);
+        let position = self.buckets.iter().rposition(|bucket| bucket.minute <= minute);
+        let bucket = match position {
+            Some(index) if self.buckets[index].minute == minute => &mut self.buckets[index],
+            _ => {
+                let index = position.map_or(0, |index| index + 1);
+                self.buckets.insert(
+                    index,
+                    FlowBucket {
+                        minute,
+                        buy_value: 0.0,
+                        sell_value: 0.0,
+                        trade_count: 0,
+                    },
+                );
+                &mut self.buckets[index]
+            }
+        };
+        if trade.data.trade_type == "BUY" {
+            bucket.buy_value += lossy_f64(trade.data.total_value);
+        } else {
+            bucket.sell_value += lossy_f64(trade.data.total_value);
+        }
+        bucket.trade_count += 1;
+
+        // Keep only the minutes the chart can show
+        let newest = self.buckets.back().map_or(minute, |bucket| bucket.minute);
+        let cutoff = newest - chrono::Duration::minutes(VOLUME_MINUTES as i64);
+        while self.buckets.front().is_some_and(|bucket| bucket.minute <= cutoff) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// The `VOLUME_MINUTES` minutes ending with the one containing `now`,
+    /// oldest first, with empty buckets for minutes without trades.
+    pub fn series(&self, now: DateTime<Local>) -> Vec<FlowBucket> {
+        let current_minute = truncate_to_minute(now);
+        let mut recorded = self.buckets.iter().peekable();
+        (0..VOLUME_MINUTES)
+            .rev()
+            .map(|ago| {
+                let minute = current_minute - chrono::Duration::minutes(ago as i64);
+                while recorded.next_if(|bucket| bucket.minute < minute).is_some() {}
+                match recorded.next_if(|bucket| bucket.minute == minute) {
+                    Some(bucket) => bucket.clone(),
+                    None => FlowBucket {
+                        minute,
+                        buy_value: 0.0,
+                        sell_value: 0.0,
+                        trade_count: 0,
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+/// Upper bounds of the trade size buckets; the last bucket is open-ended.
+pub const SIZE_BUCKET_BOUNDS: [f64; 4] = [10.0, 100.0, 1_000.0, 10_000.0];
+pub const SIZE_BUCKET_LABELS: [&str; 5] = ["$0-10", "$10-100", "$100-1k", "$1k-10k", "$10k+"];
+
+/// Trade count and total value per size bucket, on a log scale of
+/// total_value.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SizeHistogram {
+    pub counts: [usize; 5],
+    pub values: [f64; 5],
+}
+
+impl SizeHistogram {
+    pub fn bucket(value: f64) -> usize {
+        SIZE_BUCKET_BOUNDS
+            .iter()
+            .position(|&bound| value < bound)
+            .unwrap_or(SIZE_BUCKET_BOUNDS.len())
+    }
+
+    pub fn add(&mut self, value: f64) {
+        let bucket = Self::bucket(value);
+        self.counts[bucket] += 1;
+        self.values[bucket] += value;
+    }
+
+    pub fn remove(&mut self, value: f64) {
+        let bucket = Self::bucket(value);
+        self.counts[bucket] = self.counts[bucket].saturating_sub(1);
+        self.values[bucket] = if self.counts[bucket] == 0 { 0.0 } else { self.values[bucket] - value };
+    }
+
+    pub fn combined(&self, other: &SizeHistogram) -> SizeHistogram {
+        let mut combined = *self;
+        for bucket in 0..combined.counts.len() {
+            combined.counts[bucket] += other.counts[bucket];
+            combined.values[bucket] += other.values[bucket];
+        }
+        combined
+    }
+}
+
+/// Rolling window the net flow is computed over.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum NetFlowWindow {
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+}
+
+impl NetFlowWindow {
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            NetFlowWindow::FiveMinutes => chrono::Duration::minutes(5),
+            NetFlowWindow::FifteenMinutes => chrono::Duration::minutes(15),
+            NetFlowWindow::OneHour => chrono::Duration::hours(1),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NetFlowWindow::FiveMinutes => "5m",
+            NetFlowWindow::FifteenMinutes => "15m",
+            NetFlowWindow::OneHour => "1h",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CoinFlow {
+    /// Received time and signed value (buys positive), oldest first
+    trades: VecDeque<(DateTime<Local>, Decimal)>,
+    net: Decimal,
+    gross: Decimal,
+}
+
+/// Buy minus sell value per coin over a rolling window. Trades are added as
+/// they arrive and subtracted again once they're older than the window, so
+/// reading never rescans the buffers. The sums are decimal, so subtracting
+/// every trade again brings them back to exactly zero.
+#[derive(Debug)]
+pub struct NetFlow {
+    window: NetFlowWindow,
+    coins: HashMap<String, CoinFlow>,
+}
+
+impl Default for NetFlow {
+    fn default() -> Self {
+        Self::new(NetFlowWindow::FifteenMinutes)
+    }
+}
+
+impl NetFlow {
+    pub fn new(window: NetFlowWindow) -> Self {
+        Self {
+            window,
+            coins: HashMap::new(),
+        }
+    }
+
+    pub fn window(&self) -> NetFlowWindow {
+        self.window
+    }
+
+    pub fn record(&mut self, trade: &Trade) {
+        let value = trade.data.total_value;
+        let signed = if trade.data.trade_type == "BUY" { value } else { -value };
+        let flow = self.coins.entry(trade.data.coin_symbol.to_uppercase()).or_default();
+        flow.trades.push_back((trade.received_at, signed));
+        flow.net = flow.net.saturating_add(signed);
+        flow.gross = flow.gross.saturating_add(value);
+        Self::expire(flow, trade.received_at - self.window.duration());
+    }
+
+    /// Net flow of `coin` and the gross value traded, over the window ending
+    /// at `now`. A trade exactly one window old has already aged out.
+    pub fn get(&mut self, coin_symbol: &str, now: DateTime<Local>) -> (Decimal, Decimal) {
+        let cutoff = now - self.window.duration();
+        match self.coins.get_mut(&coin_symbol.to_uppercase()) {
+            Some(flow) => {
+                Self::expire(flow, cutoff);
+                (flow.net, flow.gross)
+            }
+            None => (Decimal::ZERO, Decimal::ZERO),
+        }
+    }
+
+    fn expire(flow: &mut CoinFlow, cutoff: DateTime<Local>) {
+        while let Some(&(at, signed)) = flow.trades.front() {
+            if at > cutoff {
+                break;
+            }
+            flow.trades.pop_front();
+            flow.net = flow.net.saturating_sub(signed);
+            flow.gross = flow.gross.saturating_sub(signed.abs());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.coins.clear();
+    }
+}
+
+/// Per-coin and per-trader aggregates, updated once per incoming item so
+/// reading them never rescans the buffers. They cover the whole session,
+/// including entries the buffers have since evicted. Coins are keyed by
+/// uppercase symbol, traders by lowercase username.
+#[derive(Debug, Default)]
+pub struct SessionStats {
+    coins: HashMap<String, CoinTradeStats>,
+    traders: HashMap<String, TraderTotals>,
+    prices: HashMap<String, PriceSpan>,
+    latest_prices: HashMap<String, PriceUpdate>,
+    /// Bumped whenever `latest_prices` changes
+    price_generation: u64,
+    history: PriceHistory,
+    /// Bumped whenever `history` changes
+    history_generation: u64,
+    net_flow: NetFlow,
+    feed_volume: MinuteVolume,
+    coin_volume: HashMap<String, MinuteVolume>,
+    feed_lag: FeedLag,
+}
+
+impl SessionStats {
+    pub fn new(net_flow_window: NetFlowWindow) -> Self {
+        Self {
+            net_flow: NetFlow::new(net_flow_window),
+            ..Self::default()
+        }
+    }
+
+    pub fn record_trade(&mut self, trade: &Trade) {
+        self.coins
+            .entry(trade.data.coin_symbol.to_uppercase())
+            .or_insert_with(|| CoinTradeStats::new(trade))
+            .record(trade);
+
+        self.net_flow.record(trade);
+        self.feed_volume.record(trade);
+        if let Some(lag) = trade.feed_lag() {
+            self.feed_lag.0.push(lag.num_milliseconds() as f64 / 1000.0);
+        }
+        self.coin_volume
+            .entry(trade.data.coin_symbol.to_uppercase())
+            .or_default()
+            .record(trade);
+
+        let totals = self.traders.entry(trade.data.username.to_lowercase()).or_default();
+        totals.trade_count += 1;
+        if trade.data.trade_type == "BUY" {
+            totals.buy_value = totals.buy_value.saturating_add(trade.data.total_value);
+        } else {
+            totals.sell_value = totals.sell_value.saturating_add(trade.data.total_value);
+        }
+    }
+
+    pub fn record_price(&mut self, update: &PriceUpdate) {
+        let coin_symbol = update.coin_symbol.to_uppercase();
+        let price = update.current_price;
+        let at = update.received_at;
+        self.prices
+            .entry(coin_symbol.clone())
+            .and_modify(|span| {
+                span.last_price = price;
+                span.last_at = at;
+            })
+            .or_insert(PriceSpan {
+                first_price: price,
+                first_at: at,
+                last_price: price,
+                last_at: at,
+            });
+        match self.latest_prices.get_mut(&coin_symbol) {
+            // Reuse the entry so a busy feed doesn't reallocate the update
+            Some(latest) => latest.clone_from(update),
+            None => {
+                self.latest_prices.insert(coin_symbol, update.clone());
+            }
+        }
+        self.price_generation += 1;
+    }
+
+    pub fn trade_stats(&self, coin_symbol: &str) -> Option<&CoinTradeStats> {
+        self.coins.get(&coin_symbol.to_uppercase())
+    }
+
+    pub fn trader_totals(&self, username: &str) -> Option<&TraderTotals> {
+        self.traders.get(&username.to_lowercase())
+    }
+
+    pub fn price_span(&self, coin_symbol: &str) -> Option<&PriceSpan> {
+        self.prices.get(&coin_symbol.to_uppercase())
+    }
+
+    /// `price` as a percentage change from the first price seen for the coin
+    /// this session, if there is one and it isn't zero.
+    pub fn session_change_percent(&self, coin_symbol: &str, price: Decimal) -> Option<f64> {
+        let first_price = self.price_span(coin_symbol)?.first_price;
+        (!first_price.is_zero()).then(|| lossy_f64(price - first_price) / lossy_f64(first_price) * 100.0)
+    }
+
+    pub fn clear_trades(&mut self) {
+        self.coins.clear();
+        self.traders.clear();
+        self.net_flow.clear();
+        self.feed_volume = MinuteVolume::default();
+        self.coin_volume.clear();
+        self.feed_lag = FeedLag::default();
+    }
+
+    /// Average feed lag in seconds, once a full sample window has arrived.
+    pub fn average_feed_lag(&self) -> Option<f64> {
+        self.feed_lag.0.is_warm().then(|| self.feed_lag.0.average()).flatten()
+    }
+
+    /// Per-minute buy/sell value of `coin`, or of the whole feed, for the
+    /// volume chart.
+    pub fn minute_volume(&self, coin_symbol: Option<&str>, now: DateTime<Local>) -> Vec<FlowBucket> {
+        match coin_symbol {
+            Some(coin) => self
+                .coin_volume
+                .get(&coin.to_uppercase())
+                .map(|volume| volume.series(now))
+                .unwrap_or_else(|| MinuteVolume::default().series(now)),
+            None => self.feed_volume.series(now),
+        }
+    }
+
+    /// Adds an update that made it into the price history buffer to the
+    /// downsampled per-coin history.
+    pub fn record_history(&mut self, update: &PriceUpdate) {
+        self.history.record(update);
+        self.history_generation += 1;
+    }
+
+    /// See [`PriceHistory::points`].
+    pub fn price_history(&self, coin_symbol: &str, since: Option<DateTime<Local>>) -> Vec<HistoryPoint> {
+        self.history.points(coin_symbol, since)
+    }
+
+    /// The most recent update for `coin_symbol`, throttled or not.
+    pub fn latest_price(&self, coin_symbol: &str) -> Option<&PriceUpdate> {
+        self.latest_prices.get(&coin_symbol.to_uppercase())
+    }
+
+    /// The most recent update for every coin seen this session.
+    pub fn latest_prices(&self) -> impl Iterator<Item = &PriceUpdate> {
+        self.latest_prices.values()
+    }
+
+    /// Up to `count` coins with the largest absolute 24h change.
+    pub fn top_movers(&self, count: usize) -> Vec<&PriceUpdate> {
+        self.leaders(count, |update| update.change_24h.abs())
+    }
+
+    /// Up to `count` coins with the highest 24h volume.
+    pub fn volume_leaders(&self, count: usize) -> Vec<&PriceUpdate> {
+        self.leaders(count, |update| lossy_f64(update.volume_24h))
+    }
+
+    // Ties are broken by symbol so the panels don't shuffle between frames
+    fn leaders(&self, count: usize, figure: impl Fn(&PriceUpdate) -> f64) -> Vec<&PriceUpdate> {
+        let mut updates: Vec<&PriceUpdate> = self.latest_prices.values().collect();
+        updates.sort_by(|a, b| {
+            figure(b)
+                .total_cmp(&figure(a))
+                .then_with(|| a.coin_symbol.cmp(&b.coin_symbol))
+        });
+        updates.truncate(count);
+        updates
+    }
+
+    pub fn price_generation(&self) -> u64 {
+        self.price_generation
+    }
+
+    pub fn history_generation(&self) -> u64 {
+        self.history_generation
+    }
+
+    /// Buy minus sell value for `coin` over the net flow window ending at
+    /// `now`, with the gross value traded in it.
+    pub fn net_flow(&mut self, coin_symbol: &str, now: DateTime<Local>) -> (Decimal, Decimal) {
+        self.net_flow.get(coin_symbol, now)
+    }
+
+    pub fn net_flow_window(&self) -> NetFlowWindow {
+        self.net_flow.window()
+    }
+
+    pub fn clear_prices(&mut self) {
+        self.prices.clear();
+        self.latest_prices.clear();
+        self.history.clear();
+        self.price_generation += 1;
+        self.history_generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: f64) -> Decimal {
+        Decimal::try_from(value).unwrap()
+    }
+
+    fn trade_at(trade_type: &str, value: f64, received_at: DateTime<Local>) -> Trade {
+        Trade {
+            received_at,
+            ..trade(trade_type, "alice", value)
+        }
+    }
+
+    fn trade(trade_type: &str, username: &str, value: f64) -> Trade {
+        let mut trade = Trade::sample("pepe", username);
+        trade.data.trade_type = trade_type.to_string();
+        trade.data.total_value = dec(value);
+        trade
+    }
+
+    #[test]
+    fn feed_lag_is_averaged_once_the_window_is_full() {
+        let mut stats = SessionStats::default();
+        let lagged = |secs: i64| {
+            let mut trade = trade("BUY", "alice", 1.0);
+            trade.data.timestamp = (trade.received_at - chrono::Duration::seconds(secs)).timestamp_millis();
+            trade
+        };
+        for _ in 1..FEED_LAG_SAMPLES {
+            stats.record_trade(&lagged(4));
+        }
+        assert_eq!(stats.average_feed_lag(), None);
+        // Trades without a timestamp don't count
+        let mut untimed = trade("BUY", "alice", 1.0);
+        untimed.data.timestamp = 0;
+        stats.record_trade(&untimed);
+        assert_eq!(stats.average_feed_lag(), None);
+        stats.record_trade(&lagged(4));
+        assert!((stats.average_feed_lag().unwrap() - 4.0).abs() < 0.01);
+        stats.clear_trades();
+        assert_eq!(stats.average_feed_lag(), None);
+    }
+
+    #[test]
+    fn trade_stats_accumulate_per_coin() {
+        let mut session = SessionStats::default();
+        session.record_trade(&trade("BUY", "alice", 10.0));
+        session.record_trade(&trade("SELL", "bob", 30.0));
+        session.record_trade(&trade("BUY", "carol", 20.0));
+
+        let stats = session.trade_stats("PEPE").unwrap();
+        assert_eq!(stats.trade_count, 3);
+        assert_eq!((stats.buy_count, stats.sell_count), (2, 1));
+        assert_eq!(stats.total_value(), dec(60.0));
+        assert_eq!(stats.average_value(), dec(20.0));
+        assert_eq!(stats.largest_trader, "bob");
+        assert!(session.trade_stats("DOGE").is_none());
+
+        let bob = session.trader_totals("Bob").unwrap();
+        assert_eq!((bob.trade_count, bob.sell_value), (1, dec(30.0)));
+    }
+
+    #[test]
+    fn moving_average_warms_up_then_slides() {
+        let mut average = MovingAverage::new(3);
+        assert_eq!(average.push(3.0), 3.0);
+        assert!(!average.is_warm());
+        assert_eq!(average.push(6.0), 4.5);
+        assert_eq!(average.push(9.0), 6.0);
+        assert!(average.is_warm());
+        // The first sample drops out of the window
+        assert_eq!(average.push(12.0), 9.0);
+        assert_eq!(average.sample_count(), 3);
+    }
+
+    #[test]
+    fn minute_volume_is_bucketed_by_minute_and_bounded() {
+        let start = truncate_to_minute(Local::now()) - chrono::Duration::hours(1);
+        let at = |minutes: i64, secs: i64| start + chrono::Duration::minutes(minutes) + chrono::Duration::seconds(secs);
+        let mut volume = MinuteVolume::default();
+        volume.record(&trade_at("BUY", 10.0, at(0, 5)));
+        volume.record(&trade_at("SELL", 4.0, at(0, 59)));
+        volume.record(&trade_at("BUY", 1.0, at(2, 0)));
+        // A late arrival for an earlier minute still lands in its own bucket
+        volume.record(&trade_at("BUY", 2.0, at(1, 30)));
+
+        let series = volume.series(at(2, 30));
+        assert_eq!(series.len(), VOLUME_MINUTES);
+        let last: Vec<(f64, f64)> = series[VOLUME_MINUTES - 3..].iter().map(|b| (b.buy_value, b.sell_value)).collect();
+        assert_eq!(last, [(10.0, 4.0), (2.0, 0.0), (1.0, 0.0)]);
+        assert_eq!(series.last().unwrap().minute, at(2, 0));
+
+        // Minutes that fall out of the window are dropped
+        volume.record(&trade_at("BUY", 1.0, at(VOLUME_MINUTES as i64, 0)));
+        assert_eq!(volume.buckets.len(), 3);
+        assert_eq!(volume.buckets.front().unwrap().minute, at(1, 0));
+    }
+
+    #[test]
+    fn size_buckets_split_at_powers_of_ten() {
+        assert_eq!(SizeHistogram::bucket(0.0), 0);
+        assert_eq!(SizeHistogram::bucket(9.99), 0);
+        assert_eq!(SizeHistogram::bucket(10.0), 1);
+        assert_eq!(SizeHistogram::bucket(999.99), 2);
+        assert_eq!(SizeHistogram::bucket(1_000.0), 3);
+        assert_eq!(SizeHistogram::bucket(10_000.0), 4);
+        assert_eq!(SizeHistogram::bucket(1e12), 4);
+
+        let mut histogram = SizeHistogram::default();
+        histogram.add(5.0);
+        histogram.add(50.0);
+        histogram.add(60.0);
+        histogram.remove(50.0);
+        assert_eq!(histogram.counts, [1, 1, 0, 0, 0]);
+        assert_eq!(histogram.values, [5.0, 60.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn net_flow_ages_trades_out_at_the_window_boundary() {
+        let start = Local::now();
+        let window = NetFlowWindow::FiveMinutes.duration();
+        let mut flow = NetFlow::new(NetFlowWindow::FiveMinutes);
+        flow.record(&trade_at("BUY", 100.0, start));
+        flow.record(&trade_at("SELL", 30.0, start + chrono::Duration::minutes(2)));
+        flow.record(&trade_at("BUY", 5.0, start + chrono::Duration::minutes(4)));
+
+        assert_eq!(flow.get("PEPE", start + chrono::Duration::minutes(4)), (dec(75.0), dec(135.0)));
+        // Just inside the window the first trade still counts
+        let first_expiry = start + window;
+        assert_eq!(flow.get("PEPE", first_expiry - chrono::Duration::milliseconds(1)), (dec(75.0), dec(135.0)));
+        // Exactly one window old is out
+        assert_eq!(flow.get("PEPE", first_expiry), (dec(-25.0), dec(35.0)));
+        assert_eq!(flow.get("PEPE", first_expiry + chrono::Duration::minutes(2)), (dec(5.0), dec(5.0)));
+        assert_eq!(flow.get("PEPE", first_expiry + chrono::Duration::minutes(4)), (Decimal::ZERO, Decimal::ZERO));
+        assert_eq!(flow.get("DOGE", start), (Decimal::ZERO, Decimal::ZERO));
+    }
+
+    #[test]
+    fn net_flow_sums_cents_without_drifting() {
+        let start = Local::now();
+        let mut flow = NetFlow::new(NetFlowWindow::FiveMinutes);
+        for seconds in 0..3 {
+            flow.record(&trade_at("BUY", 0.1, start + chrono::Duration::seconds(seconds)));
+        }
+        flow.record(&trade_at("SELL", 0.3, start + chrono::Duration::seconds(3)));
+        // The same sums in f64 are off in the last digits
+        assert_ne!(0.1 + 0.1 + 0.1 - 0.3, 0.0);
+        assert_eq!(flow.get("PEPE", start + chrono::Duration::seconds(3)), (Decimal::ZERO, dec(0.6)));
+        // Taking the buys off again leaves the sell exactly
+        assert_eq!(flow.get("PEPE", start + chrono::Duration::seconds(302)), (dec(-0.3), dec(0.3)));
+    }
+
+    #[test]
+    fn recording_expires_old_trades_of_the_same_coin() {
+        let start = Local::now();
+        let mut flow = NetFlow::new(NetFlowWindow::FiveMinutes);
+        flow.record(&trade_at("SELL", 50.0, start));
+        flow.record(&trade_at("BUY", 20.0, start + chrono::Duration::minutes(5)));
+        assert_eq!(flow.coins["PEPE"].trades.len(), 1);
+        assert_eq!(flow.get("pepe", start + chrono::Duration::minutes(5)), (dec(20.0), dec(20.0)));
+    }
+
+    fn price(coin_symbol: &str, change_24h: f64, volume_24h: f64) -> PriceUpdate {
+        PriceUpdate {
+            change_24h,
+            volume_24h: dec(volume_24h),
+            ..PriceUpdate::sample(coin_symbol)
+        }
+    }
+
+    #[test]
+    fn leaders_rank_by_figure_then_symbol() {
+        let mut session = SessionStats::default();
+        session.record_price(&price("DOGE", -40.0, 10.0));
+        session.record_price(&price("PEPE", 40.0, 10.0));
+        session.record_price(&price("SHIB", 5.0, 300.0));
+
+        let movers: Vec<&str> = session.top_movers(5).iter().map(|u| u.coin_symbol.as_str()).collect();
+        assert_eq!(movers, ["DOGE", "PEPE", "SHIB"]);
+        let volume: Vec<&str> = session.volume_leaders(2).iter().map(|u| u.coin_symbol.as_str()).collect();
+        assert_eq!(volume, ["SHIB", "DOGE"]);
+    }
+
+    #[test]
+    fn session_change_is_measured_from_the_first_price_seen() {
+        let mut session = SessionStats::default();
+        assert_eq!(session.session_change_percent("PEPE", Decimal::ONE), None);
+
+        let mut update = price("PEPE", 3.0, 0.0);
+        update.current_price = dec(0.5);
+        session.record_price(&update);
+        update.current_price = dec(0.75);
+        session.record_price(&update);
+        assert_eq!(session.session_change_percent("pepe", dec(0.75)), Some(50.0));
+        assert_eq!(session.session_change_percent("PEPE", dec(0.25)), Some(-50.0));
+
+        update.current_price = Decimal::ZERO;
+        update.coin_symbol = "DOGE".to_string();
+        session.record_price(&update);
+        assert_eq!(session.session_change_percent("DOGE", Decimal::ONE), None);
+    }
+
+    #[test]
+    fn latest_price_is_found_whatever_the_symbol_case() {
+        let mut session = SessionStats::default();
+        session.record_price(&price("pepe", 1.0, 0.0));
+        session.record_price(&price("PEPE", 2.0, 0.0));
+        assert_eq!(session.latest_prices().count(), 1);
+        assert_eq!(session.latest_price("Pepe").map(|update| update.change_24h), Some(2.0));
+    }
+}
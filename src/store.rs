@@ -0,0 +1,339 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+
+/// How often a writer publishes a fresh snapshot (and applies time-based
+/// retention) while messages are flowing.
+pub const PUBLISH_INTERVAL: Duration = Duration::from_millis(50);
+/// Copies of one item from different feeds arrive within this many seconds
+/// of each other, so older entries aren't searched for duplicates.
+pub const DUPLICATE_WINDOW_SECS: i64 = 5;
+
+pub trait Timestamped {
+    fn received_at(&self) -> DateTime<Local>;
+}
+
+/// Bounds for one of the shared buffers: always capped by count, and
+/// optionally by age as well.
+#[derive(Debug, Clone, Copy)]
+pub struct Retention {
+    pub max_items: usize,
+    pub max_age: Option<chrono::Duration>,
+}
+
+impl Retention {
+    pub fn new(max_items: usize, max_age: Option<Duration>) -> Result<Self> {
+        if max_items == 0 {
+            bail!("buffer limit must be at least 1");
+        }
+        let max_age = match max_age {
+            Some(age) if age.is_zero() => bail!("retention must be greater than zero"),
+            Some(age) => Some(chrono::Duration::from_std(age)?),
+            None => None,
+        };
+        Ok(Self { max_items, max_age })
+    }
+
+    /// Drops entries from the back (oldest end) of a newest-first buffer until
+    /// it fits. Returns whether anything was removed.
+    pub fn enforce<T: Timestamped>(&self, items: &mut VecDeque<T>) -> bool {
+        let before = items.len();
+        items.truncate(self.max_items);
+        if let Some(max_age) = self.max_age {
+            let cutoff = Local::now() - max_age;
+            while items.back().is_some_and(|item| item.received_at() < cutoff) {
+                items.pop_back();
+            }
+        }
+        items.len() != before
+    }
+}
+
+/// A newest-first buffer with a single writer and many readers.
+///
+/// The writer mutates a private deque and periodically publishes an immutable
+/// snapshot. Readers only ever clone the snapshot `Arc`, so a render never
+/// waits on ingestion and ingestion never waits on a render.
+#[derive(Debug)]
+pub struct SharedBuffer<T> {
+    items: Mutex<VecDeque<T>>,
+    snapshot: Mutex<Arc<Vec<T>>>,
+    generation: AtomicU64,
+}
+
+impl<T: Clone> SharedBuffer<T> {
+    pub fn new() -> Self {
+        Self {
+            items: Mutex::new(VecDeque::new()),
+            snapshot: Mutex::new(Arc::new(Vec::new())),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// The most recently published contents, newest first.
+    pub fn snapshot(&self) -> Arc<Vec<T>> {
+        self.snapshot.lock().unwrap().clone()
+    }
+
+    /// Bumped every time a new snapshot is published.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Runs `f` against the writer-side deque. Changes become visible to
+    /// readers on the next `publish`.
+    pub fn modify<R>(&self, f: impl FnOnce(&mut VecDeque<T>) -> R) -> R {
+        f(&mut self.items.lock().unwrap())
+    }
+
+    /// Makes the writer's changes visible. This clones every buffered item
+    /// into a new snapshot, so its cost grows with the buffer (a thousand
+    /// trades by default) and `ingest` calls it at most once per
+    /// `PUBLISH_INTERVAL`, never per item.
+    pub fn publish(&self) {
+        let snapshot: Vec<T> = self.items.lock().unwrap().iter().cloned().collect();
+        *self.snapshot.lock().unwrap() = Arc::new(snapshot);
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Empties the buffer and publishes the empty snapshot immediately.
+    pub fn clear(&self) {
+        self.items.lock().unwrap().clear();
+        self.publish();
+    }
+}
+
+impl<T: Clone> Default for SharedBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The buffered item that `is_duplicate` considers the same as `item`,
+/// looking only at entries received shortly before it.
+pub fn find_recent_duplicate<'a, T: Timestamped>(
+    items: &'a mut VecDeque<T>,
+    item: &T,
+    is_duplicate: impl Fn(&T, &T) -> bool,
+) -> Option<&'a mut T> {
+    let since = item.received_at() - chrono::Duration::seconds(DUPLICATE_WINDOW_SECS);
+    items
+        .iter_mut()
+        .take_while(|buffered| buffered.received_at() >= since)
+        .find(|buffered| is_duplicate(item, buffered))
+}
+
+/// What `ingest`'s `accept` made of an incoming item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accept {
+    /// Buffer it as a new item
+    Insert,
+    /// It was folded into a buffered item, which changed
+    Updated,
+    /// Drop it; nothing changed
+    Skip,
+}
+
+/// Drains `rx` into `buffer`, applying `retention`, and publishes snapshots
+/// at most every `PUBLISH_INTERVAL`. `accept` can reject an incoming item by
+/// looking at what's already buffered, or fold it into a buffered item; an
+/// update is published like a new item. `notify` is signalled after each
+/// publish so the UI can redraw.
+pub async fn ingest<T: Clone + Timestamped>(
+    mut rx: mpsc::Receiver<T>,
+    buffer: Arc<SharedBuffer<T>>,
+    retention: Retention,
+    mut accept: impl FnMut(&mut VecDeque<T>, &T) -> Accept,
+    notify: Arc<Notify>,
+) {
+    let mut publish_interval = tokio::time::interval(PUBLISH_INTERVAL);
+    let mut dirty = false;
+    loop {
+        tokio::select! {
+            item = rx.recv() => {
+                let Some(item) = item else { break };
+                dirty |= buffer.modify(|items| match accept(items, &item) {
+                    Accept::Insert => {
+                        items.push_front(item);
+                        retention.enforce(items);
+                        true
+                    }
+                    Accept::Updated => true,
+                    Accept::Skip => false,
+                });
+            }
+            _ = publish_interval.tick() => {
+                // Expire old entries even when the feed goes quiet
+                dirty |= buffer.modify(|items| retention.enforce(items));
+                if dirty {
+                    buffer.publish();
+                    notify.notify_one();
+                    dirty = false;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc as std_mpsc;
+
+    #[derive(Debug, Clone)]
+    struct Item {
+        id: usize,
+        at: DateTime<Local>,
+        large: bool,
+    }
+
+    fn item(id: usize) -> Item {
+        Item { id, at: Local::now(), large: false }
+    }
+
+    impl Timestamped for Item {
+        fn received_at(&self) -> DateTime<Local> {
+            self.at
+        }
+    }
+
+    #[test]
+    fn retention_caps_by_count_and_age() {
+        let retention = Retention::new(3, Some(Duration::from_secs(60))).unwrap();
+        let now = Local::now();
+        let mut items: VecDeque<Item> = (0..5)
+            .map(|id| Item { at: now - chrono::Duration::seconds(id as i64 * 40), ..item(id) })
+            .collect();
+        assert!(retention.enforce(&mut items));
+        // id 2 is 80s old, past the 60s window
+        assert_eq!(items.iter().map(|i| i.id).collect::<Vec<_>>(), vec![0, 1]);
+        assert!(Retention::new(0, None).is_err());
+        assert!(Retention::new(1, Some(Duration::ZERO)).is_err());
+    }
+
+    #[test]
+    fn duplicates_are_only_searched_among_recent_items() {
+        let now = Local::now();
+        let mut items: VecDeque<Item> = VecDeque::from([
+            Item { at: now - chrono::Duration::seconds(1), ..item(1) },
+            Item { at: now - chrono::Duration::seconds(60), ..item(2) },
+        ]);
+        let same_id = |a: &Item, b: &Item| a.id == b.id;
+
+        let found = find_recent_duplicate(&mut items, &Item { at: now, ..item(1) }, same_id);
+        assert_eq!(found.map(|item| item.id), Some(1));
+        assert!(find_recent_duplicate(&mut items, &Item { at: now, ..item(2) }, same_id).is_none());
+    }
+
+    #[tokio::test]
+    async fn ingest_publishes_newest_first_within_limits() {
+        let buffer = Arc::new(SharedBuffer::new());
+        let notify = Arc::new(Notify::new());
+        let (tx, rx) = mpsc::channel(16);
+        let retention = Retention::new(3, None).unwrap();
+        let task = tokio::spawn(ingest(rx, buffer.clone(), retention, |_, _| Accept::Insert, notify.clone()));
+
+        for id in 0..5 {
+            tx.send(item(id)).await.unwrap();
+        }
+        notify.notified().await;
+        drop(tx);
+        task.await.unwrap();
+
+        let snapshot = buffer.snapshot();
+        assert_eq!(snapshot.iter().map(|i| i.id).collect::<Vec<_>>(), vec![4, 3, 2]);
+    }
+
+    #[tokio::test]
+    async fn an_item_updated_in_place_is_published() {
+        let buffer = Arc::new(SharedBuffer::new());
+        let notify = Arc::new(Notify::new());
+        let (tx, rx) = mpsc::channel(16);
+        let retention = Retention::new(10, None).unwrap();
+        // A second copy of an item only flags the buffered one, like the
+        // large-trades feed does for trades
+        let merge = |items: &mut VecDeque<Item>, item: &Item| match find_recent_duplicate(items, item, |a, b| a.id == b.id) {
+            Some(existing) if !existing.large && item.large => {
+                existing.large = true;
+                Accept::Updated
+            }
+            Some(_) => Accept::Skip,
+            None => Accept::Insert,
+        };
+        let task = tokio::spawn(ingest(rx, buffer.clone(), retention, merge, notify.clone()));
+
+        tx.send(item(1)).await.unwrap();
+        notify.notified().await;
+        assert!(!buffer.snapshot()[0].large);
+
+        tx.send(Item { large: true, ..item(1) }).await.unwrap();
+        notify.notified().await;
+        drop(tx);
+        task.await.unwrap();
+        let snapshot = buffer.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert!(snapshot[0].large);
+    }
+
+    /// Replays items into the buffer at full speed while a simulated UI loop
+    /// takes snapshots and handles input events. Every input event must be
+    /// handled, and the reader must keep seeing newer snapshots rather than
+    /// being held off until the writer is done.
+    #[test]
+    fn readers_stay_responsive_under_max_speed_replay() {
+        const REPLAYED: usize = 200_000;
+        const INPUT_EVENTS: usize = 1_000;
+
+        let buffer = Arc::new(SharedBuffer::<Item>::new());
+        let retention = Retention::new(1_000, None).unwrap();
+
+        let writer_buffer = buffer.clone();
+        let writer = std::thread::spawn(move || {
+            for id in 0..REPLAYED {
+                writer_buffer.modify(|items| {
+                    items.push_front(item(id));
+                    retention.enforce(items);
+                });
+                if id % 100 == 0 {
+                    writer_buffer.publish();
+                }
+            }
+            writer_buffer.publish();
+        });
+
+        let (input_tx, input_rx) = std_mpsc::channel();
+        let input = std::thread::spawn(move || {
+            for event in 0..INPUT_EVENTS {
+                input_tx.send(event).unwrap();
+            }
+        });
+
+        let mut handled = 0;
+        let mut generations_seen = Vec::new();
+        while handled < INPUT_EVENTS || !writer.is_finished() {
+            let generation = buffer.generation();
+            let snapshot = buffer.snapshot();
+            assert!(snapshot.len() <= 1_000);
+            if generations_seen.last() != Some(&generation) {
+                generations_seen.push(generation);
+            }
+
+            while let Ok(_event) = input_rx.try_recv() {
+                handled += 1;
+            }
+        }
+
+        input.join().unwrap();
+        writer.join().unwrap();
+
+        assert_eq!(handled, INPUT_EVENTS);
+        assert_eq!(buffer.snapshot().first().map(|i| i.id), Some(REPLAYED - 1));
+        // The reader saw the replay progress while it ran
+        assert!(generations_seen.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(generations_seen.len() > 1, "saw generations {:?}", generations_seen);
+    }
+}
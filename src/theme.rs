@@ -0,0 +1,164 @@
+//! Colors the UI draws with, by role rather than by name, so the same
+//! screens stay readable on dark and light terminal backgrounds. The light
+//! palette swaps white text for black and the bright accents for darker
+//! 256-color shades; the colorblind one is the dark palette with blue and
+//! orange in place of green and red.
+
+use ratatui::style::Color;
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub light: bool,
+    /// Blue and orange in place of green and red, heatmap included
+    pub colorblind: bool,
+    /// Primary text and values
+    pub text: Color,
+    /// Labels, axes and help text
+    pub muted: Color,
+    /// Hints, placeholders and inactive borders
+    pub dim: Color,
+    /// Coin symbols, prices and the focused pane
+    pub highlight: Color,
+    /// Usernames and secondary accents
+    pub accent: Color,
+    /// Buys and gains
+    pub positive: Color,
+    /// Sells and losses
+    pub negative: Color,
+    /// Badges and the slower moving average
+    pub special: Color,
+    /// Background of the selected row
+    pub selection: Color,
+    /// Background of empty gauges
+    pub surface: Color,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        light: false,
+        colorblind: false,
+        text: Color::White,
+        muted: Color::Gray,
+        dim: Color::DarkGray,
+        highlight: Color::Yellow,
+        accent: Color::Cyan,
+        positive: Color::Green,
+        negative: Color::Red,
+        special: Color::Magenta,
+        selection: Color::DarkGray,
+        surface: Color::Black,
+    };
+
+    pub const LIGHT: Theme = Theme {
+        light: true,
+        colorblind: false,
+        text: Color::Black,
+        muted: Color::Indexed(240),
+        dim: Color::Indexed(245),
+        highlight: Color::Indexed(130),
+        accent: Color::Indexed(25),
+        positive: Color::Indexed(28),
+        negative: Color::Indexed(124),
+        special: Color::Indexed(90),
+        selection: Color::Indexed(252),
+        surface: Color::Indexed(254),
+    };
+
+    pub const COLORBLIND: Theme = Theme {
+        colorblind: true,
+        positive: Color::Indexed(33),
+        negative: Color::Indexed(208),
+        ..Theme::DARK
+    };
+
+    /// Palettes `:theme` switches between, by name.
+    pub const NAMED: [(&'static str, Theme); 3] = [("dark", Theme::DARK), ("light", Theme::LIGHT), ("colorblind", Theme::COLORBLIND)];
+
+    pub fn named(name: &str) -> Option<Theme> {
+        Self::NAMED
+            .iter()
+            .find(|(theme_name, _)| theme_name.eq_ignore_ascii_case(name))
+            .map(|&(_, theme)| theme)
+    }
+
+    pub fn name(&self) -> &'static str {
+        Self::NAMED
+            .iter()
+            .find(|(_, theme)| theme == self)
+            .map_or("custom", |&(name, _)| name)
+    }
+
+    /// The light theme when asked for with `--light` or when `COLORFGBG`
+    /// reports a light background, the dark one otherwise.
+    pub fn detect(force_light: bool) -> Theme {
+        let light = force_light || env::var("COLORFGBG").ok().and_then(|value| is_light_background(&value)).unwrap_or(false);
+        if light {
+            Theme::LIGHT
+        } else {
+            Theme::DARK
+        }
+    }
+
+    /// Heatmap cell for a net flow of `intensity` (0 to 1) of the full
+    /// scale. Faint cells stay visible against the terminal's background.
+    pub fn heat(&self, positive: bool, intensity: f64) -> Color {
+        let intensity = intensity.clamp(0.0, 1.0);
+        if self.light {
+            // Pale tints deepening to the full color
+            let fade = (200.0 * (1.0 - intensity)) as u8;
+            let level = (255.0 - intensity * 95.0) as u8;
+            if positive {
+                Color::Rgb(fade, level, fade)
+            } else {
+                Color::Rgb(level, fade, fade)
+            }
+        } else {
+            let level = (60.0 + intensity * 195.0) as u8;
+            match (positive, self.colorblind) {
+                (true, false) => Color::Rgb(0, level, 0),
+                (false, false) => Color::Rgb(level, 0, 0),
+                (true, true) => Color::Rgb(0, level / 2, level),
+                (false, true) => Color::Rgb(level, level / 2, 0),
+            }
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
+
+/// Reads the background from a `COLORFGBG` value such as `15;0` or
+/// `0;default;15`: the last field is the ANSI background color, where 7
+/// and 9-15 are light. `None` when it doesn't say.
+pub fn is_light_background(colorfgbg: &str) -> Option<bool> {
+    let background: u8 = colorfgbg.rsplit(';').next()?.trim().parse().ok()?;
+    Some(background == 7 || (9..=15).contains(&background))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colorfgbg_background_decides_light_or_dark() {
+        assert_eq!(is_light_background("0;15"), Some(true));
+        assert_eq!(is_light_background("0;default;7"), Some(true));
+        assert_eq!(is_light_background("15;0"), Some(false));
+        assert_eq!(is_light_background("7;8"), Some(false));
+        assert_eq!(is_light_background("15;default"), None);
+        assert_eq!(is_light_background(""), None);
+    }
+
+    #[test]
+    fn palettes_are_found_by_name() {
+        assert_eq!(Theme::named("Colorblind"), Some(Theme::COLORBLIND));
+        assert_eq!(Theme::named("sepia"), None);
+        for (name, theme) in Theme::NAMED {
+            assert_eq!(theme.name(), name);
+        }
+    }
+}
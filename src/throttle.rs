@@ -0,0 +1,212 @@
+//! Per-coin coalescing of price updates on their way into the history
+//! buffer. A hot coin can send many updates a second; with a throttle
+//! interval only one of them per coin per interval is kept, the latest, so
+//! the 100-entry history isn't churned by a single coin. The latest-price
+//! map sees every update as it arrives, so the current price stays live.
+
+use crate::models::PriceUpdate;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+
+#[derive(Debug, Default)]
+struct CoinSlot {
+    last_flush: Option<Instant>,
+    pending: Option<PriceUpdate>,
+}
+
+#[derive(Debug)]
+pub struct PriceThrottle {
+    interval: Duration,
+    coins: HashMap<String, CoinSlot>,
+}
+
+impl PriceThrottle {
+    /// An interval of zero lets every update straight through.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            coins: HashMap::new(),
+        }
+    }
+
+    /// Returns the updates held back under the old interval that are due
+    /// under the new one; all of them when coalescing is turned off.
+    pub fn set_interval(&mut self, interval: Duration, now: Instant) -> Vec<PriceUpdate> {
+        self.interval = interval;
+        self.take_due(now)
+    }
+
+    /// Returns `update` when it can go into the history now, otherwise holds
+    /// it in place of any update of the same coin already held.
+    pub fn offer(&mut self, update: PriceUpdate, now: Instant) -> Option<PriceUpdate> {
+        if self.interval.is_zero() {
+            return Some(update);
+        }
+        let slot = self.coins.entry(update.coin_symbol.clone()).or_default();
+        let idle = slot.last_flush.is_none_or(|at| now.duration_since(at) >= self.interval);
+        if idle && slot.pending.is_none() {
+            slot.last_flush = Some(now);
+            Some(update)
+        } else {
+            slot.pending = Some(update);
+            None
+        }
+    }
+
+    /// Held updates whose coin's interval has passed, oldest first.
+    pub fn take_due(&mut self, now: Instant) -> Vec<PriceUpdate> {
+        let mut due: Vec<PriceUpdate> = Vec::new();
+        for slot in self.coins.values_mut() {
+            let ready = slot.last_flush.is_none_or(|at| now.duration_since(at) >= self.interval);
+            if ready {
+                if let Some(update) = slot.pending.take() {
+                    slot.last_flush = Some(now);
+                    due.push(update);
+                }
+            }
+        }
+        due.sort_by_key(|update| update.received_at);
+        due
+    }
+
+    /// Every held update, for when the feed closes.
+    pub fn take_all(&mut self) -> Vec<PriceUpdate> {
+        let mut held: Vec<PriceUpdate> = self.coins.values_mut().filter_map(|slot| slot.pending.take()).collect();
+        held.sort_by_key(|update| update.received_at);
+        held
+    }
+
+    /// When the next held update is due, if any is held.
+    pub fn next_due(&self) -> Option<Instant> {
+        // Only a coin that has flushed recently holds an update back
+        self.coins
+            .values()
+            .filter_map(|slot| slot.pending.as_ref().and(slot.last_flush))
+            .map(|at| at + self.interval)
+            .min()
+    }
+}
+
+/// Passes price updates from `rx` to `tx` through a [`PriceThrottle`] whose
+/// interval follows `interval`. `on_update` sees every update as it arrives
+/// and returns whether to keep it at all (duplicates aren't).
+pub async fn run(
+    mut rx: mpsc::Receiver<PriceUpdate>,
+    tx: mpsc::Sender<PriceUpdate>,
+    mut interval: watch::Receiver<Duration>,
+    mut on_update: impl FnMut(&PriceUpdate) -> bool,
+) {
+    let mut throttle = PriceThrottle::new(*interval.borrow_and_update());
+    loop {
+        let next_due = throttle.next_due();
+        let ready = tokio::select! {
+            update = rx.recv() => {
+                let Some(update) = update else { break };
+                if !on_update(&update) {
+                    continue;
+                }
+                throttle.offer(update, Instant::now()).into_iter().collect()
+            }
+            _ = tokio::time::sleep_until(next_due.unwrap_or_else(Instant::now)), if next_due.is_some() => {
+                throttle.take_due(Instant::now())
+            }
+            Ok(()) = interval.changed() => {
+                let new_interval = *interval.borrow_and_update();
+                throttle.set_interval(new_interval, Instant::now())
+            }
+        };
+        for update in ready {
+            if tx.send(update).await.is_err() {
+                return;
+            }
+        }
+    }
+    for update in throttle.take_all() {
+        if tx.send(update).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn price(coin_symbol: &str, cents: i64) -> PriceUpdate {
+        PriceUpdate {
+            current_price: Decimal::new(cents, 2),
+            ..PriceUpdate::sample(coin_symbol)
+        }
+    }
+
+    fn prices(updates: Vec<PriceUpdate>) -> Vec<(String, i64)> {
+        updates
+            .into_iter()
+            .map(|update| (update.coin_symbol, update.current_price.mantissa() as i64))
+            .collect()
+    }
+
+    #[test]
+    fn a_burst_collapses_to_its_first_and_last_update_per_coin() {
+        let start = Instant::now();
+        let mut throttle = PriceThrottle::new(Duration::from_secs(1));
+        assert!(throttle.offer(price("PEPE", 1), start).is_some());
+        assert!(throttle.offer(price("DOGE", 50), start).is_some());
+        for cents in 2..=9 {
+            assert!(throttle.offer(price("PEPE", cents), start + Duration::from_millis(cents as u64 * 10)).is_none());
+        }
+        assert_eq!(throttle.next_due(), Some(start + Duration::from_secs(1)));
+        assert!(throttle.take_due(start + Duration::from_millis(999)).is_empty());
+
+        // The last update of the burst wins, and DOGE had nothing held back
+        assert_eq!(prices(throttle.take_due(start + Duration::from_secs(1))), [("PEPE".to_string(), 9)]);
+        assert_eq!(throttle.next_due(), None);
+
+        // A quiet coin's next update goes straight through again
+        assert!(throttle.offer(price("DOGE", 51), start + Duration::from_secs(1)).is_some());
+        assert!(throttle.offer(price("PEPE", 10), start + Duration::from_millis(1500)).is_none());
+        assert_eq!(prices(throttle.take_all()), [("PEPE".to_string(), 10)]);
+    }
+
+    #[test]
+    fn a_zero_interval_lets_everything_through() {
+        let start = Instant::now();
+        let mut throttle = PriceThrottle::new(Duration::from_secs(1));
+        throttle.offer(price("PEPE", 1), start);
+        throttle.offer(price("PEPE", 2), start);
+        // Turning coalescing off releases what it held
+        assert_eq!(prices(throttle.set_interval(Duration::ZERO, start)), [("PEPE".to_string(), 2)]);
+        for cents in 3..6 {
+            assert!(throttle.offer(price("PEPE", cents), start).is_some());
+        }
+        assert_eq!(throttle.next_due(), None);
+    }
+
+    #[tokio::test]
+    async fn run_forwards_the_last_update_of_a_burst() {
+        let (in_tx, in_rx) = mpsc::channel(16);
+        let (out_tx, mut out_rx) = mpsc::channel(16);
+        let (_interval_tx, interval_rx) = watch::channel(Duration::from_secs(60));
+        let mut seen = 0;
+        let task = tokio::spawn(run(in_rx, out_tx, interval_rx, move |_| {
+            seen += 1;
+            seen != 3
+        }));
+        for cents in 1..=5 {
+            in_tx.send(price("PEPE", cents)).await.unwrap();
+        }
+        drop(in_tx);
+        task.await.unwrap();
+
+        // The first goes straight in, the rest collapse into the last; the
+        // one `on_update` turned down never counts
+        let mut forwarded = Vec::new();
+        while let Some(update) = out_rx.recv().await {
+            forwarded.push(update);
+        }
+        assert_eq!(prices(forwarded), [("PEPE".to_string(), 1), ("PEPE".to_string(), 5)]);
+    }
+}
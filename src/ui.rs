@@ -1,54 +1,333 @@
-use crate::app::App;
-use crate::models::{AppPage, InputMode, TradeFilter};
+use crate::app::{self, App, OrderField, QuickFilter, QuickFilterRegion, ScrollbarTrack};
+use crate::cli;
+use crate::command;
+use crate::events::Severity;
+use crate::history::Resolution;
+use crate::keymap;
+use crate::models::{lossy_f64, round_to, AppPage, ClearScope, GroupedTrade, InputMode, MuteKind, PriceRange, PriceUpdate, TradeFilter};
+use crate::portfolio::Side;
+use crate::row_template::{RowTemplate, TemplatePart, TradeField};
+use crate::stats::{MovingAverage, SIZE_BUCKET_LABELS};
+use crate::theme::Theme;
+use crate::websocket::FeedState;
+use chrono::{DateTime, Local};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
+    symbols,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Tabs},
+    widgets::{
+        Axis, Bar, BarChart, BarGroup, Block, Borders, Cell, Chart, Clear, Dataset, Gauge, GraphType, List, ListItem, Paragraph, Row, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Table, Tabs, Wrap,
+    },
     Frame,
 };
+use rust_decimal::Decimal;
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 pub fn draw(f: &mut Frame, app: &App) {
+    // One clock reading per frame keeps every relative timestamp consistent
+    let now = Local::now();
+    app.begin_frame(now);
+    app.scrollbar_tracks.borrow_mut().clear();
+    app.quick_filter_regions.borrow_mut().clear();
+    app.follow_pane_area.set(None);
+    app.split_panes.set(None);
+    app.viewport_top.set(f.area().y);
+
+    let too_small = f.area().width < MIN_WIDTH || f.area().height < MIN_HEIGHT;
+    app.too_small.set(too_small);
+    if too_small {
+        app.page_tabs.borrow_mut().clear();
+        draw_too_small(f, app);
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),  // Page tabs
             Constraint::Length(3),  // Content-specific area (filters or coin selection)
             Constraint::Min(0),     // Main content
+            Constraint::Length(u16::from(app.status_bar)),
             Constraint::Length(3),  // Help
         ])
         .split(f.area());
 
     draw_page_tabs(f, app, chunks[0]);
     
-    match app.current_page {
-        AppPage::Trades => {
-            draw_filters(f, app, chunks[1]);
-            draw_trades(f, app, chunks[2]);
-        }
-        AppPage::PriceTracker => {
-            draw_coin_selection(f, app, chunks[1]);
-            draw_price_tracker(f, app, chunks[2]);
+    if app.split_active(f.area().width) {
+        // Trades on the left, tracked coin on the right; the current page
+        // decides which pane keys and scrolling apply to
+        let panes = [Constraint::Percentage(app.split_percent), Constraint::Percentage(100 - app.split_percent)];
+        let top = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(panes)
+            .split(chunks[1]);
+        let main = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(panes)
+            .split(chunks[2]);
+        app.split_panes.set(Some((top[0].union(main[0]), top[1].union(main[1]))));
+        draw_filters(f, app, top[0]);
+        draw_coin_selection(f, app, top[1]);
+        draw_trades(f, app, main[0], now);
+        draw_price_tracker(f, app, main[1], now);
+    } else {
+        match app.current_page {
+            AppPage::Trades => {
+                draw_filters(f, app, chunks[1]);
+                draw_trades(f, app, chunks[2], now);
+            }
+            AppPage::PriceTracker => {
+                draw_coin_selection(f, app, chunks[1]);
+                draw_price_tracker(f, app, chunks[2], now);
+            }
+            AppPage::Portfolio => {
+                draw_portfolio_summary(f, app, chunks[1]);
+                draw_portfolio(f, app, chunks[2]);
+            }
         }
     }
     
-    draw_help(f, app, chunks[3]);
+    if app.status_bar {
+        draw_status_bar(f, app, chunks[3]);
+    }
+    draw_help(f, app, chunks[4]);
+
+    match app.input_mode {
+        InputMode::MuteList => draw_mute_list(f, app),
+        InputMode::FollowList => draw_follow_list(f, app),
+        InputMode::BookmarkList => draw_bookmark_list(f, app),
+        InputMode::EventLog => draw_event_log(f, app),
+        InputMode::CoinList => draw_coin_list(f, app),
+        InputMode::Help => draw_help_overlay(f, app),
+        InputMode::ConfirmQuit => draw_quit_confirmation(f, app),
+        InputMode::CoinStats => draw_coin_stats(f, app, now),
+        InputMode::PositionEntry => draw_position_entry(f, app),
+        InputMode::OrderEntry => draw_order_form(f, app),
+        _ => {}
+    }
+
+    // Nothing has happened for a while; see `App::update_idle`
+    if app.idle {
+        let area = f.area();
+        f.buffer_mut().set_style(area, Style::default().fg(app.theme.dim).add_modifier(Modifier::DIM));
+    }
+}
+
+// Below this the panels can't be laid out usefully
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 10;
+
+fn draw_too_small(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let message = vec![
+        Line::from(Span::styled("Terminal too small", Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD))),
+        Line::from(format!("{}x{}, needs {}x{}", area.width, area.height, MIN_WIDTH, MIN_HEIGHT)),
+    ];
+    // Vertically centered when there's room for it
+    let top = area.height.saturating_sub(message.len() as u16) / 2;
+    let area = Rect { y: area.y + top, height: area.height - top, ..area };
+    f.render_widget(Paragraph::new(message).alignment(Alignment::Center).wrap(Wrap { trim: true }), area);
+}
+
+/// Border style of a pane's main list: highlighted for the focused pane in
+/// split view, plain otherwise.
+fn pane_border_style(app: &App, page: AppPage) -> Style {
+    if app.split_panes.get().is_some() && app.current_page == page {
+        Style::default().fg(app.theme.accent)
+    } else {
+        Style::default()
+    }
 }
 
 fn draw_page_tabs(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    let page_tabs = vec!["Trade Monitor", "Price Tracker"];
+    // The badge only matters while the trades aren't on screen
+    let unseen = if app.split_active(area.width) { 0 } else { app.unseen_trade_count() };
+    let trades_tab = if unseen > 0 {
+        format!("Trade Monitor (+{})", unseen)
+    } else {
+        "Trade Monitor".to_string()
+    };
+    let page_tabs = vec![trades_tab, "Price Tracker".to_string(), "Portfolio".to_string()];
     let selected_page = match app.current_page {
         AppPage::Trades => 0,
         AppPage::PriceTracker => 1,
+        AppPage::Portfolio => 2,
     };
+    // Each tab is padded by a space on both sides and followed by a
+    // one-column divider
+    let mut x = area.x + 1;
+    let mut tab_columns = app.page_tabs.borrow_mut();
+    tab_columns.clear();
+    for (label, page) in page_tabs.iter().zip([AppPage::Trades, AppPage::PriceTracker, AppPage::Portfolio]) {
+        let width = label.chars().count() as u16 + 2;
+        tab_columns.push((x..x + width, page));
+        x += width + 1;
+    }
+    let mut block = Block::default().borders(Borders::ALL).title("Pages");
+    if app.is_paused() {
+        block = block.title(Line::from(Span::styled(
+            format!(" PAUSED ({} queued) - P: Resume ", app.queued_count()),
+            Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )).alignment(Alignment::Right));
+    }
     let tabs_widget = Tabs::new(page_tabs)
-        .block(Block::default().borders(Borders::ALL).title("Pages"))
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .block(block)
+        .style(Style::default().fg(app.theme.text))
+        .highlight_style(Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD))
         .select(selected_page);
     f.render_widget(tabs_widget, area);
 }
 
+fn pnl_color(theme: &Theme, pnl: Decimal) -> Color {
+    if pnl > Decimal::ZERO {
+        theme.positive
+    } else if pnl < Decimal::ZERO {
+        theme.negative
+    } else {
+        theme.text
+    }
+}
+
+fn draw_portfolio_summary(f: &mut Frame, app: &App, area: Rect) {
+    let summary = app.portfolio_summary();
+    let label = Style::default().fg(app.theme.muted);
+    let mut spans = vec![
+        Span::styled("Value: ", label),
+        Span::raw(format!("${}", format_decimal(summary.market_value, 2))),
+        Span::styled(" | Cost: ", label),
+        Span::raw(format!("${}", format_decimal(summary.cost, 2))),
+        Span::styled(" | Unrealized: ", label),
+        Span::styled(format!("{:+.2}", round_to(summary.unrealized_pnl, 2)), Style::default().fg(pnl_color(&app.theme, summary.unrealized_pnl))),
+        Span::styled(" | Realized: ", label),
+        Span::styled(format!("{:+.2}", round_to(summary.realized_pnl, 2)), Style::default().fg(pnl_color(&app.theme, summary.realized_pnl))),
+        Span::styled(" | Total P/L: ", label),
+        Span::styled(
+            format!("{:+.2}", round_to(summary.total_pnl(), 2)),
+            Style::default().fg(pnl_color(&app.theme, summary.total_pnl())).add_modifier(Modifier::BOLD),
+        ),
+    ];
+    if summary.unpriced > 0 {
+        spans.push(Span::styled(
+            format!("  ({} without a price yet, at cost)", summary.unpriced),
+            Style::default().fg(app.theme.dim),
+        ));
+    }
+    let paragraph = Paragraph::new(Line::from(spans))
+        .block(Block::default().borders(Borders::ALL).title("Paper Portfolio (b: Buy | s: Sell)"));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_portfolio(f: &mut Frame, app: &App, area: Rect) {
+    let holdings = &app.portfolio.holdings;
+    // Borders plus the header row, with at least one row for an empty table
+    let holdings_height = (holdings.len().max(1) as u16 + 3).min(area.height / 2);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(holdings_height), Constraint::Min(0)])
+        .split(area);
+
+    let right = |text: String| Cell::from(Line::from(text).alignment(Alignment::Right));
+    let rows: Vec<Row> = holdings
+        .iter()
+        .map(|(coin, holding)| {
+            let price = app.latest_price_of(coin);
+            let mut cells = vec![
+                Cell::from(coin.as_str()).style(Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)),
+                right(format_decimal(holding.quantity, 4)),
+                right(format!("${}", format_price(holding.average_entry))),
+            ];
+            match price {
+                Some(price) => {
+                    let pnl = holding.unrealized_pnl(price);
+                    let percent = (lossy_f64(price) / lossy_f64(holding.average_entry) - 1.0) * 100.0;
+                    cells.push(right(format!("${}", format_price(price))));
+                    cells.push(right(format!("${}", format_decimal(holding.quantity.saturating_mul(price), 2))));
+                    cells.push(right(format!("{:+.2}", round_to(pnl, 2))).style(Style::default().fg(pnl_color(&app.theme, pnl))));
+                    cells.push(right(format!("{:+.2}%", percent)).style(Style::default().fg(pnl_color(&app.theme, pnl))));
+                }
+                None => cells.push(Cell::from("waiting for a price").style(Style::default().fg(app.theme.dim))),
+            }
+            Row::new(cells)
+        })
+        .collect();
+    let header = Row::new(vec![
+        Cell::from("Coin"),
+        right("Quantity".to_string()),
+        right("Avg Entry".to_string()),
+        right("Price".to_string()),
+        right("Value".to_string()),
+        right("Unrealized".to_string()),
+        right("P/L %".to_string()),
+    ])
+    .style(Style::default().fg(app.theme.muted).add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Length(16),
+        Constraint::Length(16),
+        Constraint::Length(16),
+        Constraint::Length(14),
+        Constraint::Length(14),
+        Constraint::Min(9),
+    ];
+    let table = if rows.is_empty() {
+        Table::new(
+            vec![Row::new(vec![Cell::from("No paper positions - press b to buy at the latest price")])
+                .style(Style::default().fg(app.theme.muted))],
+            [Constraint::Min(0)],
+        )
+    } else {
+        Table::new(rows, widths).header(header)
+    };
+    let block = Block::default().borders(Borders::ALL).title(format!("Positions ({})", holdings.len()));
+    f.render_widget(table.block(block), chunks[0]);
+
+    let orders = &app.portfolio.orders;
+    let visible_height = chunks[1].height.saturating_sub(2) as usize;
+    let start_idx = app.scroll_offset.min(orders.len());
+    let end_idx = (start_idx + visible_height).min(orders.len());
+    let items: Vec<ListItem> = if orders.is_empty() {
+        vec![ListItem::new(Span::styled("No paper orders yet", Style::default().fg(app.theme.muted)))]
+    } else {
+        orders[start_idx..end_idx]
+            .iter()
+            .enumerate()
+            .map(|(i, order)| {
+                let side_color = match order.side {
+                    Side::Buy => app.theme.positive,
+                    Side::Sell => app.theme.negative,
+                };
+                let mut spans = vec![
+                    Span::styled(format!("[{} {}] ", order.at.format("%m-%d"), order.at.format(app.time_format())), Style::default().fg(app.theme.dim)),
+                    Span::styled(format!("{:<4} ", order.side.label()), Style::default().fg(side_color).add_modifier(Modifier::BOLD)),
+                    Span::raw(format!("{} ", format_decimal(order.quantity, 4))),
+                    Span::styled(order.coin.as_str(), Style::default().fg(app.theme.highlight)),
+                    Span::raw(format!(" @ ${} = ${}", format_price(order.price), format_decimal(order.value, 2))),
+                ];
+                if order.side == Side::Sell {
+                    spans.push(Span::styled(
+                        format!("  P/L {:+.2}", round_to(order.realized_pnl, 2)),
+                        Style::default().fg(pnl_color(&app.theme, order.realized_pnl)),
+                    ));
+                }
+                let item = ListItem::new(Line::from(spans));
+                if i == 0 {
+                    item.style(selected_row_style(&app.theme))
+                } else {
+                    item
+                }
+            })
+            .collect()
+    };
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(format!("Paper Orders ({})", orders.len())));
+    f.render_widget(list, chunks[1]);
+    draw_scrollbar(f, app, AppPage::Portfolio, chunks[1], orders.len(), start_idx, visible_height);
+}
+
 fn draw_coin_selection(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     let coin_text = if app.input_mode == InputMode::CoinSelection {
         &app.input_buffer
@@ -57,9 +336,9 @@ fn draw_coin_selection(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     };
 
     let coin_style = if app.input_mode == InputMode::CoinSelection {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(app.theme.highlight)
     } else {
-        Style::default().fg(Color::White)
+        Style::default().fg(app.theme.text)
     };
 
     let coin_selection = Paragraph::new(coin_text)
@@ -68,11 +347,25 @@ fn draw_coin_selection(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     f.render_widget(coin_selection, area);
 }
 
-fn draw_price_tracker(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+fn draw_price_tracker(f: &mut Frame, app: &App, area: ratatui::layout::Rect, now: DateTime<Local>) {
+    if app.all_coins_view {
+        if area.width < LEADERS_MIN_WIDTH {
+            draw_all_coins_table(f, app, area, now);
+            return;
+        }
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(LEADERS_WIDTH)])
+            .split(area);
+        draw_all_coins_table(f, app, columns[0], now);
+        draw_price_leaders(f, app, columns[1]);
+        return;
+    }
+
     if app.tracked_coin.is_none() {
         let help_text = Paragraph::new("Press 's' to select a coin to track")
-            .block(Block::default().borders(Borders::ALL).title("Price Tracker"))
-            .style(Style::default().fg(Color::Gray));
+            .block(Block::default().borders(Borders::ALL).border_style(pane_border_style(app, AppPage::PriceTracker)).title("Price Tracker"))
+            .style(Style::default().fg(app.theme.muted));
         f.render_widget(help_text, area);
         return;
     }
@@ -80,135 +373,672 @@ fn draw_price_tracker(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(8),  // Current price info
+            Constraint::Length(if app.tracked_position().is_some() { 9 } else { 8 }),  // Current price info
+            Constraint::Length(3),  // Buy/sell gauge
+            Constraint::Length(3),  // Net flow bar
             Constraint::Min(0),     // Price history
         ])
         .split(area);
 
     // Draw current price info
-    draw_current_price(f, app, chunks[0]);
+    draw_current_price(f, app, chunks[0], now);
+
+    draw_buy_sell_gauge(f, app, chunks[1]);
+    draw_net_flow_bar(f, app, chunks[2], now);
     
     // Draw price history
-    draw_price_history(f, app, chunks[1]);
+    if app.price_chart {
+        draw_price_chart(f, app, chunks[3]);
+    } else {
+        draw_price_history(f, app, chunks[3], now);
+    }
+}
+
+const LEADERS_COUNT: usize = 5;
+const LEADERS_WIDTH: u16 = 30;
+/// Narrower Price Tracker areas show the all-coins table without the
+/// leader panels.
+const LEADERS_MIN_WIDTH: u16 = 110;
+
+/// "Top movers" and "Volume leaders" panels beside the all-coins table. The
+/// arrow compares each coin's latest price with the first one this session.
+fn draw_price_leaders(f: &mut Frame, app: &App, area: Rect) {
+    let panel_height = LEADERS_COUNT as u16 + 2;
+    let panels = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(panel_height), Constraint::Length(panel_height), Constraint::Min(0)])
+        .split(area);
+
+    let session = app.session_stats.lock().unwrap();
+    let leader_line = |update: &PriceUpdate, figure: Span<'static>| {
+        let (arrow, color) = match session.price_span(&update.coin_symbol).map(|span| span.change()) {
+            Some(change) if change > Decimal::ZERO => ("▲", app.theme.positive),
+            Some(change) if change < Decimal::ZERO => ("▼", app.theme.negative),
+            _ => ("•", app.theme.dim),
+        };
+        Line::from(vec![
+            Span::styled(format!("{} ", arrow), Style::default().fg(color)),
+            Span::styled(fit_to_width(&update.coin_symbol, 10), Style::default().fg(app.theme.highlight)),
+            figure,
+        ])
+    };
+    let placeholder = || vec![Line::from(Span::styled("No prices yet", Style::default().fg(app.theme.muted)))];
+
+    let movers: Vec<Line> = session
+        .top_movers(LEADERS_COUNT)
+        .into_iter()
+        .map(|update| {
+            let color = change_color(&app.theme, update.change_24h);
+            leader_line(update, Span::styled(format!("{:>13}", format_change(update.change_24h)), Style::default().fg(color)))
+        })
+        .collect();
+    let volume: Vec<Line> = session
+        .volume_leaders(LEADERS_COUNT)
+        .into_iter()
+        .map(|update| leader_line(update, Span::raw(format!("{:>13}", format!("${}", format_decimal(update.volume_24h, 0))))))
+        .collect();
+
+    let movers = if movers.is_empty() { placeholder() } else { movers };
+    let volume = if volume.is_empty() { placeholder() } else { volume };
+    f.render_widget(Paragraph::new(movers).block(Block::default().borders(Borders::ALL).title("Top Movers (24h)")), panels[0]);
+    f.render_widget(Paragraph::new(volume).block(Block::default().borders(Borders::ALL).title("Volume Leaders")), panels[1]);
+}
+
+/// Latest price of every coin on the feed, one row each. The top row is the
+/// selection, like the other lists.
+fn draw_all_coins_table(f: &mut Frame, app: &App, area: Rect, now: DateTime<Local>) {
+    let rows = app.coin_table();
+    let mut session = app.session_stats.lock().unwrap();
+    // Borders plus the header row
+    let visible_height = area.height.saturating_sub(3) as usize;
+    let start_idx = app.pane_scroll_offset(&AppPage::PriceTracker).min(rows.len());
+    let end_idx = (start_idx + visible_height).min(rows.len());
+
+    let table_rows: Vec<Row> = rows[start_idx..end_idx]
+        .iter()
+        .enumerate()
+        .map(|(i, update)| {
+            let change_color = change_color(&app.theme, update.change_24h);
+            let (net_flow, _) = session.net_flow(&update.coin_symbol, now);
+            let row = Row::new(vec![
+                Cell::from(update.coin_symbol.as_str())
+                    .style(Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)),
+                Cell::from(Line::from(format!("${}{}", format_price(update.current_price), repaired_mark(update.repaired))).alignment(Alignment::Right)),
+                Cell::from(Line::from(format_change(update.change_24h)).alignment(Alignment::Right))
+                    .style(Style::default().fg(change_color)),
+                Cell::from(Line::from(format!("${}", format_decimal(update.volume_24h, 2))).alignment(Alignment::Right)),
+                Cell::from(Line::from(format!("${}", format_decimal(update.market_cap, 2))).alignment(Alignment::Right)),
+                Cell::from(Line::from(format_net_flow(net_flow)).alignment(Alignment::Right))
+                    .style(Style::default().fg(net_flow_color(&app.theme, net_flow))),
+                Cell::from(relative_time(update.received_at, now)).style(Style::default().fg(app.theme.dim)),
+            ]);
+            if i == 0 {
+                row.style(selected_row_style(&app.theme))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let header = Row::new(vec![
+        Cell::from("Coin"),
+        Cell::from(Line::from("Price").alignment(Alignment::Right)),
+        Cell::from(Line::from("24h").alignment(Alignment::Right)),
+        Cell::from(Line::from("Volume 24h").alignment(Alignment::Right)),
+        Cell::from(Line::from("Market Cap").alignment(Alignment::Right)),
+        Cell::from(Line::from(format!("Net Flow {}", session.net_flow_window().label())).alignment(Alignment::Right)),
+        Cell::from("Updated"),
+    ])
+    .style(Style::default().fg(app.theme.muted).add_modifier(Modifier::BOLD));
+
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Length(18),
+        Constraint::Length(10),
+        Constraint::Length(18),
+        Constraint::Length(18),
+        Constraint::Length(14),
+        Constraint::Min(10),
+    ];
+
+    let title = format!(
+        "All Coins ({}) - Sort: {} | o: Sort | Enter: Track | a: Back",
+        rows.len(),
+        app.coin_sort.label()
+    );
+    let table = if rows.is_empty() {
+        Table::new(
+            vec![Row::new(vec![Cell::from("Waiting for price updates...")]).style(Style::default().fg(app.theme.muted))],
+            [Constraint::Min(0)],
+        )
+    } else {
+        Table::new(table_rows, widths).header(header)
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(pane_border_style(app, AppPage::PriceTracker))
+        .title(title);
+    f.render_widget(table.block(block), area);
+    draw_scrollbar(f, app, AppPage::PriceTracker, area, rows.len(), start_idx, visible_height);
+}
+
+const BUY_SELL_WINDOW: usize = 50;
+
+fn net_flow_color(theme: &Theme, net_flow: Decimal) -> Color {
+    if net_flow > Decimal::ZERO {
+        theme.positive
+    } else if net_flow < Decimal::ZERO {
+        theme.negative
+    } else {
+        theme.dim
+    }
+}
+
+/// Dollar amount with a K/M/B/T suffix, e.g. $1.25K or $3.40M. Anything
+/// past trillions switches to an exponent.
+pub fn format_compact_value(value: f64) -> String {
+    let magnitude = value.abs();
+    if !magnitude.is_finite() {
+        return format!("${}", NOT_A_NUMBER);
+    }
+    let (scaled, suffix) = if magnitude >= 1e15 {
+        return format!("${:.2e}", magnitude);
+    } else if magnitude >= 1e12 {
+        (magnitude / 1e12, "T")
+    } else if magnitude >= 1e9 {
+        (magnitude / 1e9, "B")
+    } else if magnitude >= 1e6 {
+        (magnitude / 1e6, "M")
+    } else if magnitude >= 1e3 {
+        (magnitude / 1e3, "K")
+    } else {
+        (magnitude, "")
+    };
+    format!("${:.2}{}", scaled, suffix)
+}
+
+/// Signed compact dollar amount, e.g. +$1.25K or -$3.40M.
+pub fn format_net_flow(net_flow: Decimal) -> String {
+    let sign = if net_flow > Decimal::ZERO {
+        "+"
+    } else if net_flow < Decimal::ZERO {
+        "-"
+    } else {
+        ""
+    };
+    format!("{}{}", sign, format_compact_value(lossy_f64(net_flow)))
+}
+
+/// Net flow of the tracked coin as a bar growing right (green, net buying)
+/// or left (red, net selling) from the middle, scaled by the value traded
+/// in the window.
+fn draw_net_flow_bar(f: &mut Frame, app: &App, area: Rect, now: DateTime<Local>) {
+    let coin = app.tracked_coin.as_deref().unwrap_or_default();
+    let mut session = app.session_stats.lock().unwrap();
+    let (net_flow, gross) = session.net_flow(coin, now);
+    let title = format!(
+        "Net Flow - last {}: {} of {} traded",
+        session.net_flow_window().label(),
+        format_net_flow(net_flow),
+        format_compact_value(lossy_f64(gross)),
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(title, Style::default().fg(net_flow_color(&app.theme, net_flow))));
+
+    let half = (area.width.saturating_sub(3) / 2) as usize;
+    let filled = if gross > Decimal::ZERO {
+        ((lossy_f64(net_flow.abs()) / lossy_f64(gross)) * half as f64).round() as usize
+    } else {
+        0
+    };
+    let (left, right) = if net_flow < Decimal::ZERO { (filled, 0) } else { (0, filled) };
+    let bar = Line::from(vec![
+        Span::raw(" ".repeat(half - left)),
+        Span::styled("█".repeat(left), Style::default().fg(app.theme.negative)),
+        Span::styled("┃", Style::default().fg(app.theme.muted)),
+        Span::styled("█".repeat(right), Style::default().fg(app.theme.positive)),
+    ]);
+    f.render_widget(Paragraph::new(bar).block(block), area);
+}
+
+/// Share of buy value (green) against sell value (red) over the tracked
+/// coin's most recent trades.
+fn draw_buy_sell_gauge(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let coin = app.tracked_coin.as_deref().unwrap_or_default();
+    let (buys, sells) = app.buy_sell_ratio(coin, BUY_SELL_WINDOW);
+    let total = buys.saturating_add(sells);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Buy/Sell Value - last {} trades", BUY_SELL_WINDOW));
+
+    let gauge = if total > Decimal::ZERO {
+        let buy_ratio = lossy_f64(buys) / lossy_f64(total);
+        Gauge::default()
+            .block(block)
+            .gauge_style(Style::default().fg(app.theme.positive).bg(app.theme.negative))
+            .ratio(buy_ratio)
+            .label(Span::styled(
+                format!("Buys {:.0}% / Sells {:.0}%", buy_ratio * 100.0, (1.0 - buy_ratio) * 100.0),
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            ))
+    } else {
+        Gauge::default()
+            .block(block)
+            .gauge_style(Style::default().fg(app.theme.dim).bg(app.theme.surface))
+            .ratio(0.5)
+            .label("No trades for this coin yet")
+    };
+    f.render_widget(gauge, area);
+}
+
+/// Picks one color out of the active theme.
+type ThemeColor = fn(&Theme) -> Color;
+
+/// Moving averages drawn over the price chart: window in samples, color and
+/// marker.
+const MOVING_AVERAGES: [(usize, ThemeColor, symbols::Marker); 2] = [
+    (20, |theme| theme.highlight, symbols::Marker::Braille),
+    (60, |theme| theme.special, symbols::Marker::Dot),
+];
+
+/// Price of the tracked coin over time, scaled to the observed range.
+fn draw_price_chart(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    // Older stretches come as bars; their last price stands for them
+    let history = app.tracked_price_history();
+    let price_updates: Vec<&PriceUpdate> = history.iter().map(|point| &point.update).collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(pane_border_style(app, AppPage::PriceTracker))
+        .title(format!("Price Chart [{}] ({}) - g: List | m: Averages", app.price_range().label(), price_updates.len()));
+
+    // Updates are newest-first
+    let (Some(newest), Some(oldest)) = (price_updates.first(), price_updates.last()) else {
+        f.render_widget(Paragraph::new(empty_history_message(app)).style(Style::default().fg(app.theme.muted)).block(block), area);
+        return;
+    };
+    if price_updates.len() < 2 {
+        f.render_widget(Paragraph::new("Waiting for more price data...").style(Style::default().fg(app.theme.muted)).block(block), area);
+        return;
+    }
+
+    let points: Vec<(f64, f64)> = price_updates
+        .iter()
+        .rev()
+        .map(|update| {
+            let secs = (update.received_at - oldest.received_at).num_milliseconds() as f64 / 1000.0;
+            (secs, lossy_f64(update.current_price))
+        })
+        .collect();
+    let x_max = points.last().map_or(0.0, |&(x, _)| x).max(1.0);
+
+    let (mut y_min, mut y_max) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(_, y)| (lo.min(y), hi.max(y)));
+    // Flat prices still need a visible band around the line
+    let padding = if y_max > y_min { (y_max - y_min) * 0.05 } else { y_max.abs().max(1e-12) * 0.01 };
+    y_min -= padding;
+    y_max += padding;
+
+    let mid_time = oldest.received_at + (newest.received_at - oldest.received_at) / 2;
+    let x_labels = vec![
+        Span::raw(oldest.received_at.format(app.time_format()).to_string()),
+        Span::raw(mid_time.format(app.time_format()).to_string()),
+        Span::raw(newest.received_at.format(app.time_format()).to_string()),
+    ];
+    let y_label = |y: f64| Span::raw(format_price(Decimal::try_from(y).unwrap_or_default()));
+    let y_labels = vec![y_label(y_min), y_label((y_min + y_max) / 2.0), y_label(y_max)];
+
+    let mut datasets = vec![Dataset::default()
+        .name("Price")
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(app.theme.accent))
+        .data(&points)];
+
+    // Computed up front because the datasets borrow the lines
+    let averages: Vec<(MovingAverage, Vec<(f64, f64)>)> = if app.moving_averages {
+        MOVING_AVERAGES
+            .iter()
+            .map(|&(window, _, _)| {
+                let mut average = MovingAverage::new(window);
+                let line = points.iter().map(|&(x, price)| (x, average.push(price))).collect();
+                (average, line)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut readout = Vec::new();
+    for ((average, line), &(window, color_of, marker)) in averages.iter().zip(MOVING_AVERAGES.iter()) {
+        let color = color_of(&app.theme);
+        datasets.push(
+            Dataset::default()
+                .name(format!("SMA {}", window))
+                .marker(marker)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(color))
+                .data(line),
+        );
+        let Some(&(_, current_average)) = line.last() else {
+            continue;
+        };
+        let distance = (lossy_f64(newest.current_price) / current_average - 1.0) * 100.0;
+        let side = if distance >= 0.0 { "above" } else { "below" };
+        // Until the window fills the average only covers what's been seen
+        let warm_up = if average.is_warm() {
+            String::new()
+        } else {
+            format!(" ({}/{})", average.sample_count(), window)
+        };
+        readout.push(Span::styled(
+            format!(" SMA {}{}: {} {:+.2}% ", window, warm_up, side, distance),
+            Style::default().fg(color),
+        ));
+    }
+    let block = if readout.is_empty() { block } else { block.title_bottom(Line::from(readout)) };
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .legend_position(Some(ratatui::widgets::LegendPosition::TopLeft))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(app.theme.muted))
+                .bounds([0.0, x_max])
+                .labels(x_labels),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(app.theme.muted))
+                .bounds([y_min, y_max])
+                .labels(y_labels),
+        );
+    f.render_widget(chart, area);
+}
+
+/// Shown in place of a NaN or infinite number.
+const NOT_A_NUMBER: &str = "—";
+
+/// Follows a value from a message that had missing or negative numbers set
+/// to 0, so it isn't taken at face value.
+const REPAIRED_MARK: &str = "?";
+
+fn repaired_mark(repaired: bool) -> &'static str {
+    if repaired {
+        REPAIRED_MARK
+    } else {
+        ""
+    }
+}
+
+/// `value` rounded to a fixed number of decimals, or with an exponent once
+/// it's too large to print digit by digit.
+pub fn format_decimal(value: Decimal, decimals: usize) -> String {
+    if lossy_f64(value).abs() >= 1e15 {
+        format!("{:.2e}", lossy_f64(value))
+    } else {
+        format!("{:.*}", decimals, round_to(value, decimals as u32))
+    }
+}
+
+/// A 24h change such as +1.25%. Negative zero reads as +0.00%.
+pub fn format_change(change: f64) -> String {
+    if change.is_finite() {
+        format!("{:+.2}%", change + 0.0)
+    } else {
+        NOT_A_NUMBER.to_string()
+    }
+}
+
+/// Green for a rise, red for a fall; a change that isn't a number is
+/// neither.
+fn change_color(theme: &Theme, change: f64) -> Color {
+    if !change.is_finite() {
+        theme.dim
+    } else if change >= 0.0 {
+        theme.positive
+    } else {
+        theme.negative
+    }
+}
+
+/// Enough decimals to show the significant digits of small prices without
+/// padding large ones, e.g. 1234.56, 0.5123 or 0.00001234.
+pub fn format_price(price: Decimal) -> String {
+    let magnitude = lossy_f64(price.abs());
+    let decimals = if magnitude >= 1000.0 || magnitude == 0.0 {
+        2
+    } else if magnitude >= 1.0 {
+        4
+    } else {
+        ((-magnitude.log10().floor()) as usize + 3).min(12)
+    };
+    format_decimal(price, decimals)
 }
 
-fn draw_current_price(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+fn draw_current_price(f: &mut Frame, app: &App, area: ratatui::layout::Rect, now: DateTime<Local>) {
     let coin_symbol = app.tracked_coin.as_ref().unwrap();
     
     if let Some(ref price) = app.latest_price {
-        let change_color = if price.change_24h >= 0.0 {
-            Color::Green
+        // An old number shouldn't look like a live one
+        let stale = app.latest_price_is_stale(now);
+        let price_style = if stale {
+            Style::default().fg(app.theme.dim).add_modifier(Modifier::DIM)
+        } else if is_fresh(price, now) {
+            Style::default().fg(app.theme.text).add_modifier(Modifier::BOLD | Modifier::REVERSED)
         } else {
-            Color::Red
+            Style::default().fg(app.theme.text).add_modifier(Modifier::BOLD)
         };
-        
-        let change_sign = if price.change_24h >= 0.0 { "+" } else { "" };
-        
-        let content = vec![
+
+        let change = app.basis_change(&app.session_stats.lock().unwrap(), price);
+        let change_color = change_color(&app.theme, change);
+
+        let mut content = vec![
             Line::from(vec![
                 Span::styled(
                     format!("{} - Latest Price", coin_symbol), 
-                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                    Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)
                 ),
             ]),
             Line::from(""),
             Line::from(vec![
                 Span::raw("Price: $"),
+                Span::styled(format!("{}{}", format_decimal(price.current_price, 8), repaired_mark(price.repaired)), price_style),
                 Span::styled(
-                    format!("{:.8}", price.current_price),
-                    Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+                    if stale { " (stale)" } else { "" },
+                    Style::default().fg(app.theme.highlight).add_modifier(Modifier::SLOW_BLINK),
                 ),
-                Span::raw("   24h Change: "),
+                Span::raw(format!("   {} Change: ", app.color_basis.label())),
                 Span::styled(
-                    format!("{}{:.2}%", change_sign, price.change_24h),
+                    format_change(change),
                     Style::default().fg(change_color).add_modifier(Modifier::BOLD)
                 ),
             ]),
             Line::from(vec![
                 Span::raw("Market Cap: $"),
-                Span::raw(format!("{:.2}", price.market_cap)),
+                Span::raw(format_decimal(price.market_cap, 2)),
                 Span::raw("   Volume 24h: $"),
-                Span::raw(format!("{:.2}", price.volume_24h)),
+                Span::raw(format_decimal(price.volume_24h, 2)),
             ]),
             Line::from(vec![
                 Span::raw("Pool Coin: "),
-                Span::raw(format!("{:.2}", price.pool_coin_amount)),
+                Span::raw(format_decimal(price.pool_coin_amount, 2)),
                 Span::raw("   Pool Base: "),
-                Span::raw(format!("{:.2}", price.pool_base_currency_amount)),
+                Span::raw(format_decimal(price.pool_base_currency_amount, 2)),
             ]),
             Line::from(vec![
                 Span::raw("Last Updated: "),
                 Span::styled(
-                    price.received_at.format("%H:%M:%S").to_string(),
-                    Style::default().fg(Color::Cyan)
+                    price.received_at.format(app.time_format()).to_string(),
+                    Style::default().fg(app.theme.accent)
+                ),
+                Span::styled(
+                    if stale { format!(" ({})", relative_time(price.received_at, now)) } else { String::new() },
+                    Style::default().fg(app.theme.highlight),
                 ),
             ]),
         ];
+        if let Some(position) = app.tracked_position() {
+            let pnl = position.pnl(price.current_price);
+            let pnl_color = if pnl >= Decimal::ZERO { app.theme.positive } else { app.theme.negative };
+            content.push(Line::from(vec![
+                Span::raw(format!(
+                    "Position: {} @ ${} (cost ${})   P&L: ",
+                    position.quantity,
+                    format_price(position.entry_price),
+                    format_decimal(position.cost(), 2)
+                )),
+                Span::styled(
+                    format!("{:+.2} ({:+.2}%)", round_to(pnl, 2), position.pnl_percent(price.current_price)),
+                    Style::default().fg(pnl_color).add_modifier(Modifier::BOLD),
+                ),
+            ]));
+        }
         
-        let price_info = Paragraph::new(content)
-            .block(Block::default().borders(Borders::ALL).title("Current Price Data"));
+        let block = if stale {
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(app.theme.highlight))
+                .title("Current Price Data (stale)")
+        } else {
+            Block::default().borders(Borders::ALL).title("Current Price Data")
+        };
+        let price_info = Paragraph::new(content).block(block);
         f.render_widget(price_info, area);
     } else {
         let waiting_text = Paragraph::new("Waiting for price data...")
             .block(Block::default().borders(Borders::ALL).title("Current Price Data"))
-            .style(Style::default().fg(Color::Gray));
+            .style(Style::default().fg(app.theme.muted));
         f.render_widget(waiting_text, area);
     }
 }
 
-fn draw_price_history(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    let price_updates = app.get_tracked_price_updates();
+/// How long a fresh price update stays highlighted. Checked against the
+/// frame time, so the highlight clears on the first redraw after it.
+const PRICE_FLASH_MS: i64 = 1000;
+
+fn is_fresh(update: &PriceUpdate, now: DateTime<Local>) -> bool {
+    (now - update.received_at).num_milliseconds() < PRICE_FLASH_MS
+}
+
+/// "Δ" change from the previous update of the same coin, colored by
+/// direction. The oldest update has nothing to compare against.
+fn price_delta_span(theme: &Theme, update: &PriceUpdate, previous: Option<&PriceUpdate>) -> Span<'static> {
+    let Some(previous) = previous else {
+        return Span::styled("   Δ —", Style::default().fg(theme.dim));
+    };
+    let (delta, percent) = update.delta_from(previous);
+    let (sign, color) = if delta > Decimal::ZERO {
+        ("+", theme.positive)
+    } else if delta < Decimal::ZERO {
+        ("-", theme.negative)
+    } else {
+        ("±", theme.dim)
+    };
+    let percent = percent.map_or(String::new(), |percent| format!(" ({:+.2}%)", percent));
+    Span::styled(
+        format!("   Δ {}{}{}", sign, format_price(delta.abs()), percent),
+        Style::default().fg(color),
+    )
+}
+
+/// What an empty price history pane says: the range picked may simply hold
+/// nothing yet.
+fn empty_history_message(app: &App) -> String {
+    match app.price_range() {
+        PriceRange::All => "Waiting for price data...".to_string(),
+        range => format!("No data in range - nothing in the last {} (4 shows all)", range.label()),
+    }
+}
+
+fn draw_price_history(f: &mut Frame, app: &App, area: ratatui::layout::Rect, now: DateTime<Local>) {
+    let price_updates = app.tracked_price_history();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(pane_border_style(app, AppPage::PriceTracker))
+        .title(format!("Price History [{}] ({}) - g: Chart - Scroll: ↑/↓/Mouse", app.price_range().label(), price_updates.len()));
+    if price_updates.is_empty() && app.price_range() != PriceRange::All {
+        f.render_widget(Paragraph::new(empty_history_message(app)).style(Style::default().fg(app.theme.muted)).block(block), area);
+        return;
+    }
     let visible_height = area.height.saturating_sub(2) as usize;
-    let start_idx = app.scroll_offset;
+    let start_idx = app.pane_scroll_offset(&AppPage::PriceTracker).min(price_updates.len());
     let end_idx = (start_idx + visible_height).min(price_updates.len());
+    let session = app.session_stats.lock().unwrap();
     
     let items: Vec<ListItem> = price_updates[start_idx..end_idx]
         .iter()
-        .map(|update| {
-            let change_color = if update.change_24h >= 0.0 {
-                Color::Green
-            } else {
-                Color::Red
-            };
-            
-            let change_sign = if update.change_24h >= 0.0 { "+" } else { "" };
+        .enumerate()
+        .map(|(i, point)| {
+            let update = &point.update;
+            let change = app.basis_change(&session, update);
+            let change_color = change_color(&app.theme, change);
+            // Updates are newest-first, so the previous one is the next row
+            let previous = price_updates.get(start_idx + i + 1).map(|point| &point.update);
+            let mut price_style = Style::default().fg(app.theme.text).add_modifier(Modifier::BOLD);
+            if start_idx + i == 0 && is_fresh(update, now) {
+                price_style = price_style.add_modifier(Modifier::REVERSED);
+            }
             
             let content = vec![
                 Line::from(vec![
                     Span::raw("Price: $"),
+                    Span::styled(format!("{}{}", format_decimal(update.current_price, 8), repaired_mark(update.repaired)), price_style),
+                    price_delta_span(&app.theme, update, previous),
+                    Span::raw(format!("   {} Change: ", app.color_basis.label())),
                     Span::styled(
-                        format!("{:.8}", update.current_price),
-                        Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
-                    ),
-                    Span::raw("   Change: "),
-                    Span::styled(
-                        format!("{}{:.2}%", change_sign, update.change_24h),
+                        format_change(change),
                         Style::default().fg(change_color)
                     ),
                     Span::raw("   @ "),
                     Span::styled(
-                        update.received_at.format("%H:%M:%S").to_string(),
-                        Style::default().fg(Color::Cyan)
+                        update.received_at.format(app.time_format()).to_string(),
+                        Style::default().fg(app.theme.accent)
+                    ),
+                    Span::styled(
+                        format!(" ({})", relative_time(update.received_at, now)),
+                        Style::default().fg(app.theme.dim)
                     ),
                 ]),
                 Line::from(vec![
                     Span::raw("  Market Cap: $"),
-                    Span::raw(format!("{:.2}", update.market_cap)),
+                    Span::raw(format_decimal(update.market_cap, 2)),
                     Span::raw("   Volume: $"),
-                    Span::raw(format!("{:.2}", update.volume_24h)),
+                    Span::raw(format_decimal(update.volume_24h, 2)),
+                    Span::styled(
+                        if point.resolution == Resolution::Tick {
+                            String::new()
+                        } else {
+                            format!("   {} bar: ${} - ${}", point.resolution.label(), format_price(point.low), format_price(point.high))
+                        },
+                        Style::default().fg(app.theme.dim),
+                    ),
                 ]),
                 Line::from(""),
             ];
             
-            ListItem::new(content)
+            // The top row is the selection when this pane has focus
+            let item = ListItem::new(content);
+            if i == 0 && app.current_page == AppPage::PriceTracker {
+                item.style(selected_row_style(&app.theme))
+            } else {
+                item
+            }
         })
         .collect();
 
     let price_list = List::new(items)
-        .block(Block::default()
-            .borders(Borders::ALL)
-            .title(format!("Price History ({}) - Scroll: ↑/↓/Mouse", price_updates.len())));
+        .block(block);
     f.render_widget(price_list, area);
+    draw_scrollbar(
+        f,
+        app,
+        AppPage::PriceTracker,
+        area,
+        price_updates.len(),
+        start_idx,
+        visible_height / PRICE_HISTORY_ITEM_HEIGHT,
+    );
 }
 
 fn draw_filters(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
@@ -218,15 +1048,15 @@ fn draw_filters(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         .split(area);
 
     let coin_filter_style = if app.input_mode == InputMode::CoinFilter {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(app.theme.highlight)
     } else {
-        Style::default().fg(Color::White)
+        Style::default().fg(app.theme.text)
     };
     
     let trader_filter_style = if app.input_mode == InputMode::TraderFilter {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(app.theme.highlight)
     } else {
-        Style::default().fg(Color::White)
+        Style::default().fg(app.theme.text)
     };
 
     let coin_filter_text = if app.input_mode == InputMode::CoinFilter {
@@ -241,8 +1071,18 @@ fn draw_filters(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         &app.trader_filter
     };
 
+    let coin_count = app.coin_filter_list().len();
+    let mut coin_filter_title = if coin_count > 0 {
+        format!("Coin Filter (c) - {} coin{}", coin_count, if coin_count == 1 { "" } else { "s" })
+    } else {
+        "Coin Filter (c)".to_string()
+    };
+    if app.coin_link {
+        coin_filter_title.push_str(" ⇄ linked to tracker (l)");
+    }
+
     let coin_filter = Paragraph::new(coin_filter_text.as_str())
-        .block(Block::default().borders(Borders::ALL).title("Coin Filter (c)"))
+        .block(Block::default().borders(Borders::ALL).title(coin_filter_title))
         .style(coin_filter_style);
     f.render_widget(coin_filter, filter_chunks[0]);
 
@@ -252,11 +1092,16 @@ fn draw_filters(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     f.render_widget(trader_filter, filter_chunks[1]);
 }
 
-fn draw_trades(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+fn draw_trades(f: &mut Frame, app: &App, area: ratatui::layout::Rect, now: DateTime<Local>) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),  // Trade type tabs
+            Constraint::Length(3),  // Order flow heatmap
+            Constraint::Length(if app.size_histogram { SIZE_HISTOGRAM_HEIGHT } else { 0 }),
+            Constraint::Length(if app.volume_chart { VOLUME_CHART_HEIGHT } else { 0 }),
+            Constraint::Length(if app.coin_counts_chart { COIN_COUNTS_HEIGHT } else { 0 }),
+            Constraint::Length(if app.buy_sell_mix { BUY_SELL_MIX_HEIGHT } else { 0 }),
             Constraint::Min(0),     // Trades list
         ])
         .split(area);
@@ -269,82 +1114,1490 @@ fn draw_trades(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     };
     let tabs_widget = Tabs::new(tabs)
         .block(Block::default().borders(Borders::ALL).title("Trade Type"))
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .style(Style::default().fg(app.theme.text))
+        .highlight_style(Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD))
         .select(selected_tab);
     f.render_widget(tabs_widget, chunks[0]);
 
-    // Draw trades list
-    let trades = app.filtered_trades();
-    let visible_height = chunks[1].height.saturating_sub(2) as usize;
-    let start_idx = app.scroll_offset;
+    draw_flow_heatmap(f, app, chunks[1]);
+    if app.size_histogram {
+        draw_size_histogram(f, app, chunks[2]);
+    }
+    if app.volume_chart {
+        draw_volume_chart(f, app, chunks[3], now);
+    }
+    if app.coin_counts_chart {
+        draw_coin_counts(f, app, chunks[4]);
+    }
+    if app.buy_sell_mix {
+        draw_buy_sell_mix(f, app, chunks[5]);
+    }
+
+    let list_area = if app.follow_pane {
+        let panes = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(chunks[6]);
+        draw_followed_trades(f, app, panes[1], now);
+        panes[0]
+    } else {
+        chunks[6]
+    };
+
+    // Draw trades list. Recording the height first lets the filtered view
+    // stop materialising trades just past the bottom of the screen.
+    app.trades_viewport_rows.set(list_area.height.saturating_sub(2) as usize);
+    let trades = app.grouped_trades();
+    if app.compact_view {
+        draw_trades_table(f, app, &trades, list_area, now);
+    } else if let Some(template) = app.row_template() {
+        draw_templated_trades(f, app, template, &trades, list_area, now);
+    } else {
+        draw_trades_list(f, app, &trades, list_area, now);
+    }
+}
+
+/// One row per trade by a followed trader, ignoring the filters above.
+fn draw_followed_trades(f: &mut Frame, app: &App, area: Rect, now: DateTime<Local>) {
+    app.follow_pane_area.set(Some(area));
+    let trades = app.followed_trades();
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let start_idx = app.follow_scroll.min(trades.len());
     let end_idx = (start_idx + visible_height).min(trades.len());
-    
-    let items: Vec<ListItem> = trades[start_idx..end_idx]
+
+    let items: Vec<ListItem> = if app.follow_list.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "Not following anyone yet - press f on a trade to follow its trader",
+            Style::default().fg(app.theme.muted),
+        ))]
+    } else {
+        trades[start_idx..end_idx]
+            .iter()
+            .map(|trade| {
+                let trade_type_color = if trade.data.trade_type == "BUY" {
+                    app.theme.positive
+                } else {
+                    app.theme.negative
+                };
+                let tier = app.value_tier(trade.data.total_value);
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{} ", trade.received_at.format(app.time_format()))),
+                    Span::styled(&trade.data.username, Style::default().fg(app.theme.accent)),
+                    Span::raw(" "),
+                    Span::styled(&trade.data.trade_type, tier.hue_style(trade_type_color)),
+                    Span::raw(format!(" {} ", format_decimal(trade.data.amount, 2))),
+                    Span::styled(&trade.data.coin_symbol, Style::default().fg(app.theme.highlight)),
+                    Span::raw(" "),
+                    Span::styled(format!("${}{}", format_decimal(trade.data.total_value, 2), repaired_mark(trade.repaired)), tier.value_style(trade_type_color)),
+                    Span::styled(
+                        format!(" ({})", relative_time(trade.received_at, now)),
+                        Style::default().fg(app.theme.dim),
+                    ),
+                ]))
+            })
+            .collect()
+    };
+
+    let title = format!(
+        "Followed Traders ({} trades) - J/K: Scroll | L: Manage | F: Hide",
+        trades.len()
+    );
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(list, area);
+}
+
+const FLOW_CELL_WIDTH: u16 = 3;
+const MAX_FLOW_MINUTES: usize = 60;
+
+const VOLUME_CHART_HEIGHT: u16 = 10;
+
+/// Buy (green) and sell (red) value traded per minute, oldest on the left,
+/// scaled to the busiest minute. The current minute is still filling up, so
+/// it's drawn dimmer.
+fn draw_volume_chart(f: &mut Frame, app: &App, area: Rect, now: DateTime<Local>) {
+    let coin = app.volume_chart_coin();
+    let series = app.session_stats.lock().unwrap().minute_volume(coin.as_deref(), now);
+    // Two bars and a gap per minute; drop the oldest minutes if they don't fit
+    let fits = (area.width.saturating_sub(2) / 3) as usize;
+    let series = &series[series.len().saturating_sub(fits)..];
+
+    // Bars take whole numbers, so chart in cents
+    let cents = |value: f64| (value * 100.0).round() as u64;
+    let max = series
         .iter()
-        .map(|trade| {
-            let trade_type_color = if trade.data.trade_type == "BUY" {
-                Color::Green
-            } else {
-                Color::Red
-            };
-            
-            let trade_size = if trade.msg_type == "live-trade" {
-                " [LARGE]"
-            } else {
-                ""
-            };
-            
-            let content = vec![
-                Line::from(vec![
-                    Span::styled(&trade.data.trade_type, Style::default().fg(trade_type_color).add_modifier(Modifier::BOLD)),
-                    Span::raw(trade_size),
-                    Span::raw(" - "),
-                    Span::styled(&trade.data.username, Style::default().fg(Color::Cyan)),
-                    Span::raw(" @ "),
-                    Span::raw(trade.received_at.format("%H:%M:%S").to_string()),
-                ]),
-                Line::from(vec![
-                    Span::raw("  "),
-                    Span::styled(&trade.data.coin_symbol, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-                    Span::raw(" ("),
-                    Span::raw(&trade.data.coin_name),
-                    Span::raw(")"),
-                ]),
-                Line::from(vec![
-                    Span::raw("  Amount: "),
-                    Span::raw(format!("{:.2}", trade.data.amount)),
-                    Span::raw(" | Value: $"),
-                    Span::raw(format!("{:.2}", trade.data.total_value)),
-                    Span::raw(" | Price: $"),
-                    Span::raw(format!("{:.8}", trade.data.price)),
-                ]),
-                Line::from(""),
-            ];
-            
-            ListItem::new(content)
+        .map(|bucket| bucket.buy_value.max(bucket.sell_value))
+        .fold(0.0, f64::max);
+    let last = series.len().saturating_sub(1);
+    let mut chart = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Value per Minute - {} - last {}m, max {} (green: buys, red: sells) - V: Hide",
+            coin.as_deref().unwrap_or("all coins"),
+            series.len(),
+            format_compact_value(max)
+        )))
+        .bar_width(1)
+        .bar_gap(0)
+        .group_gap(1)
+        .max(cents(max).max(1));
+    for (i, bucket) in series.iter().enumerate() {
+        let (buy_color, sell_color) = if i == last {
+            (Color::Rgb(0, 100, 0), Color::Rgb(110, 0, 0))
+        } else {
+            (app.theme.positive, app.theme.negative)
+        };
+        let bars = [
+            Bar::default().value(cents(bucket.buy_value)).text_value(String::new()).style(Style::default().fg(buy_color)),
+            Bar::default().value(cents(bucket.sell_value)).text_value(String::new()).style(Style::default().fg(sell_color)),
+        ];
+        chart = chart.data(BarGroup::default().bars(&bars));
+    }
+    f.render_widget(chart, area);
+}
+
+const COIN_COUNTS_HEIGHT: u16 = 10;
+const COIN_COUNTS_MAX_BARS: usize = 10;
+const COIN_COUNTS_BAR_WIDTH: u16 = 7;
+
+/// Trade count of the busiest coins in the buffer, busiest on the left. As
+/// many bars as fit the width, up to ten.
+fn draw_coin_counts(f: &mut Frame, app: &App, area: Rect) {
+    let counts = app.trade_counts_by_coin();
+    let block = Block::default().borders(Borders::ALL);
+    if counts.is_empty() {
+        let block = block.title("Trades per Coin - R: Hide");
+        f.render_widget(Paragraph::new("Waiting for trades...").style(Style::default().fg(app.theme.muted)).block(block), area);
+        return;
+    }
+    let fits = ((area.width.saturating_sub(2) + 1) / (COIN_COUNTS_BAR_WIDTH + 1)) as usize;
+    let shown = &counts[..counts.len().min(fits).min(COIN_COUNTS_MAX_BARS)];
+    let bars: Vec<Bar> = shown
+        .iter()
+        .map(|(coin, count)| {
+            Bar::default()
+                .label(Line::from(coin.as_str()))
+                .value(*count)
+                .style(Style::default().fg(app.theme.highlight))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Yellow))
         })
         .collect();
+    let chart = BarChart::default()
+        .block(block.title(format!("Trades per Coin - top {} of {} - R: Hide", shown.len(), counts.len())))
+        .bar_width(COIN_COUNTS_BAR_WIDTH)
+        .bar_gap(1)
+        .max(shown.first().map_or(1, |(_, count)| *count))
+        .data(BarGroup::default().bars(&bars));
+    f.render_widget(chart, area);
+}
 
-    let trades_list = List::new(items)
-        .block(Block::default()
-            .borders(Borders::ALL)
-            .title(format!("Trades ({}/{}) - Scroll: ↑/↓/Mouse", trades.len(), app.trades.lock().unwrap().len())));
-    f.render_widget(trades_list, chunks[1]);
+// One gauge row plus the borders
+const BUY_SELL_MIX_HEIGHT: u16 = 3;
+
+/// Buys (green) against sells (red) by trade count over the trades in
+/// view, so it follows the tab, filters and mutes.
+fn draw_buy_sell_mix(f: &mut Frame, app: &App, area: Rect) {
+    let (buys, sells) = app.buy_sell_counts();
+    let total = buys + sells;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Buy/Sell Mix - {} trades in view - %: Hide", total));
+    let gauge = if total > 0 {
+        let buy_ratio = buys as f64 / total as f64;
+        Gauge::default()
+            .block(block)
+            .gauge_style(Style::default().fg(app.theme.positive).bg(app.theme.negative))
+            .ratio(buy_ratio)
+            .label(Span::styled(
+                format!("{} buys {:.0}% / {} sells {:.0}%", buys, buy_ratio * 100.0, sells, (1.0 - buy_ratio) * 100.0),
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            ))
+    } else {
+        Gauge::default()
+            .block(block)
+            .gauge_style(Style::default().fg(app.theme.dim).bg(app.theme.surface))
+            .ratio(0.5)
+            .label("No trades in view")
+    };
+    f.render_widget(gauge, area);
+}
+
+// One row per size bucket plus the borders
+const SIZE_HISTOGRAM_HEIGHT: u16 = SIZE_BUCKET_LABELS.len() as u16 + 2;
+
+/// Trade count per size bucket for the filtered trades, with the value
+/// traded in each. Empty buckets keep their row so the scale stays readable.
+fn draw_size_histogram(f: &mut Frame, app: &App, area: Rect) {
+    let histogram = app.size_histogram();
+    let bars: Vec<Bar> = SIZE_BUCKET_LABELS
+        .iter()
+        .zip(histogram.counts.iter().zip(histogram.values.iter()))
+        .map(|(label, (&count, &value))| {
+            Bar::default()
+                .label(Line::from(format!("{:>8}", label)))
+                .value(count as u64)
+                .text_value(format!("{} trades, {}", count, format_compact_value(value)))
+                .style(Style::default().fg(app.theme.accent))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Cyan))
+        })
+        .collect();
+    let total: usize = histogram.counts.iter().sum();
+    let chart = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title(format!("Trade Sizes ({} trades) - H: Hide", total)))
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .max(histogram.counts.iter().copied().max().unwrap_or(0).max(1) as u64)
+        .data(BarGroup::default().bars(&bars));
+    f.render_widget(chart, area);
+}
+
+/// One colored cell per minute, oldest on the left: green where buys
+/// outweighed sells, red where sells did, brighter for larger net value.
+fn draw_flow_heatmap(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let minutes = ((area.width.saturating_sub(2) / FLOW_CELL_WIDTH) as usize).min(MAX_FLOW_MINUTES);
+    let buckets = app.flow_by_minute(minutes);
+    let max_net = buckets.iter().map(|b| b.net().abs()).fold(0.0, f64::max);
+
+    let cells: Vec<Span> = buckets
+        .iter()
+        .map(|bucket| {
+            if bucket.trade_count == 0 {
+                return Span::styled(" · ", Style::default().fg(app.theme.dim));
+            }
+            let net = bucket.net();
+            let intensity = if max_net > 0.0 { net.abs() / max_net } else { 0.0 };
+            let color = app.theme.heat(net >= 0.0, intensity);
+            Span::styled("   ", Style::default().bg(color))
+        })
+        .collect();
+
+    let since = buckets
+        .first()
+        .map(|bucket| format!(" since {}", bucket.minute.format("%H:%M")))
+        .unwrap_or_default();
+    let heatmap = Paragraph::new(Line::from(cells))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Order Flow - last {}m{} (green: buys, red: sells)", minutes, since)));
+    f.render_widget(heatmap, area);
+}
+
+// Lines per entry in the expanded trades list and the price history
+const TRADE_ITEM_HEIGHT: usize = 4;
+const PRICE_HISTORY_ITEM_HEIGHT: usize = 3;
+
+/// Draws a scrollbar over the right border of a bordered list and records
+/// its track for mouse hit-testing. Hidden when every entry fits on screen.
+fn draw_scrollbar(
+    f: &mut Frame,
+    app: &App,
+    page: AppPage,
+    area: Rect,
+    content_len: usize,
+    position: usize,
+    visible_items: usize,
+) {
+    if content_len <= visible_items && position == 0 {
+        return;
+    }
+    // The track runs between the top and bottom borders, without arrows, so
+    // each track row maps directly onto a slice of the list
+    let track_area = area.inner(Margin { vertical: 1, horizontal: 0 });
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .thumb_style(Style::default().fg(app.theme.highlight))
+        .track_style(Style::default().fg(app.theme.dim));
+    let mut state = ScrollbarState::new(content_len)
+        .position(position)
+        .viewport_content_length(visible_items.max(1));
+    f.render_stateful_widget(scrollbar, track_area, &mut state);
+
+    app.scrollbar_tracks.borrow_mut().push(ScrollbarTrack {
+        page,
+        area: Rect {
+            x: track_area.right().saturating_sub(1),
+            width: 1,
+            ..track_area
+        },
+        content_len,
+        visible_items,
+    });
+}
+
+/// Remembers a clickable coin symbol or username `width` columns wide,
+/// drawn `offset` columns into line `y` of the bordered `area`.
+fn record_quick_filter(app: &App, area: Rect, y: u16, offset: usize, width: usize, filter: QuickFilter) {
+    let inner = area.inner(Margin::new(1, 1));
+    let x = inner.x as usize + offset;
+    if width == 0 || y >= inner.bottom() || x >= inner.right() as usize {
+        return;
+    }
+    let width = width.min(inner.right() as usize - x) as u16;
+    app.quick_filter_regions
+        .borrow_mut()
+        .push(QuickFilterRegion { area: Rect::new(x as u16, y, width, 1), filter });
+}
+
+/// ` ×N` after a collapsed row, nothing for a single trade.
+fn group_badge(row: &GroupedTrade) -> String {
+    if row.count > 1 {
+        format!(" ×{}", row.count)
+    } else {
+        String::new()
+    }
+}
+
+fn draw_trades_list(f: &mut Frame, app: &App, trades: &[GroupedTrade], area: ratatui::layout::Rect, now: DateTime<Local>) {
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let start_idx = app.pane_scroll_offset(&AppPage::Trades).min(trades.len());
+    let end_idx = (start_idx + visible_height).min(trades.len());
+    let search = app.active_search();
+    let line_width = area.width.saturating_sub(2) as usize;
+
+    let items: Vec<ListItem> = trades[start_idx..end_idx]
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let trade = &row.trade;
+            let trade_type_color = if trade.data.trade_type == "BUY" {
+                app.theme.positive
+            } else {
+                app.theme.negative
+            };
+            let tier = app.value_tier(row.total_value);
+
+            let trade_size = if trade.is_large {
+                " [LARGE]"
+            } else {
+                ""
+            };
+
+            let highlight_marker = if app.is_highlighted(trade) { "★ " } else { "" };
+            let bookmark_marker = if app.is_bookmarked(trade) { "◆ " } else { "" };
+
+            let mut header = vec![
+                Span::styled(bookmark_marker, Style::default().fg(app.theme.special).add_modifier(Modifier::BOLD)),
+                Span::styled(highlight_marker, Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)),
+                Span::styled(&trade.data.trade_type, tier.hue_style(trade_type_color)),
+                Span::styled(group_badge(row), Style::default().fg(app.theme.special).add_modifier(Modifier::BOLD)),
+                Span::raw(trade_size),
+                Span::raw(" - "),
+            ];
+            let timing = [
+                Span::raw(" @ "),
+                Span::raw(trade.received_at.format(app.time_format()).to_string()),
+                Span::styled(
+                    format!(" ({})", relative_time(trade.received_at, now)),
+                    Style::default().fg(app.theme.dim)
+                ),
+            ];
+            // The username gets whatever the rest of the line leaves over
+            let before_username: usize = header.iter().map(Span::width).sum();
+            let used = before_username + timing.iter().map(Span::width).sum::<usize>();
+            let username = truncate_with_ellipsis(&trade.data.username, line_width.saturating_sub(used));
+            header.extend(search_spans(&username, search, Style::default().fg(app.theme.accent)));
+            header.extend(timing);
+
+            // "  SYMBOL (Name)": the symbol comes first, the name fits in after it
+            let symbol = truncate_with_ellipsis(&trade.data.coin_symbol, line_width.saturating_sub(5));
+            let coin_name = truncate_with_ellipsis(&trade.data.coin_name, line_width.saturating_sub(5 + symbol.width()));
+            let item_top = area.y + 1 + (i * TRADE_ITEM_HEIGHT) as u16;
+            record_quick_filter(app, area, item_top, before_username, username.width(), QuickFilter::Trader(trade.data.username.clone()));
+            record_quick_filter(app, area, item_top + 1, 2, symbol.width(), QuickFilter::Coin(trade.data.coin_symbol.clone()));
+            let mut coin = vec![Span::raw("  ")];
+            coin.extend(search_spans(&symbol, search, Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)));
+            // A coin name the feed left out isn't drawn as "()"
+            if !coin_name.is_empty() {
+                coin.push(Span::raw(" ("));
+                coin.extend(search_spans(&coin_name, search, Style::default()));
+                coin.push(Span::raw(")"));
+            }
+
+            let content = vec![
+                Line::from(header),
+                Line::from(coin),
+                Line::from(vec![
+                    Span::raw(if row.count > 1 { "  Total amount: " } else { "  Amount: " }),
+                    Span::raw(format_decimal(row.amount, 2)),
+                    Span::raw(" | Value: "),
+                    Span::styled(format!("${}{}", format_decimal(row.total_value, 2), repaired_mark(trade.repaired)), tier.value_style(trade_type_color)),
+                    Span::raw(if row.count > 1 { " | Last price: $" } else { " | Price: $" }),
+                    Span::raw(format_decimal(trade.data.price, 8)),
+                ]),
+                // The selected trade also shows how late it reached us
+                match trade.feed_lag().filter(|_| i == 0) {
+                    Some(lag) => Line::from(Span::styled(
+                        format!("  Feed lag: {:+.3}s", lag.num_milliseconds() as f64 / 1000.0),
+                        Style::default().fg(app.theme.dim),
+                    )),
+                    None => Line::from(""),
+                },
+            ];
+
+            let item = ListItem::new(content);
+            if i == 0 {
+                item.style(selected_row_style(&app.theme))
+            } else if app.is_aged(trade, now) {
+                item.style(aged_row_style())
+            } else {
+                item
+            }
+        })
+        .collect();
+
+    let trades_list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_style(pane_border_style(app, AppPage::Trades))
+            .title(trades_title(app, false)));
+    f.render_widget(trades_list, area);
+    draw_scrollbar(f, app, AppPage::Trades, area, app.filtered_trade_count(), start_idx, visible_height / TRADE_ITEM_HEIGHT);
+}
+
+/// The list view with one line per trade laid out by `trade_row_template`.
+fn draw_templated_trades(
+    f: &mut Frame,
+    app: &App,
+    template: &RowTemplate,
+    trades: &[GroupedTrade],
+    area: Rect,
+    now: DateTime<Local>,
+) {
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let start_idx = app.pane_scroll_offset(&AppPage::Trades).min(trades.len());
+    let end_idx = (start_idx + visible_height).min(trades.len());
+    let search = app.active_search();
+
+    let items: Vec<ListItem> = trades[start_idx..end_idx]
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let trade = &row.trade;
+            let trade_type_color = if trade.data.trade_type == "BUY" {
+                app.theme.positive
+            } else {
+                app.theme.negative
+            };
+            let tier = app.value_tier(row.total_value);
+            let y = area.y + 1 + i as u16;
+
+            let mut spans = vec![
+                Span::styled(if app.is_bookmarked(trade) { "◆ " } else { "" }, Style::default().fg(app.theme.special).add_modifier(Modifier::BOLD)),
+                Span::styled(if app.is_highlighted(trade) { "★ " } else { "" }, Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)),
+            ];
+            for part in template.parts() {
+                let offset: usize = spans.iter().map(Span::width).sum();
+                match part {
+                    TemplatePart::Literal(text) => spans.push(Span::raw(text.clone())),
+                    TemplatePart::Field(field) => match field {
+                        TradeField::Time => spans.push(Span::raw(trade.received_at.format(app.time_format()).to_string())),
+                        TradeField::Age => spans.push(Span::styled(relative_time(trade.received_at, now), Style::default().fg(app.theme.dim))),
+                        TradeField::Type => spans.push(Span::styled(trade.data.trade_type.clone(), tier.hue_style(trade_type_color))),
+                        TradeField::Coin => {
+                            record_quick_filter(app, area, y, offset, trade.data.coin_symbol.width(), QuickFilter::Coin(trade.data.coin_symbol.clone()));
+                            spans.extend(search_spans(&trade.data.coin_symbol, search, Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)));
+                        }
+                        TradeField::Name => spans.extend(search_spans(&trade.data.coin_name, search, Style::default())),
+                        TradeField::Amount => spans.push(Span::raw(format_decimal(row.amount, 2))),
+                        TradeField::Price => spans.push(Span::raw(format_decimal(trade.data.price, 8))),
+                        TradeField::Value => spans.push(Span::styled(
+                            format!("${}{}", format_decimal(row.total_value, 2), repaired_mark(trade.repaired)),
+                            tier.value_style(trade_type_color),
+                        )),
+                        TradeField::User => {
+                            record_quick_filter(app, area, y, offset, trade.data.username.width(), QuickFilter::Trader(trade.data.username.clone()));
+                            spans.extend(search_spans(&trade.data.username, search, Style::default().fg(app.theme.accent)));
+                        }
+                        TradeField::Count => spans.push(Span::styled(
+                            group_badge(row).trim_start().to_string(),
+                            Style::default().fg(app.theme.special).add_modifier(Modifier::BOLD),
+                        )),
+                    },
+                }
+            }
+
+            let item = ListItem::new(Line::from(spans));
+            if i == 0 {
+                item.style(selected_row_style(&app.theme))
+            } else if app.is_aged(trade, now) {
+                item.style(aged_row_style())
+            } else {
+                item
+            }
+        })
+        .collect();
+
+    let trades_list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_style(pane_border_style(app, AppPage::Trades))
+            .title(trades_title(app, false)));
+    f.render_widget(trades_list, area);
+    draw_scrollbar(f, app, AppPage::Trades, area, app.filtered_trade_count(), start_idx, visible_height);
+}
+
+// Fixed widths for every compact column except Trader, which takes what's left.
+const COMPACT_MARKER_WIDTH: u16 = 3;
+const COMPACT_TIME_WIDTH: u16 = 8;
+const COMPACT_TIME_MS_WIDTH: u16 = 12;
+const COMPACT_AGE_WIDTH: u16 = 8;
+const COMPACT_SIDE_WIDTH: u16 = 4;
+const COMPACT_COIN_WIDTH: u16 = 10;
+const COMPACT_AMOUNT_WIDTH: u16 = 14;
+const COMPACT_PRICE_WIDTH: u16 = 14;
+const COMPACT_VALUE_WIDTH: u16 = 12;
+const COMPACT_COLUMN_SPACING: u16 = 1;
+
+fn draw_trades_table(f: &mut Frame, app: &App, trades: &[GroupedTrade], area: ratatui::layout::Rect, now: DateTime<Local>) {
+    // Borders plus the header row
+    let visible_height = area.height.saturating_sub(3) as usize;
+    let start_idx = app.pane_scroll_offset(&AppPage::Trades).min(trades.len());
+    let end_idx = (start_idx + visible_height).min(trades.len());
+
+    let time_width = if app.millisecond_timestamps { COMPACT_TIME_MS_WIDTH } else { COMPACT_TIME_WIDTH };
+    let fixed_width = COMPACT_MARKER_WIDTH
+        + time_width
+        + COMPACT_AGE_WIDTH
+        + COMPACT_SIDE_WIDTH
+        + COMPACT_COIN_WIDTH
+        + COMPACT_AMOUNT_WIDTH
+        + COMPACT_PRICE_WIDTH
+        + COMPACT_VALUE_WIDTH
+        + COMPACT_COLUMN_SPACING * 8;
+    let trader_width = area.width.saturating_sub(2).saturating_sub(fixed_width);
+    let search = app.active_search();
+    // Squeezed columns move, so nothing is clickable until they all fit
+    let columns_fit = area.width.saturating_sub(2) >= fixed_width;
+    let coin_offset = (COMPACT_MARKER_WIDTH + time_width + COMPACT_AGE_WIDTH + COMPACT_SIDE_WIDTH + COMPACT_COLUMN_SPACING * 4) as usize;
+    let trader_offset = coin_offset
+        + (COMPACT_COIN_WIDTH + COMPACT_AMOUNT_WIDTH + COMPACT_PRICE_WIDTH + COMPACT_VALUE_WIDTH + COMPACT_COLUMN_SPACING * 4) as usize;
+
+    let rows: Vec<Row> = trades[start_idx..end_idx]
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let trade = &row.trade;
+            let trade_type_color = if trade.data.trade_type == "BUY" {
+                app.theme.positive
+            } else {
+                app.theme.negative
+            };
+            let tier = app.value_tier(row.total_value);
+            let symbol = truncate_with_ellipsis(&trade.data.coin_symbol, COMPACT_COIN_WIDTH as usize);
+            // The count badge stays visible however narrow the column
+            let badge = group_badge(row);
+            let username = truncate_with_ellipsis(&trade.data.username, (trader_width as usize).saturating_sub(badge.width()));
+            if columns_fit {
+                // Below the border and the header row
+                let y = area.y + 2 + i as u16;
+                record_quick_filter(app, area, y, coin_offset, symbol.width(), QuickFilter::Coin(trade.data.coin_symbol.clone()));
+                record_quick_filter(app, area, y, trader_offset, username.width(), QuickFilter::Trader(trade.data.username.clone()));
+            }
+
+            let marker = format!(
+                "{}{}{}",
+                if trade.is_large { "!" } else { " " },
+                if app.is_bookmarked(trade) { "◆" } else { " " },
+                if app.is_highlighted(trade) { "★" } else { "" },
+            );
+
+            let row = Row::new(vec![
+                Cell::from(marker).style(Style::default().fg(app.theme.special).add_modifier(Modifier::BOLD)),
+                Cell::from(trade.received_at.format(app.time_format()).to_string()),
+                Cell::from(relative_time(trade.received_at, now)).style(Style::default().fg(app.theme.dim)),
+                Cell::from(trade.data.trade_type.as_str()).style(tier.hue_style(trade_type_color)),
+                Cell::from(Line::from(search_spans(&symbol, search, Style::default().fg(app.theme.highlight)))),
+                Cell::from(Line::from(format_decimal(row.amount, 2)).alignment(Alignment::Right)),
+                Cell::from(Line::from(format_decimal(trade.data.price, 8)).alignment(Alignment::Right)),
+                Cell::from(
+                    Line::styled(format!("${}{}", format_decimal(row.total_value, 2), repaired_mark(trade.repaired)), tier.value_style(trade_type_color))
+                        .alignment(Alignment::Right),
+                ),
+                Cell::from(Line::from({
+                    let mut spans = search_spans(&username, search, Style::default().fg(app.theme.accent));
+                    spans.push(Span::styled(badge, Style::default().fg(app.theme.special).add_modifier(Modifier::BOLD)));
+                    spans
+                })),
+            ]);
+            if i == 0 {
+                row.style(selected_row_style(&app.theme))
+            } else if app.is_aged(trade, now) {
+                row.style(aged_row_style())
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let header = Row::new(vec![
+        Cell::from(""),
+        Cell::from("Time"),
+        Cell::from("Age"),
+        Cell::from("Type"),
+        Cell::from("Coin"),
+        Cell::from(Line::from("Amount").alignment(Alignment::Right)),
+        Cell::from(Line::from("Price").alignment(Alignment::Right)),
+        Cell::from(Line::from("Value").alignment(Alignment::Right)),
+        Cell::from("Trader"),
+    ])
+    .style(Style::default().fg(app.theme.muted).add_modifier(Modifier::BOLD));
+
+    let widths = [
+        Constraint::Length(COMPACT_MARKER_WIDTH),
+        Constraint::Length(time_width),
+        Constraint::Length(COMPACT_AGE_WIDTH),
+        Constraint::Length(COMPACT_SIDE_WIDTH),
+        Constraint::Length(COMPACT_COIN_WIDTH),
+        Constraint::Length(COMPACT_AMOUNT_WIDTH),
+        Constraint::Length(COMPACT_PRICE_WIDTH),
+        Constraint::Length(COMPACT_VALUE_WIDTH),
+        Constraint::Min(trader_width),
+    ];
+
+    let trades_table = Table::new(rows, widths)
+        .header(header)
+        .column_spacing(COMPACT_COLUMN_SPACING)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_style(pane_border_style(app, AppPage::Trades))
+            .title(trades_title(app, true)));
+    f.render_widget(trades_table, area);
+    draw_scrollbar(f, app, AppPage::Trades, area, app.filtered_trade_count(), start_idx, visible_height);
+}
+
+// Window of the traded value shown in the trades title
+const ROLLING_VOLUME_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+fn trades_title(app: &App, compact: bool) -> String {
+    let muted_count = app.muted_trade_count();
+    let muted = if muted_count > 0 {
+        format!(", {} muted", muted_count)
+    } else {
+        String::new()
+    };
+    let search = match app.search_position() {
+        Some((_, 0)) => format!(" - \"{}\": no matches", app.active_search()),
+        Some((Some(index), total)) => format!(" - \"{}\": {}/{} matches", app.active_search(), index, total),
+        Some((None, total)) => format!(" - \"{}\": {} matches", app.active_search(), total),
+        None => String::new(),
+    };
+    format!(
+        "Trades ({}/{}{}){} - 1h: {}{}{}{}{} - Sort: {} - Scroll: ↑/↓/Mouse",
+        app.filtered_trade_count(),
+        app.trades_snapshot().len(),
+        muted,
+        search,
+        format_compact_value(lossy_f64(app.rolling_volume(ROLLING_VOLUME_WINDOW))),
+        if compact { " - Compact" } else { "" },
+        if app.group_trades { " - Grouped" } else { "" },
+        if app.avatar_only { " - Avatars only" } else { "" },
+        app.trade_since.map(|window| format!(" - Since {}", cli::format_retention(window))).unwrap_or_default(),
+        app.trade_sort.label(),
+    )
+}
+
+/// Short human-readable age like "just now", "12s ago" or "3m ago".
+pub fn relative_time(then: DateTime<Local>, now: DateTime<Local>) -> String {
+    let seconds = now.signed_duration_since(then).num_seconds();
+    if seconds < 5 {
+        "just now".to_string()
+    } else if seconds < 60 {
+        format!("{}s ago", seconds)
+    } else if seconds < 60 * 60 {
+        format!("{}m ago", seconds / 60)
+    } else if seconds < 24 * 60 * 60 {
+        format!("{}h ago", seconds / (60 * 60))
+    } else {
+        format!("{}d ago", seconds / (24 * 60 * 60))
+    }
+}
+
+/// `HH:MM:SS`; the hours keep counting past a day.
+pub fn format_uptime(uptime: Duration) -> String {
+    let seconds = uptime.as_secs();
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+fn selected_row_style(theme: &Theme) -> Style {
+    Style::default().bg(theme.selection)
+}
+
+/// Trades older than `trade_dim_age_secs` keep their colours, only fainter.
+fn aged_row_style() -> Style {
+    Style::default().add_modifier(Modifier::DIM)
+}
+
+/// `text` in `style`, with the places it matches the search picked out.
+fn search_spans(text: &str, search: &str, style: Style) -> Vec<Span<'static>> {
+    let hit_style = Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for range in app::search_match_ranges(text, search) {
+        if range.start > end {
+            spans.push(Span::styled(text[end..range.start].to_string(), style));
+        }
+        end = range.end;
+        spans.push(Span::styled(text[range].to_string(), hit_style));
+    }
+    if end < text.len() || spans.is_empty() {
+        spans.push(Span::styled(text[end..].to_string(), style));
+    }
+    spans
+}
+
+/// `text` cut down to at most `max_width` terminal columns, ending in `…`
+/// when anything had to go. Double-width characters count as two columns
+/// and grapheme clusters such as emoji ZWJ sequences are never split.
+pub fn truncate_with_ellipsis(text: &str, max_width: usize) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let mut truncated = String::new();
+    let mut width = 0;
+    for grapheme in text.graphemes(true) {
+        width += grapheme.width();
+        if width > max_width - 1 {
+            break;
+        }
+        truncated.push_str(grapheme);
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// [`truncate_with_ellipsis`], padded with spaces to exactly `width` columns.
+pub fn fit_to_width(text: &str, width: usize) -> String {
+    let mut fitted = truncate_with_ellipsis(text, width);
+    let padding = width.saturating_sub(fitted.width());
+    fitted.push_str(&" ".repeat(padding));
+    fitted
+}
+
+fn draw_coin_list(f: &mut Frame, app: &App) {
+    let area = centered_rect(40, 60, f.area());
+    let visible_height = area.height.saturating_sub(2) as usize;
+    // Keep the cursor in view by scrolling the window with it
+    let start_idx = app.coin_list_index.saturating_sub(visible_height.saturating_sub(1));
+    let end_idx = (start_idx + visible_height).min(app.coin_list.len());
+
+    let items: Vec<ListItem> = if app.coin_list.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "No coins seen yet - press / to type one",
+            Style::default().fg(app.theme.muted),
+        ))]
+    } else {
+        app.coin_list[start_idx..end_idx]
+            .iter()
+            .enumerate()
+            .map(|(i, coin)| {
+                let is_tracked = app.tracked_coin.as_deref() == Some(coin.as_str());
+                let item = ListItem::new(Line::from(vec![
+                    Span::raw(if is_tracked { "● " } else { "  " }),
+                    Span::styled(coin.as_str(), Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)),
+                ]));
+                if start_idx + i == app.coin_list_index {
+                    item.style(selected_row_style(&app.theme))
+                } else {
+                    item
+                }
+            })
+            .collect()
+    };
+
+    let coin_list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Select Coin ({}) - Enter: Track | /: Type | Esc: Cancel", app.coin_list.len())));
+    f.render_widget(Clear, area);
+    f.render_widget(coin_list, area);
+}
+
+/// Every key binding, grouped by context, straight from the keymap tables.
+fn draw_help_overlay(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 80, f.area());
+
+    let mut lines = Vec::new();
+    for section in keymap::SECTIONS {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            section.title,
+            Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD),
+        )));
+        for binding in section.bindings {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<24}", keymap::keys_label(binding.keys)),
+                    Style::default().fg(app.theme.accent),
+                ),
+                Span::raw(binding.description),
+            ]));
+        }
+        if let Some(note) = section.note {
+            lines.push(Line::from(Span::styled(format!("  {}", note), Style::default().fg(app.theme.muted))));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Commands",
+        Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD),
+    )));
+    for command in command::COMMANDS {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  :{:<23}", command.usage), Style::default().fg(app.theme.accent)),
+            Span::raw(command.description),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Feed diagnostics",
+        Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(format!("  Subscribed channels: {}", app.subscribe_channels().join(", "))));
+    let unknown_types = if app.unknown_message_types.is_empty() {
+        "none".to_string()
+    } else {
+        app.unknown_message_types.iter().cloned().collect::<Vec<_>>().join(", ")
+    };
+    lines.push(Line::from(format!("  Unrecognized message types: {}", unknown_types)));
+    let unknown_fields = if app.unknown_trade_fields.is_empty() {
+        "none".to_string()
+    } else {
+        app.unknown_trade_fields.iter().cloned().collect::<Vec<_>>().join(", ")
+    };
+    lines.push(Line::from(format!("  Unrecognized trade fields: {}", unknown_fields)));
+
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let max_scroll = lines.len().saturating_sub(visible_height);
+    app.help_max_scroll.set(max_scroll);
+    let scroll = app.help_scroll.min(max_scroll);
+
+    let title = if max_scroll > 0 {
+        "Key Bindings - ↑/↓: Scroll | ?/Esc/q: Close"
+    } else {
+        "Key Bindings - ?/Esc/q: Close"
+    };
+    let help = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .scroll((scroll as u16, 0));
+    f.render_widget(Clear, area);
+    f.render_widget(help, area);
+}
+
+fn draw_quit_confirmation(f: &mut Frame, app: &App) {
+    let popup = centered_rect(40, 20, f.area());
+    let area = Rect {
+        height: popup.height.min(6),
+        y: popup.y + popup.height.saturating_sub(6) / 2,
+        ..popup
+    };
+    let trades = app.trades_snapshot().len();
+    let lines = vec![
+        Line::from(Span::styled("Quit?", Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD))),
+        Line::from(format!(
+            "{} buffered trade{} will be lost.",
+            trades,
+            if trades == 1 { "" } else { "s" }
+        )),
+        Line::from(""),
+        Line::from(Span::styled("y/Enter: Quit | n/Esc: Back", Style::default().fg(app.theme.muted))),
+    ];
+    let dialog = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Confirm"));
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+// How long a refused keystroke keeps a numeric prompt flashed
+const REJECTED_INPUT_FLASH_MS: i64 = 1000;
+
+/// Red border and hint while a numeric prompt is refusing a keystroke.
+fn rejected_input_flash(app: &App) -> Option<(Style, &'static str)> {
+    let at = app.rejected_input_at?;
+    if (Local::now() - at).num_milliseconds() >= REJECTED_INPUT_FLASH_MS {
+        return None;
+    }
+    let hint = match app.numeric_input() {
+        Some(numeric) if numeric.values > 1 => "Numbers only, separated by a space",
+        _ => "Numbers only",
+    };
+    Some((Style::default().fg(app.theme.negative).add_modifier(Modifier::BOLD), hint))
+}
+
+fn draw_position_entry(f: &mut Frame, app: &App) {
+    let popup = centered_rect(50, 20, f.area());
+    let area = Rect {
+        height: popup.height.min(6),
+        y: popup.y + popup.height.saturating_sub(6) / 2,
+        ..popup
+    };
+    let coin = app.tracked_coin.as_deref().unwrap_or_default();
+    let flash = rejected_input_flash(app);
+    let lines = vec![
+        Line::from("Entry price and quantity, e.g. 0.0042 1000"),
+        Line::from(Span::styled(format!("> {}", app.input_buffer), Style::default().fg(app.theme.highlight))),
+        match flash {
+            Some((style, hint)) => Line::from(Span::styled(hint, style)),
+            None => Line::from(""),
+        },
+        Line::from(Span::styled("Enter: Save (empty removes it) | Esc: Cancel", Style::default().fg(app.theme.muted))),
+    ];
+    let mut block = Block::default().borders(Borders::ALL).title(format!("Simulated {} Position", coin));
+    if let Some((style, _)) = flash {
+        block = block.border_style(style);
+    }
+    let dialog = Paragraph::new(lines).block(block);
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+fn draw_order_form(f: &mut Frame, app: &App) {
+    let popup = centered_rect(50, 30, f.area());
+    let area = Rect {
+        height: popup.height.min(9),
+        y: popup.y + popup.height.saturating_sub(9) / 2,
+        ..popup
+    };
+    let form = &app.order_form;
+    let field_style = |field: OrderField| {
+        if form.field == field {
+            Style::default().fg(app.theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(app.theme.text)
+        }
+    };
+    let label = Style::default().fg(app.theme.muted);
+    let mut coin_spans = vec![Span::styled("Coin:   ", label), Span::styled(form.coin.clone(), field_style(OrderField::Coin))];
+    if let Some(suggestion) = app.order_coin_suggestion() {
+        coin_spans.push(Span::styled(
+            format!("{} (Tab)", &suggestion[form.coin.len().min(suggestion.len())..]),
+            Style::default().fg(app.theme.dim),
+        ));
+    }
+    let price = match app.latest_price_of(&form.coin) {
+        Some(price) => format!("${}", format_price(price)),
+        None => "no price yet".to_string(),
+    };
+    let side_color = match form.side {
+        Side::Buy => app.theme.positive,
+        Side::Sell => app.theme.negative,
+    };
+    let mut lines = vec![
+        Line::from(coin_spans),
+        Line::from(vec![
+            Span::styled("Side:   ", label),
+            Span::styled(form.side.label(), field_style(OrderField::Side).fg(side_color)),
+            Span::styled(" (←/→)", Style::default().fg(app.theme.dim)),
+        ]),
+        Line::from(vec![Span::styled("Amount: $", label), Span::styled(form.amount.clone(), field_style(OrderField::Amount))]),
+        Line::from(vec![Span::styled("Price:  ", label), Span::raw(price)]),
+    ];
+    let flash = rejected_input_flash(app);
+    match (flash, &form.error) {
+        (Some((style, hint)), _) => lines.push(Line::from(Span::styled(hint, style))),
+        (None, Some(error)) => lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(app.theme.negative)))),
+        (None, None) => lines.push(Line::from("")),
+    }
+    lines.push(Line::from(Span::styled("Enter: Place | ↑/↓: Field | Esc: Cancel", Style::default().fg(app.theme.muted))));
+    let mut block = Block::default().borders(Borders::ALL).title("Paper Order");
+    if let Some((style, _)) = flash {
+        block = block.border_style(style);
+    }
+    let dialog = Paragraph::new(lines).block(block);
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+fn draw_coin_stats(f: &mut Frame, app: &App, now: DateTime<Local>) {
+    let area = centered_rect(50, 50, f.area());
+    let Some(ref coin) = app.stats_coin else { return };
+    let session = app.session_stats.lock().unwrap();
+    let label = Style::default().fg(app.theme.muted);
+    let timestamp = |at: DateTime<Local>| format!("{} ({})", at.format(app.time_format()), relative_time(at, now));
+
+    let mut lines = Vec::new();
+    match session.trade_stats(coin) {
+        Some(stats) => {
+            lines.push(Line::from(vec![
+                Span::styled("Trades:      ", label),
+                Span::raw(stats.trade_count.to_string()),
+                Span::raw(" ("),
+                Span::styled(format!("{} buys", stats.buy_count), Style::default().fg(app.theme.positive)),
+                Span::raw(" / "),
+                Span::styled(format!("{} sells", stats.sell_count), Style::default().fg(app.theme.negative)),
+                Span::raw(")"),
+            ]));
+            lines.push(Line::from(vec![
+                Span::styled("Buy value:   ", label),
+                Span::styled(format!("${}", format_decimal(stats.buy_value, 2)), Style::default().fg(app.theme.positive)),
+                Span::raw(" | "),
+                Span::styled("Sell value: ", label),
+                Span::styled(format!("${}", format_decimal(stats.sell_value, 2)), Style::default().fg(app.theme.negative)),
+            ]));
+            lines.push(Line::from(vec![
+                Span::styled("Total value: ", label),
+                Span::raw(format!("${}", format_decimal(stats.total_value(), 2))),
+                Span::raw(" | "),
+                Span::styled("Average: ", label),
+                Span::raw(format!("${}", format_decimal(stats.average_value(), 2))),
+            ]));
+            lines.push(Line::from(vec![
+                Span::styled("Largest:     ", label),
+                Span::raw(format!("${} by ", format_decimal(stats.largest_value, 2))),
+                Span::styled(stats.largest_trader.as_str(), Style::default().fg(app.theme.accent)),
+            ]));
+            lines.push(Line::from(vec![
+                Span::styled("First trade: ", label),
+                Span::raw(timestamp(stats.first_trade_at)),
+            ]));
+            lines.push(Line::from(vec![
+                Span::styled("Last trade:  ", label),
+                Span::raw(timestamp(stats.last_trade_at)),
+            ]));
+        }
+        None => lines.push(Line::from(Span::styled(format!("No trades for {} yet", coin), label))),
+    }
+
+    if let Some(span) = session.price_span(coin) {
+        let color = if span.change() >= Decimal::ZERO { app.theme.positive } else { app.theme.negative };
+        let percent = span
+            .change_percent()
+            .map(|percent| format!(" ({:+.2}%)", percent))
+            .unwrap_or_default();
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("Price:       ", label),
+            Span::raw(format!("${} → ${}", format_price(span.first_price), format_price(span.last_price))),
+            Span::styled(percent, Style::default().fg(color)),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("             ", label),
+            Span::styled(
+                format!("{} to {}", span.first_at.format(app.time_format()), span.last_at.format(app.time_format())),
+                label,
+            ),
+        ]));
+    }
+
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("{} - Session Stats (Esc: Close)", coin)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
+
+fn draw_mute_list(f: &mut Frame, app: &App) {
+    let area = centered_rect(50, 60, f.area());
+    let entries = app.mute_entries();
+
+    let items: Vec<ListItem> = if entries.is_empty() {
+        vec![ListItem::new(Span::styled("Nothing muted", Style::default().fg(app.theme.muted)))]
+    } else {
+        entries
+            .iter()
+            .enumerate()
+            .map(|(i, (kind, value))| {
+                let label = match kind {
+                    MuteKind::Coin => "Coin  ",
+                    MuteKind::Trader => "Trader",
+                };
+                let item = ListItem::new(Line::from(vec![
+                    Span::styled(label, Style::default().fg(app.theme.muted)),
+                    Span::raw("  "),
+                    Span::styled(value.as_str(), Style::default().fg(app.theme.text).add_modifier(Modifier::BOLD)),
+                ]));
+                if i == app.mute_list_index {
+                    item.style(selected_row_style(&app.theme))
+                } else {
+                    item
+                }
+            })
+            .collect()
+    };
+
+    let mute_list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Muted ({}) - Enter/d: Unmute | Esc: Close", entries.len())));
+    f.render_widget(Clear, area);
+    f.render_widget(mute_list, area);
+}
+
+fn draw_follow_list(f: &mut Frame, app: &App) {
+    let area = centered_rect(50, 60, f.area());
+    let entries = app.follow_entries();
+
+    let items: Vec<ListItem> = if entries.is_empty() {
+        vec![ListItem::new(Span::styled("Not following anyone", Style::default().fg(app.theme.muted)))]
+    } else {
+        entries
+            .iter()
+            .enumerate()
+            .map(|(i, (trader, totals))| {
+                let mut spans = vec![Span::styled(
+                    trader.as_str(),
+                    Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD),
+                )];
+                match totals {
+                    Some(totals) => spans.extend([
+                        Span::raw(format!("  {} trades  ", totals.trade_count)),
+                        Span::styled(format!("buy ${}", format_decimal(totals.buy_value, 2)), Style::default().fg(app.theme.positive)),
+                        Span::raw(" / "),
+                        Span::styled(format!("sell ${}", format_decimal(totals.sell_value, 2)), Style::default().fg(app.theme.negative)),
+                    ]),
+                    None => spans.push(Span::styled("  no trades this session", Style::default().fg(app.theme.muted))),
+                }
+                let item = ListItem::new(Line::from(spans));
+                if i == app.follow_list_index {
+                    item.style(selected_row_style(&app.theme))
+                } else {
+                    item
+                }
+            })
+            .collect()
+    };
+
+    let follow_list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Following ({}) - Enter/d: Unfollow | Esc: Close", entries.len())));
+    f.render_widget(Clear, area);
+    f.render_widget(follow_list, area);
+}
+
+/// Bookmarked trades, whatever the filters, including those the trades
+/// buffer has already dropped.
+fn draw_bookmark_list(f: &mut Frame, app: &App) {
+    let area = centered_rect(70, 60, f.area());
+    let visible_height = area.height.saturating_sub(2) as usize;
+    // Keep the cursor in view by scrolling the window with it
+    let start_idx = app.bookmark_list_index.saturating_sub(visible_height.saturating_sub(1));
+    let end_idx = (start_idx + visible_height).min(app.bookmarks.len());
+
+    let items: Vec<ListItem> = if app.bookmarks.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "No bookmarks yet - press * on a trade to bookmark it",
+            Style::default().fg(app.theme.muted),
+        ))]
+    } else {
+        app.bookmarks[start_idx..end_idx]
+            .iter()
+            .enumerate()
+            .map(|(i, trade)| {
+                let side_color = if trade.data.trade_type == "BUY" { app.theme.positive } else { app.theme.negative };
+                let mut spans = vec![
+                    Span::styled("◆ ", Style::default().fg(app.theme.special)),
+                    Span::styled(trade.summary(), Style::default().fg(side_color)),
+                ];
+                if !app.is_live(trade) {
+                    spans.push(Span::styled("  (no longer in the feed)", Style::default().fg(app.theme.dim)));
+                }
+                let item = ListItem::new(Line::from(spans));
+                if start_idx + i == app.bookmark_list_index {
+                    item.style(selected_row_style(&app.theme))
+                } else {
+                    item
+                }
+            })
+            .collect()
+    };
+
+    let bookmark_list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Bookmarks ({}) - Enter/d: Remove | Esc: Close", app.bookmarks.len())));
+    f.render_widget(Clear, area);
+    f.render_widget(bookmark_list, area);
+}
+
+fn severity_color(theme: &Theme, severity: Severity) -> Color {
+    match severity {
+        Severity::Info => theme.muted,
+        Severity::Warning => theme.highlight,
+        Severity::Error => theme.negative,
+    }
+}
+
+fn draw_event_log(f: &mut Frame, app: &App) {
+    let area = centered_rect(80, 70, f.area());
+    let entries = app.events.entries();
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let start_idx = app.event_log_scroll.min(entries.len());
+    let end_idx = (start_idx + visible_height).min(entries.len());
+
+    let items: Vec<ListItem> = if entries.is_empty() {
+        vec![ListItem::new(Span::styled("Nothing has happened yet", Style::default().fg(app.theme.muted)))]
+    } else {
+        entries
+            .range(start_idx..end_idx)
+            .map(|event| {
+                let color = severity_color(&app.theme, event.severity);
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("[{}] ", event.at.format(app.time_format())), Style::default().fg(app.theme.dim)),
+                    Span::styled(format!("{:<5} ", event.severity.label()), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                    Span::styled(format!("{:<8}", event.source.label()), Style::default().fg(app.theme.accent)),
+                    Span::styled(event.message.as_str(), Style::default().fg(color)),
+                ]))
+            })
+            .collect()
+    };
+
+    let event_list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Event Log ({}) - ↑/↓: Scroll | Esc: Close", entries.len())),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(event_list, area);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+/// The `:` command line or `/` search input, drawn in place of the help bar
+/// while it's open.
+fn draw_command_line(f: &mut Frame, app: &App, area: Rect) {
+    let (title, prompt) = match app.input_mode {
+        InputMode::Search => ("Search - Enter: Keep, then n/N to step | Esc: Clear", '/'),
+        _ => ("Command - Enter: Run | Tab: Complete | Esc: Cancel", ':'),
+    };
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.highlight))
+        .title(title);
+    if let Some(ref error) = app.command_error {
+        block = block.title(Line::from(Span::styled(
+            format!(" {} ", error),
+            Style::default().fg(app.theme.negative).add_modifier(Modifier::BOLD),
+        )).alignment(Alignment::Right));
+    }
+    let line = Paragraph::new(format!("{}{}", prompt, app.input_buffer)).block(block);
+    f.render_widget(line, area);
+}
+
+// How long the newest alert or status message stays visible in the help bar
+const ALERT_DISPLAY_SECS: i64 = 10;
+
+const STATUS_SEPARATOR: &str = " │ ";
+
+/// How many of `items`, taken in order, fit in `width` columns once joined
+/// with separators.
+pub fn status_items_that_fit(items: &[String], width: usize) -> usize {
+    let mut used = 0;
+    for (i, item) in items.iter().enumerate() {
+        used += item.width() + if i > 0 { STATUS_SEPARATOR.width() } else { 0 };
+        if used > width {
+            return i;
+        }
+    }
+    items.len()
+}
+
+/// One line of feed health: connection, message rate, buffer fill,
+/// reconnects, dropped and repaired messages and session uptime. The items are in order of importance, so
+/// a narrow terminal drops them from the end.
+fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
+    let metrics = &app.metrics;
+    let (connection, connection_color) = match (app.feed_state, app.connection_uptime()) {
+        (FeedState::Connected, Some(uptime)) => (format!("● {}", format_uptime(uptime)), app.theme.positive),
+        (FeedState::Connecting, _) => ("◌ connecting".to_string(), app.theme.highlight),
+        _ => ("○ offline".to_string(), app.theme.negative),
+    };
+    let items = [
+        connection,
+        format!("{:.1} msg/s", metrics.messages_per_second()),
+        format!("trades {}/{}", app.trades_snapshot().len(), metrics.trade_capacity),
+        format!("prices {}/{}", app.price_updates_snapshot().len(), metrics.price_capacity),
+        format!("↻ {}", metrics.reconnects()),
+        format!("bad {} / fixed {}", metrics.unreadable(), metrics.repaired()),
+        format!("up {}", format_uptime(metrics.session_uptime())),
+    ];
+    let shown = status_items_that_fit(&items, area.width.saturating_sub(1) as usize);
+
+    let mut spans = vec![Span::raw(" ")];
+    for (i, item) in items.into_iter().take(shown).enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(STATUS_SEPARATOR, Style::default().fg(app.theme.dim)));
+        }
+        let color = if i == 0 { connection_color } else { app.theme.muted };
+        spans.push(Span::styled(item, Style::default().fg(color)));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
 }
 
 fn draw_help(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    if matches!(app.input_mode, InputMode::Command | InputMode::Search) {
+        draw_command_line(f, app, area);
+        return;
+    }
     let help_text = match app.input_mode {
         InputMode::Normal => match app.current_page {
-            AppPage::Trades => "p/Click: Pages | Tab/Click: Filter | c/Click: Coin filter | t/Click: Trader filter | ↑/↓/Mouse: Scroll | q: Quit",
-            AppPage::PriceTracker => "p/Click: Pages | s/Click: Select coin | ↑/↓/Mouse: Scroll | q: Quit",
+            AppPage::Trades if !app.search_query.is_empty() => "?: Help | n/N: Next/previous match | /: Edit search | Esc: Clear search | q: Quit",
+            AppPage::Trades => "?: Help | c/t: Filter coin/trader | X: Clear filters | /: Search | Tab: All/Large | p: Pages | q: Quit",
+            AppPage::PriceTracker if app.all_coins_view => "?: Help | ↑/↓: Select | Enter: Track coin | o: Sort | a: Back | q: Quit",
+            AppPage::PriceTracker => "?: Help | s: Select coin | a: All coins | g: Chart | h: Position | p: Pages | q: Quit",
+            AppPage::Portfolio => "?: Help | b/s: Paper buy/sell | ↑/↓: Scroll orders | y: Copy order | $: Back | q: Quit",
         },
         InputMode::CoinSelection => "Enter: Confirm coin | Esc: Cancel | Backspace: Delete",
+        InputMode::CoinList => "↑/↓: Select | Enter: Track coin | / or Tab: Type symbol | Esc: Cancel",
+        InputMode::MuteList => "↑/↓: Select | Enter/d: Unmute | Esc: Close",
+        InputMode::FollowList => "↑/↓: Select | Enter/d: Unfollow | Esc: Close",
+        InputMode::BookmarkList => "↑/↓: Select | Enter/d: Remove bookmark | Esc: Close",
+        InputMode::EventLog => "↑/↓: Scroll | Esc/E: Close",
+        InputMode::Help => "↑/↓/Mouse: Scroll | ?/Esc/q: Close",
+        InputMode::ConfirmQuit => "y/Enter: Quit | n/Esc: Back",
+        InputMode::CoinStats => "Esc/S/q: Close stats",
+        InputMode::PositionEntry => "Enter: Save position | Esc: Cancel | Backspace: Delete",
+        InputMode::OrderEntry => "Enter: Place order | Tab: Complete coin | ↑/↓: Field | ←/→: Buy/Sell | Esc: Cancel",
+        InputMode::ConfirmLinkFilter => "Filter trades to the tracked coin too? y/Enter: Yes | any other key: No",
+        InputMode::ConfirmClear => match (app.clear_scope, &app.current_page) {
+            (ClearScope::All, _) => "Clear all trades and price history? y: Yes | any other key: Cancel",
+            (ClearScope::Page, AppPage::Trades) => "Clear all trades? y: Yes | any other key: Cancel",
+            (ClearScope::Page, AppPage::PriceTracker) => "Clear price history? y: Yes | any other key: Cancel",
+            (ClearScope::Page, AppPage::Portfolio) => "Reset the paper portfolio? y: Yes | any other key: Cancel",
+        },
         _ => "Enter: Confirm | Esc: Cancel | Backspace: Delete",
     };
     
+    let mut block = Block::default().borders(Borders::ALL).title("Help");
+    if let Some((ref message, at)) = app.status_message {
+        if Local::now().signed_duration_since(at).num_seconds() < ALERT_DISPLAY_SECS {
+            block = block.title(Span::styled(
+                format!(" {} ", message),
+                Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD),
+            ));
+        }
+    }
+    if let Some(alert) = app.events.latest_alert() {
+        if Local::now().signed_duration_since(alert.at).num_seconds() < ALERT_DISPLAY_SECS {
+            block = block.title(Line::from(Span::styled(
+                format!(" ⚠ {} ", alert.message),
+                Style::default().fg(app.theme.special).add_modifier(Modifier::BOLD),
+            )).alignment(Alignment::Right));
+        }
+    }
+    if let Some(uptime) = app.connection_uptime().filter(|_| !app.status_bar) {
+        block = block.title_bottom(Span::styled(
+            format!(" Connected {} ", format_uptime(uptime)),
+            Style::default().fg(app.theme.positive),
+        ));
+    }
+    let unseen = app.events.unseen();
+    if unseen > 0 {
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {} new event{} - E: Event log ", unseen, if unseen == 1 { "" } else { "s" }),
+            Style::default().fg(app.theme.highlight),
+        )).alignment(Alignment::Right));
+    }
+
     let help = Paragraph::new(help_text)
-        .block(Block::default().borders(Borders::ALL).title("Help"))
-        .style(Style::default().fg(Color::Gray));
+        .block(block)
+        .style(Style::default().fg(app.theme.muted));
     f.render_widget(help, area);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncation_counts_terminal_columns() {
+        assert_eq!(truncate_with_ellipsis("whalewatcher", 12), "whalewatcher");
+        assert_eq!(truncate_with_ellipsis("whalewatcher", 11), "whalewatch…");
+        assert_eq!(truncate_with_ellipsis("whalewatcher", 1), "…");
+        assert_eq!(truncate_with_ellipsis("whalewatcher", 0), "");
+
+        // Two columns per character
+        assert_eq!(truncate_with_ellipsis("比特币大王", 10), "比特币大王");
+        assert_eq!(truncate_with_ellipsis("比特币大王", 9), "比特币大…");
+        assert_eq!(truncate_with_ellipsis("比特币大王", 8), "比特币…");
+        assert_eq!(truncate_with_ellipsis("比特币大王", 2), "…");
+
+        // A family emoji is one two-column grapheme and is never split
+        let family = "👨\u{200d}👩\u{200d}👧";
+        assert_eq!(family.width(), 2);
+        let doubled = format!("{}{}coin", family, family);
+        assert_eq!(truncate_with_ellipsis(&doubled, 5), format!("{}{}…", family, family));
+        assert_eq!(truncate_with_ellipsis(&doubled, 4), format!("{}…", family));
+        assert_eq!(truncate_with_ellipsis(&format!("a{}", family), 2), "a…");
+    }
+
+    #[test]
+    fn numbers_at_the_edges_stay_readable() {
+        assert_eq!(format_decimal(Decimal::new(42, 4), 8), "0.00420000");
+        assert_eq!(format_decimal(Decimal::new(1, 28), 8), "0.00000000");
+        assert_eq!(format_decimal(Decimal::MAX, 8), "7.92e28");
+        // Rounded, not cut off
+        assert_eq!(format_decimal(Decimal::new(1999, 3), 2), "2.00");
+        assert_eq!(format_decimal(Decimal::new(-1005, 3), 2), "-1.01");
+
+        assert_eq!(format_price(Decimal::new(1, 28)), "0.000000000000");
+        assert_eq!(format_price(Decimal::new(-25, 0) * Decimal::from(100_000_000_000_000_000u64)), "-2.50e18");
+
+        assert_eq!(format_compact_value(2.5e12), "$2.50T");
+        assert_eq!(format_compact_value(1e300), "$1.00e300");
+        assert_eq!(format_compact_value(f64::NAN), "$—");
+        assert_eq!(format_net_flow(Decimal::MIN), "-$7.92e28");
+
+        assert_eq!(format_change(-0.0), "+0.00%");
+        assert_eq!(format_change(-3.456), "-3.46%");
+        assert_eq!(format_change(f64::INFINITY), "—");
+        let theme = Theme::DARK;
+        assert_eq!(change_color(&theme, 0.0), theme.positive);
+        assert_eq!(change_color(&theme, -0.5), theme.negative);
+        assert_eq!(change_color(&theme, f64::NAN), theme.dim);
+        assert_eq!(change_color(&theme, f64::NEG_INFINITY), theme.dim);
+    }
+
+    #[test]
+    fn status_items_drop_from_the_end() {
+        let items: Vec<String> = ["● 00:12:03", "12.3 msg/s", "trades 873/1000"].map(String::from).to_vec();
+        assert_eq!(status_items_that_fit(&items, 80), 3);
+        // 10 + 3 + 10 + 3 + 15
+        assert_eq!(status_items_that_fit(&items, 41), 3);
+        assert_eq!(status_items_that_fit(&items, 40), 2);
+        assert_eq!(status_items_that_fit(&items, 10), 1);
+        assert_eq!(status_items_that_fit(&items, 9), 0);
+    }
+
+    #[test]
+    fn uptime_reads_as_hours_minutes_seconds() {
+        assert_eq!(format_uptime(Duration::from_millis(999)), "00:00:00");
+        assert_eq!(format_uptime(Duration::from_secs(3 * 3600 + 25 * 60 + 7)), "03:25:07");
+        assert_eq!(format_uptime(Duration::from_secs(100 * 3600 + 59)), "100:00:59");
+    }
+
+    #[test]
+    fn fitted_text_fills_the_column_exactly() {
+        assert_eq!(fit_to_width("PEPE", 6), "PEPE  ");
+        assert_eq!(fit_to_width("ドージ", 6), "ドージ");
+        assert_eq!(fit_to_width("ドージコイン", 6), "ドー… ");
+        assert_eq!(fit_to_width("ドージコイン", 6).width(), 6);
+    }
+}
@@ -0,0 +1,116 @@
+//! How loudly a trade is drawn for its value, so whales stand out without
+//! reading the numbers. Tiers only ever strengthen the buy/sell color a
+//! span already has, never replace it.
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ValueTier {
+    Small,
+    Medium,
+    Large,
+    Huge,
+}
+
+/// Lowest value, in dollars, of each tier above `Small`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValueBreakpoints {
+    pub medium: f64,
+    pub large: f64,
+    pub huge: f64,
+}
+
+impl Default for ValueBreakpoints {
+    fn default() -> Self {
+        Self { medium: 100.0, large: 1_000.0, huge: 10_000.0 }
+    }
+}
+
+impl ValueBreakpoints {
+    pub fn tier(&self, value: f64) -> ValueTier {
+        if value >= self.huge {
+            ValueTier::Huge
+        } else if value >= self.large {
+            ValueTier::Large
+        } else if value >= self.medium {
+            ValueTier::Medium
+        } else {
+            ValueTier::Small
+        }
+    }
+}
+
+impl ValueTier {
+    /// Style for a span already drawn in `hue`, such as the BUY/SELL label:
+    /// large trades get the brighter shade of it.
+    pub fn hue_style(self, hue: Color) -> Style {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        if self >= ValueTier::Large {
+            style.fg(brighten(hue))
+        } else {
+            style.fg(hue)
+        }
+    }
+
+    /// Style for the value itself, in the trade's `hue` once it's large and
+    /// on a background of it once it's huge.
+    pub fn value_style(self, hue: Color) -> Style {
+        match self {
+            ValueTier::Small => Style::default(),
+            ValueTier::Medium => Style::default().add_modifier(Modifier::BOLD),
+            ValueTier::Large => Style::default().fg(brighten(hue)).add_modifier(Modifier::BOLD),
+            ValueTier::Huge => Style::default().fg(brighten(hue)).add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        }
+    }
+}
+
+/// The brighter shade of a theme color; colors without one are kept.
+pub fn brighten(color: Color) -> Color {
+    match color {
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Yellow => Color::LightYellow,
+        Color::Blue => Color::LightBlue,
+        Color::Magenta => Color::LightMagenta,
+        Color::Cyan => Color::LightCyan,
+        Color::Gray => Color::White,
+        // The light theme's green and red
+        Color::Indexed(28) => Color::Indexed(34),
+        Color::Indexed(124) => Color::Indexed(160),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakpoints_start_each_tier() {
+        let breakpoints = ValueBreakpoints::default();
+        assert_eq!(breakpoints.tier(0.0), ValueTier::Small);
+        assert_eq!(breakpoints.tier(99.99), ValueTier::Small);
+        assert_eq!(breakpoints.tier(100.0), ValueTier::Medium);
+        assert_eq!(breakpoints.tier(1_000.0), ValueTier::Large);
+        assert_eq!(breakpoints.tier(10_000.0), ValueTier::Huge);
+        assert_eq!(breakpoints.tier(f64::NAN), ValueTier::Small);
+
+        let custom: ValueBreakpoints = serde_json::from_str(r#"{"huge": 50000}"#).unwrap();
+        assert_eq!(custom.tier(20_000.0), ValueTier::Large);
+    }
+
+    #[test]
+    fn tiers_strengthen_the_buy_sell_hue() {
+        assert_eq!(ValueTier::Small.hue_style(Color::Green).fg, Some(Color::Green));
+        assert_eq!(ValueTier::Large.hue_style(Color::Red).fg, Some(Color::LightRed));
+        assert_eq!(ValueTier::Small.value_style(Color::Green), Style::default());
+        assert_eq!(ValueTier::Medium.value_style(Color::Green).fg, None);
+
+        let huge = ValueTier::Huge.value_style(Color::Indexed(28));
+        assert_eq!(huge.fg, Some(Color::Indexed(34)));
+        assert!(huge.add_modifier.contains(Modifier::REVERSED));
+        assert_eq!(brighten(Color::Rgb(1, 2, 3)), Color::Rgb(1, 2, 3));
+    }
+}
@@ -1,56 +1,144 @@
-use crate::models::{PriceUpdate, PriceWSMessage, Trade, WSMessage};
+use crate::logging::snippet;
+use crate::models::{repair_numbers, PriceUpdate, PriceWSMessage, Trade, WSMessage, PRICE_NUMBERS, TRADE_NUMBERS};
 use anyhow::Result;
 use chrono::Local;
 use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
 use serde_json::Value;
-use tokio::sync::mpsc;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, info, info_span, warn, Instrument};
 
-const WS_URL: &str = "wss://ws.rugplay.com/";
+pub const WS_URL: &str = "wss://ws.rugplay.com/";
+/// Pseudo-coin that subscribes the price feed to every coin.
+pub const GLOBAL_FEED: &str = "@global";
+/// Channels subscribed to unless the config lists others.
+pub const DEFAULT_CHANNELS: [&str; 2] = ["trades:all", "trades:large"];
+/// Message types the handler knows how to read. Anything else is reported
+/// once so new feed formats can be spotted.
+const KNOWN_MESSAGE_TYPES: [&str; 5] = ["ping", "error", "price_update", "all-trades", "live-trade"];
+/// Unknown message types, and separately unknown trade fields, reported
+/// per connection and listed by the app. A feed that keeps inventing names
+/// stops being reported past this.
+pub const MAX_UNKNOWN_NAMES: usize = 64;
+// Coin selections arriving closer together than this are coalesced so only
+// the last one is sent
+const SET_COIN_DEBOUNCE: Duration = Duration::from_millis(250);
 
+/// Something the feed reported that isn't trade or price data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedNotice {
+    /// An `error` message, e.g. an unknown symbol after set_coin
+    ServerError(String),
+    /// First message seen with a type the handler doesn't know
+    UnknownMessageType(String),
+    /// A trade or price message that didn't have the expected shape
+    ParseError(String),
+    /// A trade or price message kept after missing or negative numbers in
+    /// it were set to 0
+    Repaired(String),
+    /// Trade fields seen for the first time that the app doesn't know about
+    UnknownTradeFields(Vec<String>),
+}
+
+/// Where the feed connection stands.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedState {
+    Connecting,
+    Connected,
+    Disconnected,
+}
+
+/// Connects to `url`, subscribes to `channels` and forwards what arrives
+/// until the server closes the connection or `coin_rx` is closed, so
+/// dropping the coin sender shuts the handler down. `state_tx` is set to
+/// `Connected` once subscribed and to `Disconnected` when the handler ends.
 pub async fn websocket_handler(
+    url: &str,
+    channels: &[String],
     trade_tx: mpsc::Sender<Trade>, 
     price_tx: mpsc::Sender<PriceUpdate>,
+    notice_tx: mpsc::Sender<FeedNotice>,
+    state_tx: watch::Sender<FeedState>,
+    coin_rx: mpsc::Receiver<String>
+) -> Result<()> {
+    let result = run_feed(url, channels, trade_tx, price_tx, notice_tx, &state_tx, coin_rx)
+        .instrument(info_span!("feed", url))
+        .await;
+    match &result {
+        Ok(()) => info!(url, "feed closed"),
+        Err(e) => warn!(url, error = %e, "feed failed"),
+    }
+    state_tx.send_replace(FeedState::Disconnected);
+    result
+}
+
+async fn run_feed(
+    url: &str,
+    channels: &[String],
+    trade_tx: mpsc::Sender<Trade>,
+    price_tx: mpsc::Sender<PriceUpdate>,
+    notice_tx: mpsc::Sender<FeedNotice>,
+    state_tx: &watch::Sender<FeedState>,
     mut coin_rx: mpsc::Receiver<String>
 ) -> Result<()> {
-    let (ws_stream, _) = connect_async(WS_URL).await?;
+    info!("connecting");
+    let (ws_stream, _) = connect_async(url).await?;
     let (mut write, mut read) = ws_stream.split();
 
     // Subscribe to channels
-    let subscribe_all = serde_json::json!({
-        "type": "subscribe",
-        "channel": "trades:all"
-    });
-    let subscribe_large = serde_json::json!({
-        "type": "subscribe",
-        "channel": "trades:large"
-    });
+    for channel in channels {
+        let subscribe = serde_json::json!({
+            "type": "subscribe",
+            "channel": channel
+        });
+        write.send(Message::Text(subscribe.to_string().into())).await?;
+        debug!(channel = %channel, "sent subscribe");
+    }
     let set_coin = serde_json::json!({
         "type": "set_coin",
-        "coinSymbol": "@global"
+        "coinSymbol": GLOBAL_FEED
     });
-
-    write.send(Message::Text(subscribe_all.to_string().into())).await?;
-    write.send(Message::Text(subscribe_large.to_string().into())).await?;
     write.send(Message::Text(set_coin.to_string().into())).await?;
+    debug!(coin = GLOBAL_FEED, "sent set_coin");
+    state_tx.send_replace(FeedState::Connected);
+    info!("connected");
+
+    let mut unknown_types = HashSet::new();
+    let mut unknown_fields = HashSet::new();
+    let mut pending_coin: Option<String> = None;
+    let mut send_coin_at = Instant::now();
 
     loop {
         tokio::select! {
-            // Handle coin selection updates
+            // Handle coin selection updates. Each new selection replaces the
+            // pending one and restarts the debounce window.
             coin_symbol = coin_rx.recv() => {
                 match coin_symbol {
                     Some(symbol) => {
-                        let set_coin_msg = serde_json::json!({
-                            "type": "set_coin",
-                            "coinSymbol": symbol
-                        });
-                        if let Err(_) = write.send(Message::Text(set_coin_msg.to_string().into())).await {
-                            break;
-                        }
+                        pending_coin = Some(symbol);
+                        send_coin_at = Instant::now() + SET_COIN_DEBOUNCE;
                     }
                     None => break, // Channel closed
                 }
             }
+
+            // Send the latest selection once it has settled
+            _ = tokio::time::sleep_until(send_coin_at), if pending_coin.is_some() => {
+                let coin = pending_coin.take();
+                let set_coin_msg = serde_json::json!({
+                    "type": "set_coin",
+                    "coinSymbol": coin
+                });
+                if write.send(Message::Text(set_coin_msg.to_string().into())).await.is_err() {
+                    break;
+                }
+                debug!(coin = coin.as_deref().unwrap_or_default(), "sent set_coin");
+            }
             
             // Handle incoming WebSocket messages
             msg = read.next() => {
@@ -58,49 +146,96 @@ pub async fn websocket_handler(
                     Some(Ok(Message::Text(text))) => {
                         // Try to parse as generic JSON first to check the type
                         if let Ok(value) = serde_json::from_str::<Value>(&text) {
-                            if let Some(msg_type) = value.get("type").and_then(|v| v.as_str()) {
+                            if let Some(msg_type) = value.get("type").and_then(|v| v.as_str()).map(str::to_string) {
+                                let msg_type = msg_type.as_str();
                                 match msg_type {
                                     "ping" => {
                                         // Respond to ping with pong
                                         let pong_msg = serde_json::json!({
                                             "type": "pong"
                                         });
-                                        if let Err(_) = write.send(Message::Text(pong_msg.to_string().into())).await {
+                                        if write.send(Message::Text(pong_msg.to_string().into())).await.is_err() {
                                             break;
                                         }
+                                        debug!("sent pong");
+                                    }
+                                    "error" => {
+                                        // e.g. an unknown symbol after set_coin
+                                        let message = ["message", "error", "msg"]
+                                            .iter()
+                                            .find_map(|field| value.get(*field).and_then(|v| v.as_str()))
+                                            .map(str::to_string)
+                                            .unwrap_or_else(|| text.to_string());
+                                        warn!(message = %message, "server error");
+                                        let _ = notice_tx.send(FeedNotice::ServerError(message)).await;
                                     }
                                     "price_update" => {
-                                        if let Ok(price_msg) = serde_json::from_str::<PriceWSMessage>(&text) {
-                                            let price_update = PriceUpdate {
-                                                coin_symbol: price_msg.coin_symbol,
-                                                current_price: price_msg.current_price,
-                                                market_cap: price_msg.market_cap,
-                                                change_24h: price_msg.change_24h,
-                                                volume_24h: price_msg.volume_24h,
-                                                pool_coin_amount: price_msg.pool_coin_amount,
-                                                pool_base_currency_amount: price_msg.pool_base_currency_amount,
-                                                received_at: Local::now(),
-                                            };
-                                            let _ = price_tx.send(price_update).await;
+                                        match read_price_update(value) {
+                                            Ok((price_update, repaired)) => {
+                                                if !repaired.is_empty() {
+                                                    warn!(fields = ?repaired, payload = %snippet(&text), "repaired price_update");
+                                                    let _ = notice_tx.send(FeedNotice::Repaired(format!("price_update {}: {}", price_update.coin_symbol, repaired.join(", ")))).await;
+                                                }
+                                                let _ = price_tx.send(price_update).await;
+                                            }
+                                            Err(e) => {
+                                                warn!(error = %e, payload = %snippet(&text), "unreadable price_update");
+                                                let _ = notice_tx.send(FeedNotice::ParseError(format!("price_update: {}", e))).await;
+                                            }
                                         }
                                     }
                                     _ => {
-                                        // Try to parse as trade message
-                                        if let Ok(ws_msg) = serde_json::from_str::<WSMessage>(&text) {
-                                            let trade = Trade {
-                                                msg_type: ws_msg.msg_type,
-                                                data: ws_msg.data,
-                                                received_at: Local::now(),
-                                            };
-                                            let _ = trade_tx.send(trade).await;
+                                        if !KNOWN_MESSAGE_TYPES.contains(&msg_type) && unknown_types.len() < MAX_UNKNOWN_NAMES && unknown_types.insert(msg_type.to_string()) {
+                                            info!(msg_type, payload = %snippet(&text), "unknown message type");
+                                            let _ = notice_tx.send(FeedNotice::UnknownMessageType(msg_type.to_string())).await;
+                                        }
+                                        // Try to parse as trade message. Unknown types
+                                        // aren't expected to fit, so only the trade
+                                        // channels report a failure.
+                                        match read_trade(value, KNOWN_MESSAGE_TYPES.contains(&msg_type)) {
+                                            Ok((trade, repaired)) => {
+                                                if !repaired.is_empty() {
+                                                    warn!(msg_type, fields = ?repaired, payload = %snippet(&text), "repaired trade");
+                                                    let _ = notice_tx.send(FeedNotice::Repaired(format!("{} by {}: {}", msg_type, trade.data.username, repaired.join(", ")))).await;
+                                                }
+                                                let new_fields: Vec<String> = trade.data.extra.keys().filter(|field| unknown_fields.len() < MAX_UNKNOWN_NAMES && unknown_fields.insert(field.to_string())).cloned().collect();
+                                                if !new_fields.is_empty() {
+                                                    info!(fields = ?new_fields, payload = %snippet(&text), "unknown trade fields");
+                                                    let _ = notice_tx.send(FeedNotice::UnknownTradeFields(new_fields)).await;
+                                                }
+                                                let _ = trade_tx.send(trade).await;
+                                            }
+                                            Err(e) if KNOWN_MESSAGE_TYPES.contains(&msg_type) => {
+                                                warn!(msg_type, error = %e, payload = %snippet(&text), "unreadable trade");
+                                                let _ = notice_tx.send(FeedNotice::ParseError(format!("{}: {}", msg_type, e))).await;
+                                            }
+                                            Err(_) => {}
                                         }
                                     }
                                 }
                             }
+                        } else {
+                            warn!(payload = %snippet(&text), "message is not JSON");
+                        }
+                    }
+                    // Keepalive frames from the server or a proxy in between.
+                    // tungstenite queues its own reply too; answering right
+                    // away gets it out without waiting for the next write.
+                    Some(Ok(Message::Ping(payload))) => {
+                        if write.send(Message::Pong(payload)).await.is_err() {
+                            break;
                         }
+                        debug!("answered protocol ping");
+                    }
+                    Some(Ok(Message::Pong(_))) => {}
+                    Some(Ok(Message::Close(frame))) => {
+                        info!(frame = ?frame, "server closed the connection");
+                        break;
+                    }
+                    Some(Err(e)) => {
+                        warn!(error = %e, "read failed");
+                        break;
                     }
-                    Some(Ok(Message::Close(_))) => break,
-                    Some(Err(_)) => break,
                     None => break,
                     _ => {}
                 }
@@ -109,4 +244,72 @@ pub async fn websocket_handler(
     }
 
     Ok(())
+}
+
+/// A price update and the names of any numbers [`repair_numbers`] set to 0.
+fn read_price_update(mut message: Value) -> Result<(PriceUpdate, Vec<&'static str>), String> {
+    let repaired = repair_numbers(&mut message, &PRICE_NUMBERS)?;
+    let data = serde_json::from_value::<PriceWSMessage>(message).map_err(|e| e.to_string())?.data;
+    let price_update = PriceUpdate {
+        coin_symbol: data.coin_symbol,
+        current_price: data.current_price,
+        market_cap: data.market_cap,
+        change_24h: data.change_24h,
+        volume_24h: data.volume_24h,
+        pool_coin_amount: data.pool_coin_amount,
+        pool_base_currency_amount: data.pool_base_currency_amount,
+        received_at: Local::now(),
+        repaired: !repaired.is_empty(),
+    };
+    Ok((price_update, repaired))
+}
+
+/// A trade and the names of any numbers set to 0. Messages of unknown
+/// types are only taken as they are, never repaired into trades.
+fn read_trade(mut message: Value, repair: bool) -> Result<(Trade, Vec<&'static str>), String> {
+    let repaired = match message.get_mut("data") {
+        Some(data) if repair => repair_numbers(data, &TRADE_NUMBERS).map_err(|e| format!("data: {}", e))?,
+        _ => Vec::new(),
+    };
+    let ws_msg = serde_json::from_value::<WSMessage>(message).map_err(|e| e.to_string())?;
+    let received_at = Local::now();
+    let mut data = ws_msg.data;
+    // A timestamp of 0 would date the trade to 1970; when it arrived is the
+    // closest guess
+    if repaired.contains(&"timestamp") {
+        data.timestamp = received_at.timestamp_millis();
+    }
+    let trade = Trade {
+        is_large: ws_msg.msg_type == "live-trade",
+        msg_type: ws_msg.msg_type,
+        data,
+        received_at,
+        repaired: !repaired.is_empty(),
+    };
+    Ok((trade, repaired))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn a_trade_without_a_timestamp_is_dated_when_it_arrived() {
+        let message = json!({
+            "type": "all-trades",
+            "data": {
+                "type": "BUY",
+                "username": "alice",
+                "amount": 1,
+                "coinSymbol": "PEPE",
+                "totalValue": 1,
+                "price": 1,
+            }
+        });
+        let (trade, repaired) = read_trade(message, true).unwrap();
+        assert_eq!(repaired, ["timestamp"]);
+        assert!(trade.repaired);
+        assert_eq!(trade.data.timestamp, trade.received_at.timestamp_millis());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,62 @@
+//! Requests against the `--serve` endpoint over a plain TCP socket.
+
+use rugplay_terminal::models::{PriceUpdate, Trade};
+use rugplay_terminal::server;
+use rugplay_terminal::store::SharedBuffer;
+use rugplay_terminal::websocket::FeedState;
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::time::timeout;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+
+/// Sends a bare HTTP/1.1 request and returns the status code and JSON body.
+async fn request(addr: SocketAddr, method: &str, path: &str) -> (u16, Value) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!("{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", method, path);
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    timeout(TIMEOUT, stream.read_to_string(&mut response)).await.unwrap().unwrap();
+
+    let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+    let body = response.split_once("\r\n\r\n").map_or("", |(_, body)| body);
+    (status, serde_json::from_str(body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn serves_buffer_snapshots_and_feed_health() {
+    let trades = Arc::new(SharedBuffer::new());
+    trades.modify(|items| items.push_back(Trade::sample("PEPE", "alice")));
+    trades.publish();
+    let price_updates: Arc<SharedBuffer<PriceUpdate>> = Arc::new(SharedBuffer::new());
+    let (state_tx, state_rx) = watch::channel(FeedState::Connecting);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(server::serve(listener, trades, price_updates, state_rx));
+
+    let (status, body) = request(addr, "GET", "/trades").await;
+    assert_eq!(status, 200);
+    assert_eq!(body[0]["data"]["coinSymbol"], "PEPE");
+
+    let (status, body) = request(addr, "GET", "/prices").await;
+    assert_eq!((status, body), (200, Value::Array(Vec::new())));
+
+    let (status, body) = request(addr, "GET", "/health").await;
+    assert_eq!((status, &body["feed"]), (503, &Value::from("connecting")));
+    assert_eq!(body["trades"], 1);
+
+    state_tx.send_replace(FeedState::Connected);
+    let (status, _) = request(addr, "GET", "/health").await;
+    assert_eq!(status, 200);
+
+    // Read-only: nothing accepts writes
+    let (status, _) = request(addr, "POST", "/trades").await;
+    assert_eq!(status, 405);
+}
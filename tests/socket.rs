@@ -0,0 +1,78 @@
+//! Clients of the `--socket` trade stream.
+#![cfg(unix)]
+
+use rugplay_terminal::models::Trade;
+use rugplay_terminal::socket;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, timeout};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn trade(username: &str) -> Trade {
+    let mut trade = Trade::sample("PEPE", username);
+    trade.data.total_value = Decimal::new(42, 2);
+    trade
+}
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rug-listener-{}-{}.sock", name, std::process::id()))
+}
+
+#[tokio::test]
+async fn every_client_gets_each_new_trade_as_a_json_line() {
+    let path = socket_path("stream");
+    let (listener, file) = socket::bind(&path).unwrap();
+    let (trades, _) = broadcast::channel(socket::CHANNEL_CAPACITY);
+    tokio::spawn(socket::serve(listener, trades.clone()));
+
+    let mut clients = Vec::new();
+    for _ in 0..2 {
+        clients.push(BufReader::new(UnixStream::connect(&path).await.unwrap()).lines());
+    }
+    // Clients only see trades sent after they're accepted
+    timeout(TIMEOUT, async {
+        while trades.receiver_count() < 2 {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    trades.send(trade("alice")).unwrap();
+    trades.send(trade("bob")).unwrap();
+    for client in &mut clients {
+        for username in ["alice", "bob"] {
+            let line = timeout(TIMEOUT, client.next_line()).await.unwrap().unwrap().unwrap();
+            let value: Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(value["data"]["username"], username);
+            assert_eq!(value["data"]["totalValue"], 0.42);
+        }
+    }
+
+    // A client leaving doesn't affect the others
+    drop(clients.pop());
+    trades.send(trade("carol")).unwrap();
+    let line = timeout(TIMEOUT, clients[0].next_line()).await.unwrap().unwrap().unwrap();
+    assert!(line.contains("carol"));
+
+    drop(file);
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn a_stale_socket_file_is_replaced_but_a_live_one_is_not() {
+    let path = socket_path("stale");
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let (_listener, _file) = socket::bind(&path).unwrap();
+    let error = socket::bind(&path).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::AddrInUse);
+    assert!(path.exists());
+}
@@ -0,0 +1,109 @@
+//! Whole frames drawn into a `TestBackend`, from unusably small terminals up
+//! to large ones.
+
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use rugplay_terminal::app::{App, QuickFilter};
+use rugplay_terminal::config::Config;
+use rugplay_terminal::models::{AppPage, Trade};
+use rugplay_terminal::store::SharedBuffer;
+use rugplay_terminal::ui;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+fn trade(i: usize) -> Trade {
+    let mut trade = Trade::sample(["PEPE", "DOGE", "BONK"][i % 3], &format!("trader{}", i));
+    trade.data.trade_type = ["BUY", "SELL"][i % 2].to_string();
+    trade.data.amount = Decimal::from(100 + i);
+    trade.data.total_value = Decimal::new(15, 1) * Decimal::from(i);
+    trade.data.price = Decimal::new(42, 4);
+    trade.data.user_id = i.to_string();
+    trade
+}
+
+fn app_with_trades(count: usize) -> App {
+    let trades = Arc::new(SharedBuffer::new());
+    trades.modify(|items| items.extend((0..count).map(trade)));
+    trades.publish();
+    App::new(trades, Arc::default(), Arc::default())
+}
+
+fn draw(app: &App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| ui::draw(f, app)).unwrap();
+    terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect()
+}
+
+#[test]
+fn every_page_draws_at_any_size() {
+    let sizes = [(1, 1), (1, 40), (160, 1), (39, 30), (120, 9), (40, 10), (80, 24), (250, 70)];
+    let mut app = app_with_trades(60);
+    app.scroll_offset = 55;
+    app.buy_sell_mix = true;
+    for split in [false, true] {
+        for page in [AppPage::Trades, AppPage::PriceTracker, AppPage::Portfolio] {
+            app.split_view = split;
+            app.show_page(page);
+            for (width, height) in sizes {
+                let screen = draw(&app, width, height);
+                let too_small = width < 40 || height < 10;
+                assert_eq!(app.too_small.get(), too_small, "{}x{}", width, height);
+                if too_small && width >= 18 {
+                    assert!(screen.contains("Terminal too small"), "{}x{}", width, height);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn resizing_forgets_the_old_layout_and_clamps_scrolling() {
+    let mut app = app_with_trades(30);
+    draw(&app, 120, 40);
+    assert!(!app.page_tabs.borrow().is_empty());
+    assert!(!app.scrollbar_tracks.borrow().is_empty());
+
+    app.scroll_offset = 500;
+    app.handle_resize();
+    assert!(app.page_tabs.borrow().is_empty());
+    assert!(app.scrollbar_tracks.borrow().is_empty());
+    assert_eq!(app.scroll_offset, 29);
+
+    // Shrinking below the minimum and back lays everything out again
+    draw(&app, 30, 5);
+    assert!(app.page_tabs.borrow().is_empty());
+    draw(&app, 120, 40);
+    assert!(!app.page_tabs.borrow().is_empty());
+}
+
+#[test]
+fn coin_symbols_and_usernames_are_clickable_where_they_are_drawn() {
+    for compact in [false, true] {
+        let mut app = app_with_trades(5);
+        app.compact_view = compact;
+        let screen = draw(&app, 120, 40);
+        let rows: Vec<String> = screen.chars().collect::<Vec<_>>().chunks(120).map(|row| row.iter().collect()).collect();
+        let regions = app.quick_filter_regions.borrow();
+        assert_eq!(regions.len(), 10, "compact: {}", compact);
+        for region in regions.iter() {
+            let area = region.area;
+            let drawn: String = rows[area.y as usize].chars().skip(area.x as usize).take(area.width as usize).collect();
+            match &region.filter {
+                QuickFilter::Coin(symbol) | QuickFilter::Trader(symbol) => assert_eq!(&drawn, symbol, "compact: {}", compact),
+            }
+        }
+    }
+}
+
+#[test]
+fn a_row_template_draws_one_line_per_trade() {
+    let mut app = app_with_trades(3);
+    let config: Config = serde_json::from_str(r#"{"trade_row_template": "{type} {coin} for {value} by {user}"}"#).unwrap();
+    app.apply_config(config, None);
+    let screen = draw(&app, 120, 40);
+    let rows: Vec<String> = screen.chars().collect::<Vec<_>>().chunks(120).map(|row| row.iter().collect()).collect();
+    let first = rows.iter().position(|row| row.contains("trader0")).expect("templated row drawn");
+    assert!(rows[first].contains("BUY PEPE for $0.00 by trader0"), "{}", rows[first]);
+    assert!(rows[first + 1].contains("SELL DOGE for $1.50 by trader1"), "{}", rows[first + 1]);
+    assert_eq!(app.quick_filter_regions.borrow().len(), 6);
+}
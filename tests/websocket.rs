@@ -0,0 +1,164 @@
+//! Drives `websocket_handler` against a local mock server instead of
+//! rugplay.com.
+
+use futures_util::{SinkExt, StreamExt};
+use rugplay_terminal::websocket::{websocket_handler, FeedNotice, FeedState};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn price_update_message() -> Value {
+    json!({
+        "type": "price_update",
+        "coinSymbol": "PEPE",
+        "currentPrice": 0.0042,
+        "marketCap": 42000.0,
+        "change24h": 12.5,
+        "volume24h": 1500.0,
+        "poolCoinAmount": 1000000.0,
+        "poolBaseCurrencyAmount": 4200.0
+    })
+}
+
+fn trade_message() -> Value {
+    json!({
+        "type": "all-trades",
+        "data": {
+            "type": "BUY",
+            "username": "alice",
+            "userImage": "",
+            "amount": 100.0,
+            "coinSymbol": "PEPE",
+            "coinName": "Pepe",
+            "coinIcon": "",
+            "totalValue": 0.42,
+            "price": 0.0042,
+            "timestamp": 1700000000000i64,
+            "userId": "42",
+            "tags": ["whale"]
+        }
+    })
+}
+
+async fn recv_json<S>(read: &mut S) -> Value
+where
+    S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    loop {
+        let message = timeout(TIMEOUT, read.next())
+            .await
+            .expect("timed out waiting for the client")
+            .expect("client closed the connection")
+            .expect("websocket error");
+        if let Message::Text(text) = message {
+            return serde_json::from_str(&text).expect("client sent invalid JSON");
+        }
+    }
+}
+
+#[tokio::test]
+async fn forwards_feed_messages_and_answers_pings() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (pong_tx, pong_rx) = oneshot::channel();
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let (mut write, mut read) = ws.split();
+
+        let mut subscriptions = Vec::new();
+        for _ in 0..4 {
+            subscriptions.push(recv_json(&mut read).await);
+        }
+        assert_eq!(subscriptions[0], json!({ "type": "subscribe", "channel": "trades:all" }));
+        assert_eq!(subscriptions[1], json!({ "type": "subscribe", "channel": "trades:large" }));
+        assert_eq!(subscriptions[2], json!({ "type": "subscribe", "channel": "trades:verified" }));
+        assert_eq!(subscriptions[3], json!({ "type": "set_coin", "coinSymbol": "@global" }));
+
+        let unknown = json!({ "type": "leaderboard", "entries": [] });
+        let mut repaired = price_update_message();
+        repaired["volume24h"] = json!(-5);
+        repaired["poolCoinAmount"] = Value::Null;
+        let mut unreadable = price_update_message();
+        unreadable["currentPrice"] = json!("NaN");
+        let messages = [price_update_message(), unknown.clone(), unknown, trade_message(), repaired, unreadable, json!({ "type": "ping" })];
+        for message in messages {
+            write.send(Message::Text(message.to_string().into())).await.unwrap();
+        }
+        let pong = recv_json(&mut read).await;
+
+        // Protocol-level keepalive, as sent by some proxies
+        write.send(Message::Ping(b"keepalive"[..].into())).await.unwrap();
+        loop {
+            let message = timeout(TIMEOUT, read.next()).await.unwrap().unwrap().unwrap();
+            if let Message::Pong(payload) = message {
+                assert_eq!(&payload[..], b"keepalive");
+                break;
+            }
+        }
+        pong_tx.send(pong).unwrap();
+
+        // Stay connected until the client goes away
+        while let Some(Ok(_)) = read.next().await {}
+    });
+
+    let (trade_tx, mut trade_rx) = mpsc::channel(10);
+    let (price_tx, mut price_rx) = mpsc::channel(10);
+    let (notice_tx, mut notice_rx) = mpsc::channel(10);
+    let (coin_tx, coin_rx) = mpsc::channel(10);
+    let (state_tx, mut state_rx) = watch::channel(FeedState::Connecting);
+    let channels: Vec<String> = ["trades:all", "trades:large", "trades:verified"].map(String::from).to_vec();
+    let handler = tokio::spawn(async move {
+        websocket_handler(&url, &channels, trade_tx, price_tx, notice_tx, state_tx, coin_rx).await
+    });
+
+    timeout(TIMEOUT, state_rx.wait_for(|state| *state == FeedState::Connected)).await.unwrap().unwrap();
+
+    let price = timeout(TIMEOUT, price_rx.recv()).await.unwrap().unwrap();
+    assert_eq!(price.coin_symbol, "PEPE");
+    assert_eq!(price.current_price, Decimal::new(42, 4));
+    assert_eq!(price.change_24h, 12.5);
+    assert!(!price.repaired);
+
+    let trade = timeout(TIMEOUT, trade_rx.recv()).await.unwrap().unwrap();
+    assert_eq!(trade.msg_type, "all-trades");
+    assert_eq!(trade.data.username, "alice");
+    assert_eq!(trade.data.coin_symbol, "PEPE");
+    assert_eq!(trade.data.total_value, Decimal::new(42, 2));
+    assert_eq!(trade.data.extra["tags"], json!(["whale"]));
+
+    // Missing and negative numbers are set to 0 and the update kept; a NaN
+    // drops it
+    let price = timeout(TIMEOUT, price_rx.recv()).await.unwrap().unwrap();
+    assert!(price.repaired);
+    assert_eq!((price.volume_24h, price.pool_coin_amount), (Decimal::ZERO, Decimal::ZERO));
+    assert_eq!(price.current_price, Decimal::new(42, 4));
+
+    let pong = timeout(TIMEOUT, pong_rx).await.unwrap().unwrap();
+    assert_eq!(pong, json!({ "type": "pong" }));
+
+    // Unknown message types are reported once, however often they arrive
+    let notice = timeout(TIMEOUT, notice_rx.recv()).await.unwrap().unwrap();
+    assert_eq!(notice, FeedNotice::UnknownMessageType("leaderboard".to_string()));
+    let notice = timeout(TIMEOUT, notice_rx.recv()).await.unwrap().unwrap();
+    assert_eq!(notice, FeedNotice::UnknownTradeFields(vec!["tags".to_string()]));
+    let notice = timeout(TIMEOUT, notice_rx.recv()).await.unwrap().unwrap();
+    assert_eq!(notice, FeedNotice::Repaired("price_update PEPE: volume24h, poolCoinAmount".to_string()));
+    let notice = timeout(TIMEOUT, notice_rx.recv()).await.unwrap().unwrap();
+    assert_eq!(notice, FeedNotice::ParseError("price_update: non-finite currentPrice".to_string()));
+    assert!(notice_rx.try_recv().is_err());
+    assert!(price_rx.try_recv().is_err());
+
+    // Closing the coin channel is the handler's shutdown signal
+    drop(coin_tx);
+    timeout(TIMEOUT, handler).await.unwrap().unwrap().unwrap();
+    assert_eq!(*state_rx.borrow(), FeedState::Disconnected);
+    timeout(TIMEOUT, server).await.unwrap().unwrap();
+}